@@ -0,0 +1,221 @@
+use {
+    crate::error::BankError,
+
+    ed25519_dalek::{
+        Keypair,
+        Signer
+    },
+
+    solana_program::{
+        clock::UnixTimestamp,
+        ed25519_program::ID as ED25519_PROGRAM_ID,
+        instruction::Instruction,
+        program_error::ProgramError,
+        pubkey::Pubkey
+    }
+};
+
+// layout of a single-signature ed25519 verification instruction (everything stored inline)
+const ED25519_PUBKEY_OFFSET: usize = 16;
+const ED25519_SIGNATURE_OFFSET: usize = 48;
+const ED25519_MESSAGE_OFFSET: usize = 112;
+// offsets referring to the ed25519 instruction itself
+const ED25519_CURRENT_INSTRUCTION_INDEX: u16 = u16::MAX;
+
+/// Builds the MessageV2 string -> "[e<revocation_epoch>,]<to>,<mint>,<amount>,[<decimals>,]<memo>,<expires_at>" and its signed ed25519 verification instruction
+///
+/// the epoch field is omitted for the initial revocation epoch (0), the decimals field when `decimals` is None,
+/// the memo can't contain the "," separator
+pub fn create_message_v2_with_ed25519_instruction(
+    signer: &Keypair,
+    to: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    decimals: Option<u8>,
+    memo: &str,
+    revocation_epoch: u64,
+    expires_at: UnixTimestamp
+) -> Result<(String, Instruction), ProgramError> {
+    if memo.contains(",") {
+        return Err(
+            BankError::MessageV2ValidationFailed.into()
+        );
+    };
+
+    let amount = match decimals {
+        Some(decimals) => format!("{},{}", amount, decimals),
+        None => amount.to_string()
+    };
+    let mut message = format!("{},{},{},{},{}", to, mint, amount, memo, expires_at);
+    if revocation_epoch != 0 {
+        message = format!("e{},{}", revocation_epoch, message);
+    };
+
+    let instruction = create_ed25519_instruction(
+        signer,
+        message.as_bytes()
+    );
+
+    Ok((message, instruction))
+}
+
+/// Signs the message and lays it out as the ed25519 verification instruction expected by the bank program
+pub fn create_ed25519_instruction(
+    signer: &Keypair,
+    message: &[u8]
+) -> Instruction {
+    let signature = signer.sign(message).to_bytes();
+    let pubkey = signer.public.to_bytes();
+
+    let mut data = Vec::with_capacity(ED25519_MESSAGE_OFFSET + message.len());
+    // number of signatures + padding
+    data.extend_from_slice(&[1u8, 0u8]);
+
+    let offsets: [u16; 7] = [
+        ED25519_SIGNATURE_OFFSET as u16,
+        ED25519_CURRENT_INSTRUCTION_INDEX,
+        ED25519_PUBKEY_OFFSET as u16,
+        ED25519_CURRENT_INSTRUCTION_INDEX,
+        ED25519_MESSAGE_OFFSET as u16,
+        message.len() as u16,
+        ED25519_CURRENT_INSTRUCTION_INDEX
+    ];
+    for offset in offsets {
+        data.extend_from_slice(&offset.to_le_bytes());
+    };
+
+    data.extend_from_slice(&pubkey);
+    data.extend_from_slice(&signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ED25519_PROGRAM_ID,
+        accounts: vec![],
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_client {
+    use {
+        super::*,
+        crate::validator::{
+            validate_message_v2,
+            split_message_revocation_epoch
+        }
+    };
+
+    fn new_keypair() -> Keypair {
+        Keypair::from_bytes(
+            &[
+                159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
+                136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
+                8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
+            ]
+        ).unwrap()
+    }
+
+    #[test]
+    fn create_message_v2_with_ed25519_instruction_round_trip() {
+        let signer = new_keypair();
+        let to = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        let (message, instruction) = create_message_v2_with_ed25519_instruction(
+            &signer,
+            &to,
+            &mint,
+            1000,
+            None,
+            "Hello PooriaGG 😃!",
+            0,
+            4102444800
+        ).unwrap();
+
+        assert_eq!(message, format!("{},{},1000,Hello PooriaGG 😃!,4102444800", to, mint));
+        assert_eq!(instruction.program_id, ED25519_PROGRAM_ID);
+        assert_eq!(instruction.data.get(ED25519_MESSAGE_OFFSET..).unwrap(), message.as_bytes());
+
+        let message_v2 = validate_message_v2(&instruction.data).unwrap();
+
+        assert_eq!(message_v2.signer.to_bytes(), signer.public.to_bytes());
+        assert_eq!(message_v2.signature, signer.sign(message.as_bytes()).to_bytes());
+        assert_eq!(message_v2.to, to);
+        assert_eq!(message_v2.mint, mint);
+        assert_eq!(message_v2.amount, 1000);
+        assert_eq!(message_v2.decimals, None);
+        assert_eq!(message_v2.memo, "Hello PooriaGG 😃!");
+        assert_eq!(message_v2.expires_at, 4102444800);
+    }
+
+    #[test]
+    fn create_message_v2_with_ed25519_instruction_revocation_epoch() {
+        let signer = new_keypair();
+        let to = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        let (message, instruction) = create_message_v2_with_ed25519_instruction(
+            &signer,
+            &to,
+            &mint,
+            1000,
+            None,
+            "",
+            3,
+            4102444800
+        ).unwrap();
+        assert!(message.starts_with("e3,"));
+
+        let (revocation_epoch, message_data) = split_message_revocation_epoch(&instruction.data).unwrap();
+        assert_eq!(revocation_epoch, 3);
+
+        let message_v2 = validate_message_v2(&message_data).unwrap();
+        assert_eq!(message_v2.to, to);
+        assert_eq!(message_v2.mint, mint);
+    }
+
+    #[test]
+    fn create_message_v2_with_ed25519_instruction_decimals() {
+        let to = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        let (message, instruction) = create_message_v2_with_ed25519_instruction(
+            &new_keypair(),
+            &to,
+            &mint,
+            1000,
+            Some(6),
+            "memo",
+            0,
+            4102444800
+        ).unwrap();
+        assert_eq!(message, format!("{},{},1000,6,memo,4102444800", to, mint));
+
+        let message_v2 = validate_message_v2(&instruction.data).unwrap();
+        assert_eq!(message_v2.amount, 1000);
+        assert_eq!(message_v2.decimals, Some(6));
+        assert_eq!(message_v2.memo, "memo");
+        assert_eq!(message_v2.expires_at, 4102444800);
+    }
+
+    #[test]
+    fn create_message_v2_with_ed25519_instruction_fail_memo_separator() {
+        let error = create_message_v2_with_ed25519_instruction(
+            &new_keypair(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            1000,
+            None,
+            "a,b",
+            0,
+            4102444800
+        ).unwrap_err();
+
+        assert_eq!(
+            error,
+            ProgramError::Custom(
+                BankError::MessageV2ValidationFailed as u32
+            )
+        );
+    }
+}
@@ -2,12 +2,15 @@ use {
     solana_program::{
         entrypoint,
         entrypoint::ProgramResult,
+        msg,
         pubkey::Pubkey,
-        account_info::AccountInfo,
-        program_error::ProgramError
+        account_info::AccountInfo
     },
     
-    crate::processor::Processor
+    crate::{
+        processor::Processor,
+        instruction::BankInstruction
+    }
 };
 
 entrypoint!(process_instruction);
@@ -17,9 +20,10 @@ pub fn process_instruction(
     accounts_info: &[AccountInfo],
     instruction_data: &[u8]
 ) -> ProgramResult {
-    if let Err(error) = Processor::processor(program_id, accounts_info, instruction_data) {
-        return Err(error);
-    };
+    // logged before dispatching so failed transactions show which instruction they carried
+    msg!("Bank instruction discriminant: {:?}", BankInstruction::unpack_discriminant(instruction_data));
+
+    Processor::processor(program_id, accounts_info, instruction_data)?;
 
     Ok(())
 }
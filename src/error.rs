@@ -3,6 +3,7 @@ use {
         IntoPrimitive,
         TryFromPrimitive
     },
+    solana_program::program_error::ProgramError,
     
     thiserror::Error
 };
@@ -39,5 +40,168 @@ pub enum BankError {
     #[error("invalid mint account")]
     InvalidMintAccount,
     #[error("invalid associated token account for bank-account")]
-    InvalidBankAssociatedTokenAccount
+    InvalidBankAssociatedTokenAccount,
+    #[error("unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    #[error("invalid withdrawal receipt account")]
+    InvalidReceiptAccount,
+    #[error("gasless withdrawals are disabled for this bank-account")]
+    GaslessDisabled,
+    #[error("token amount exceeds the per-transaction cap of the mint")]
+    ExceedsTokenMaxPerTx,
+    #[error("bank token-account still holds a balance and no destination was provided")]
+    UnsweptTokens,
+    #[error("bank-account doesn't belong to the ed25519 message signer")]
+    SignerAccountMismatch,
+    #[error("authorization is already fully consumed")]
+    AuthorizationFullyConsumed,
+    #[error("unsupported message version")]
+    UnsupportedMessageVersion,
+    #[error("invalid payment escrow account")]
+    InvalidPaymentEscrow,
+    #[error("savings bps must be at most 10000")]
+    InvalidSavingsBps,
+    #[error("savings account isn't the bank-account designated in the config")]
+    InvalidSavingsAccount,
+    #[error("withdrawal to a new recipient exceeds the distinct recipients cap")]
+    TooManyRecipients,
+    #[error("message was signed for a revoked revocation epoch")]
+    StaleRevocationEpoch,
+    #[error("recipient is one of this program's internal accounts")]
+    InvalidRecipient,
+    #[error("fee tiers must be sorted by threshold, bounded by 10000 bps and routed to a treasury")]
+    InvalidFeeTiers,
+    #[error("fee treasury account isn't the one designated in the config")]
+    InvalidFeeTreasury,
+    #[error("memo bytes aren't valid utf-8")]
+    MemoNotUtf8,
+    #[error("bank-account can't withdraw during the grace period after its creation")]
+    WithdrawalGracePeriodActive,
+    #[error("emergency withdrawal isn't signed by enough emergency signers")]
+    EmergencyThresholdNotMet,
+    #[error("bank-account doesn't allow mints with a freeze authority")]
+    FreezableMintNotAllowed,
+    #[error("bank-account requires a non-empty memo on every withdrawal")]
+    MemoRequired,
+    #[error("invalid instructions sysvar account")]
+    InvalidInstructionsSysvarAccount,
+    #[error("insufficient spl-token balance.")]
+    InsufficientTokenBalance,
+    #[error("ed25519 withdrawals are disabled for this bank-account")]
+    Ed25519WithdrawalsDisabled,
+    #[error("bank-account must be writable")]
+    BankAccountNotWritable,
+    #[error("recipient is on the bank-account's blocked recipients list")]
+    RecipientBlocked,
+    #[error("blocked recipients list exceeds its maximum length")]
+    TooManyBlockedRecipients,
+    #[error("signed message has expired")]
+    SignatureExpired,
+    #[error("stored signatures reached their cap and none of them can be evicted yet")]
+    SignatureStoreFull,
+    #[error("funder created too many bank-accounts within the creation window")]
+    CreationRateExceeded,
+    #[error("invalid program-config account")]
+    InvalidProgramConfigAccount,
+    #[error("signer isn't the admin of the program-config")]
+    InvalidProgramConfigAdmin,
+    #[error("number of recipient accounts doesn't match the number of amounts")]
+    BatchRecipientsMismatch,
+    #[error("rent calculation overflowed")]
+    RentCalculationOverflow,
+    #[error("memo of the signed message is too long")]
+    MemoTooLong,
+    #[error("destination token-account belongs to another mint")]
+    DestinationMintMismatch,
+    #[error("decimals of the signed message don't match the mint's decimals")]
+    DecimalsMismatch,
+    #[error("bank-account is frozen, withdrawals are paused")]
+    AccountFrozen,
+    #[error("invalid message-v3 format.")]
+    MessageV3ValidationFailed,
+    #[error("relayer isn't the one named by the signed message")]
+    InvalidRelayer,
+    #[error("mint account's data isn't a valid mint")]
+    InvalidMintData,
+    #[error("token-account's data isn't a valid token-account")]
+    InvalidTokenAccountData,
+    #[error("withdrawal exceeds bank-account's daily limit for the recipient")]
+    DailyLimitExceeded,
+    #[error("signed message isn't valid utf-8")]
+    NonUtf8Message,
+    #[error("bank-account can't withdraw to itself")]
+    SelfWithdrawalNotAllowed,
+    #[error("mint isn't the native (wrapped SOL) mint")]
+    NotNativeMint,
+    #[error("funder account can't be the bank-account")]
+    InvalidFunderAccount,
+    #[error("withdrawal is below bank-account's minimum withdrawal")]
+    WithdrawalBelowMinimum,
+    #[error("message's signer isn't the pubkey the ed25519 program verified")]
+    SignerPubkeyMismatch,
+    #[error("associated-token-account program isn't the canonical one")]
+    InvalidAssociatedTokenProgram,
+    #[error("signed message's payment-escrow isn't the redeemed payment-escrow's address")]
+    PaymentEscrowMessageMismatch,
+    #[error("invalid message-v4 format.")]
+    MessageV4ValidationFailed,
+    #[error("too many withdrawals in one instruction to report their events")]
+    TooManyWithdrawals
+}
+
+impl BankError {
+    /// variant behind the code of a `ProgramError::Custom`/`InstructionError::Custom`, None -> not a bank error code
+    pub fn from_code(code: u32) -> Option<BankError> {
+        u8::try_from(code)
+            .ok()
+            .and_then(|code| BankError::try_from(code).ok())
+    }
+
+    /// human-readable meaning of the error (its `Display` message)
+    pub fn describe(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl From<BankError> for ProgramError {
+    fn from(error: BankError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
+#[cfg(test)]
+mod test_error {
+    use super::*;
+
+    #[test]
+    fn bank_error_into_program_error() {
+        let error: ProgramError = BankError::SignatureAlreadyUsed.into();
+        assert_eq!(error, ProgramError::Custom(0));
+
+        let error: ProgramError = BankError::SignerPubkeyMismatch.into();
+        assert_eq!(
+            error,
+            ProgramError::Custom(
+                BankError::SignerPubkeyMismatch as u32
+            )
+        );
+    }
+
+    #[test]
+    fn bank_error_from_code_round_trip() {
+        let variants_count = BankError::TooManyWithdrawals as u32 + 1;
+
+        for code in 0..variants_count {
+            let error = BankError::from_code(code).unwrap();
+            assert_eq!(error as u32, code, "Code mismatch.");
+            assert!(error.describe().len() > 0, "Missing description.");
+        };
+
+        assert_eq!(BankError::from_code(variants_count), None);
+        assert_eq!(BankError::from_code(u32::MAX), None);
+        assert_eq!(
+            BankError::SelfWithdrawalNotAllowed.describe(),
+            "bank-account can't withdraw to itself"
+        );
+    }
 }
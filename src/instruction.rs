@@ -7,35 +7,53 @@ use {
     solana_program::{
         program_error::ProgramError,
         pubkey::Pubkey,
+        sysvar::instructions::ID as INSTRUCTIONS_SYSVAR_ID,
+        system_program::ID as SYSTEM_PROGRAM_ID,
         instruction::{
             Instruction,
             AccountMeta
         }
+    },
+
+    crate::{
+        error::BankError,
+        processor::MEMO_PROGRAM_ID,
+        state::BankAccountConfig
     }
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, BorshDeserialize, BorshSerialize)]
+/// set on the first byte of versioned instruction-data (legacy data starts with the borsh variant index)
+pub const INSTRUCTION_VERSION_FLAG: u8 = 0x80;
+/// instruction-data version produced by `BankInstruction::pack`
+pub const CURRENT_INSTRUCTION_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, BorshDeserialize, BorshSerialize)]
 pub enum BankInstruction {
     /// create new on-chain bank account
     /// 
     /// Accounts expected by this instruction:
     /// 
-    ///     0. `[writable,signer]` funding account for new bank-account creation
-    ///     1. `[]` authority of the newly created bank-account
-    ///     2. `[writable]` new bank-account
-    ///     3. `[]` system-program account 
+    /// 0. `[writable,signer]` funding account for new bank-account creation
+    /// 1. `[signer]` authority of the newly created bank-account (may be the funding account)
+    /// 2. `[writable]` new bank-account
+    /// 3. `[]` system-program account 
+    /// 4. `[writable]` program-config account (optional, creations of the funder are counted once it's initialized)
     CreateBankAccount,
     
     /// withdraw lamports from bank-account
     /// 
     /// Accounts expected by this instruction:
     /// 
-    ///     0. `[signer]` bank-account's authority account
-    ///     1. `[writable]` bank-account
-    ///     2. `[writable]` funds recepient account
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    /// 2. `[writable]` funds recepient account
+    /// 3. `[]` memo program account (if a non-empty memo is provided)
+    /// 4. `[writable]` bank-account's withdrawal-receipt account (optional)
     WithdrawLamports {
         /// lamports to withdraw from bank-account
-        lamports: u64
+        lamports: u64,
+        /// memo of the withdrawal (sent to the memo program signed by the authority, required when the config has `require_memo`)
+        memo: Option<String>
     },
     
     /// withdraw tokens from bank-account's associated-token-account
@@ -44,35 +62,48 @@ pub enum BankInstruction {
     /// 
     /// Accounts expected by this instruction:
     /// 
-    ///     0. `[signer]` bank-account's authority account
-    ///     1. `[]` bank-account
-    ///     2. `[writable]` bank-account's associated-token-account
-    ///     3. `[]` mint account
-    ///     4. `[writable]` destination token-account
-    ///     5. `[]` token program account
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[]` bank-account
+    /// 2. `[writable]` bank-account's associated-token-account
+    /// 3. `[]` mint account
+    /// 4. `[writable]` destination token-account
+    /// 5. `[]` token program account (classic token program or Token-2022)
+    /// 6. `[]` memo program account (if a non-empty memo is provided)
+    /// 7. `[writable]` bank-account's withdrawal-receipt account (optional)
     WithdrawSplTokens {
         /// token-amount to withdraw from bank-account's associated-token-account
-        amount: u64
+        amount: u64,
+        /// memo of the withdrawal (sent to the memo program signed by the authority, required when the config has `require_memo`)
+        memo: Option<String>
     },
     
     /// withdraw lamports from bank-account's associated-token-account using ed25519 signature
     /// 
     /// previous instruction must be an ed25519-signature-verification instruction
+    ///
+    /// NOTE : When the bank-account can't cover the withdrawal no lamports move, the signature is recorded as failed (`is_ok == false`)
+    /// and the funder only pays the rent of its entry, the signature stays used (`SignatureAlreadyUsed`), the owner signs a new message to retry
     /// 
     /// Accounts expected by this instruction:
     /// 
     /// 0. `[writable]` bank-account
-    /// 1. `[writable,signer]` funder for bank-account size increase
-    /// 2. `[signer]` "to" account of the ed25519 signature
+    /// 1. `[writable,signer]` funder for bank-account size increase (signer only when bank-account's lamports above rent don't cover the growth)
+    /// 2. `[signer]` "to" account of the ed25519 signature (gasless -> not a signer, recepient must be the "to" account)
     /// 3. `[writable]` recepient account of lamports
     /// 4. `[]` system program account
-    /// 5. `[]` memo program account (if memo message provided in the message)
+    /// 5. `[]` instructions sysvar account
+    /// 6. `[]` memo program account (if memo message provided in the message, missing -> `InvalidMemoProgramAccount`; passed along an empty memo -> skipped)
+    /// 7. `[writable]` fee treasury account (if the bank-account's config has fee tiers)
+    /// 8. `[writable]` bank-account's withdrawal-receipt account (optional)
     WithdrawLamportsUsingEd25519Signature,
     
     /// withdraw tokens from bank-account's associated-token-account using ed25519 signature
     /// 
     /// NOTE : The bank-account's A.T.A for the specific spl-token must be created and initialized before invoking this instruction (owner of the A.T.A must be the authority of the bank-account)
     ///
+    /// NOTE : When the bank-account's A.T.A can't cover the withdrawal no tokens move, the signature is recorded as failed (`is_ok == false`)
+    /// and the funder only pays the rent of its entry, the signature stays used (`SignatureAlreadyUsed`), the owner signs a new message to retry
+    ///
     /// previous instruction must be an ed25519-signature-verification instruction
     /// 
     /// Accounts expected by this instruction:
@@ -80,20 +111,541 @@ pub enum BankInstruction {
     /// 0. `[]` mint-account
     /// 1. `[writable]` destination bank-account
     /// 2. `[writable]` source bank-account associated token-account
-    /// 3. `[writable,signer]` funder for bank-account size increase
-    /// 4. `[signer]` "to" account of the ed25519 signature
+    /// 3. `[writable,signer]` funder for bank-account size increase (signer only when bank-account's lamports above rent don't cover the growth)
+    /// 4. `[signer]` "to" account of the ed25519 signature (gasless -> not a signer, destination must be owned by the "to" account)
     /// 5. `[writable]` destination token-account
-    /// 6. `[]` token standard program account
+    /// 6. `[]` token standard program account (classic token program or Token-2022)
     /// 7. `[]` system program account
-    /// 8. `[]` memo program account (if memo message provided in the message)
-    WithdrawSplToknesUsingEd25519Signature
+    /// 8. `[]` instructions sysvar account
+    /// 9. `[]` memo program account (if memo message provided in the message, missing -> `InvalidMemoProgramAccount`; passed along an empty memo -> skipped)
+    /// 10. `[writable]` bank-account's withdrawal-receipt account (optional)
+    WithdrawSplToknesUsingEd25519Signature,
+
+    /// create bank-account's withdrawal-receipt account (seeds -> ["receipt", bank-account])
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` funding account for new receipt account creation
+    /// 1. `[signer]` bank-account's authority account
+    /// 2. `[]` bank-account
+    /// 3. `[writable]` new withdrawal-receipt account
+    /// 4. `[]` system-program account
+    InitReceipt,
+
+    /// update bank-account's configurations
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    /// 2. `[writable,signer]` funder for bank-account size increase
+    /// 3. `[]` system-program account
+    UpdateConfig {
+        /// new configurations of the bank-account
+        config: BankAccountConfig
+    },
+
+    /// report bank-account's rent-exemption status (borsh-serialized `RentExemptStatus` as return data)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` bank-account
+    CheckRentExempt,
+
+    /// sweep bank-account's token-accounts to the authority, close them and then close the bank-account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` bank-account's authority account (receives all lamports)
+    /// 1. `[writable]` bank-account
+    /// 2. `[]` token program account (classic token program or Token-2022)
+    /// 3. `[]` memo program account (if a non-empty memo is provided)
+    /// 4. ..`[writable]` for each entry of `sweep` -> bank-account's token-account,
+    ///    followed by its `[]` mint account and the authority's `[writable]` destination token-account if the entry is `true`
+    CloseBankAccountFull {
+        /// whether a mint account and a destination token-account follow each bank-account's token-account
+        sweep: Vec<bool>,
+        /// memo of the withdrawal (sent to the memo program signed by the authority, required when the config has `require_memo`)
+        memo: Option<String>
+    },
+
+    /// partially redeem a lamports authorization (MessageV1) using ed25519 signature,
+    /// the authorization can be redeemed over multiple transactions until its whole amount is consumed
+    ///
+    /// previous instruction must be an ed25519-signature-verification instruction
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` bank-account
+    /// 1. `[writable,signer]` funder for bank-account size increase
+    /// 2. `[signer]` "to" account of the ed25519 signature
+    /// 3. `[writable]` recepient account of lamports
+    /// 4. `[]` system program account
+    /// 5. `[]` instructions sysvar account
+    /// 6. `[writable]` bank-account's withdrawal-receipt account (optional)
+    WithdrawLamportsPartiallyUsingEd25519Signature {
+        /// amount of lamports to redeem from the authorization
+        lamports: u64
+    },
+
+    /// create a single-use payment escrow (seeds -> ["payment_escrow", bank-account, nonce]) funded by the bank-account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` funding account for new escrow account creation
+    /// 1. `[signer]` bank-account's authority account
+    /// 2. `[writable]` bank-account
+    /// 3. `[writable]` new payment-escrow account
+    /// 4. `[]` system-program account
+    InitPaymentEscrow {
+        /// nonce of the escrow's PDA
+        nonce: u64,
+        /// lamports moved from the bank-account into the escrow
+        lamports: u64,
+        /// ed25519 key whose signature redeems the escrow
+        claim_signer: Pubkey
+    },
+
+    /// redeem a payment escrow using ed25519 signature of its claim-signer (MessageV4, the message must start with the "v4" version field),
+    /// the escrow is closed afterwards
+    ///
+    /// the message's payment-escrow must be the redeemed payment-escrow's address
+    ///
+    /// previous instruction must be an ed25519-signature-verification instruction
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` payment-escrow account
+    /// 1. `[writable]` bank-account (receives the escrow's rent)
+    /// 2. `[writable]` recepient account of lamports ("to" account of the message)
+    /// 3. `[]` instructions sysvar account
+    RedeemPaymentEscrow,
+
+    /// withdraw lamports from bank-account, `savings_bps` of the amount is moved into the savings bank-account
+    /// designated in bank-account's config (`BankAccountConfig::savings_account`)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    /// 2. `[writable]` funds recepient account
+    /// 3. `[writable]` savings bank-account
+    /// 4. `[]` memo program account (if a non-empty memo is provided)
+    WithdrawWithSavings {
+        /// lamports to withdraw from bank-account (recepient + savings)
+        amount: u64,
+        /// part of the amount that goes to the savings bank-account (basis points)
+        savings_bps: u16,
+        /// memo of the withdrawal (sent to the memo program signed by the authority, required when the config has `require_memo`)
+        memo: Option<String>
+    },
+
+    /// revoke every outstanding ed25519 authorization by bumping bank-account's revocation epoch,
+    /// messages must carry the current epoch ("e<epoch>" field) afterwards
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    RevokeAll,
+
+    /// withdraw lamports from bank-account bypassing the withdrawal throttles (grace period, recipients cap),
+    /// requires `emergency_threshold` of the configured emergency signers
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    /// 2. `[writable]` funds recepient account
+    /// 3. `[]` memo program account (if a non-empty memo is provided)
+    /// 4. ..`[signer]` emergency signer accounts
+    EmergencyWithdraw {
+        /// lamports to withdraw from bank-account
+        lamports: u64,
+        /// memo of the withdrawal (sent to the memo program signed by the authority, required when the config has `require_memo`)
+        memo: Option<String>
+    },
+
+    /// shrink bank-account's data down to its current serialized size, the freed rent goes to the authority
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    CompactAccount,
+
+    /// report the amount withdrawable right now and the policy limiting it (borsh-serialized `EffectiveWithdrawable` as return data),
+    /// lamports by default or tokens when bank-account's token-account is provided,
+    /// the daily limit of lamports is the one left to the provided recipient (the whole limit without one)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` bank-account
+    /// 1. `[]` bank-account's token-account (classic token program or Token-2022) or recipient of the lamports (optional)
+    GetEffectiveWithdrawable,
+
+    /// rewrite a minimal bank-account (discriminator + authority + bump) with the full `UserBankAccount` layout,
+    /// no-op when the bank-account already holds the full layout
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    /// 2. `[writable,signer]` funder for bank-account size increase
+    /// 3. `[]` system-program account
+    MigrateBankAccount,
+
+    /// deposit lamports into bank-account, recorded in bank-account's `total_deposited`
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` funding account of the deposit
+    /// 1. `[writable]` bank-account
+    /// 2. `[]` system-program account
+    Deposit {
+        /// lamports to deposit into bank-account
+        lamports: u64
+    },
+
+    /// report the integrity issues of bank-account as a bitmask (`INTEGRITY_*` flags, single byte as return data),
+    /// detects corrupted or half-migrated bank-accounts
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` bank-account
+    VerifyIntegrity,
+
+    /// sweep the whole balance of several bank-account's associated token-accounts into their destination token-accounts,
+    /// fails atomically if any of the sweeps fails
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[]` bank-account
+    /// 2. `[]` token program account (classic token program or Token-2022)
+    /// 3. `[]` memo program account (if a non-empty memo is provided)
+    /// 4. ..`[]` mint account, `[writable]` bank-account's associated token-account and
+    ///    `[writable]` destination token-account for each swept mint
+    SweepMultipleAtas {
+        /// memo of the withdrawal (sent to the memo program signed by the authority, required when the config has `require_memo`)
+        memo: Option<String>
+    },
+
+    /// create the program-config account (seeds -> ["program_config"]), the signer becomes its admin
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` admin account (funds the program-config creation)
+    /// 1. `[writable]` new program-config account
+    /// 2. `[]` system-program account
+    InitProgramConfig {
+        /// cap on bank-accounts created by a single funder within the creation window
+        max_creations_per_funder: u16,
+        /// length of the creation window in seconds
+        creation_window_secs: u32
+    },
+
+    /// update the creation cap of the program-config
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` program-config's admin account
+    /// 1. `[writable]` program-config account
+    UpdateProgramConfig {
+        /// cap on bank-accounts created by a single funder within the creation window
+        max_creations_per_funder: u16,
+        /// length of the creation window in seconds
+        creation_window_secs: u32
+    },
+
+    /// hand bank-account over to a new authority
+    ///
+    /// NOTE : bank-account's PDA is seeded by its owner, the PDA doesn't move -> the first owner is kept as `original_owner`
+    /// and keeps seeding the PDA, the new authority must pass the bank-account's address (not its own PDA) from now on
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` bank-account's authority account (funds the bank-account size increase)
+    /// 1. `[writable]` bank-account
+    /// 2. `[]` system-program account
+    TransferAuthority {
+        /// new authority of the bank-account
+        new_authority: Pubkey
+    },
+
+    /// report bank-account's authority, creation time and signatures count (borsh-serialized `BankAccountSummary` as return data)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` bank-account
+    GetAccountInfo,
+
+    /// withdraw lamports from bank-account to several recipients at once, the total is checked against the balance before any transfer
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    /// 2. ..`[writable]` recepient account for each entry of `amounts`
+    WithdrawLamportsBatch {
+        /// lamports sent to each recepient account (same order as the accounts)
+        amounts: Vec<u64>
+    },
+
+    /// withdraw every lamport above the rent-exempt minimum from bank-account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    /// 2. `[writable]` funds recepient account
+    /// 3. `[writable]` bank-account's withdrawal-receipt account (optional)
+    WithdrawAllLamports,
+
+    /// remove signatures recorded before `cutoff` whose messages expired, shrink bank-account and refund the freed rent to the authority
+    ///
+    /// NOTE : Signatures of messages that didn't expire yet are kept whatever their age, removing them would allow replays
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    ReclaimSignatureRent {
+        /// signatures recorded before this unix-timestamp are removed
+        cutoff: i64
+    },
+
+    /// create bank-account's associated-token-account for a mint (required by the token withdrawals)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` funder of the associated-token-account's rent
+    /// 1. `[]` bank-account
+    /// 2. `[writable]` bank-account's associated-token-account
+    /// 3. `[]` mint account
+    /// 4. `[]` system program account
+    /// 5. `[]` token program account (classic token program or Token-2022)
+    /// 6. `[]` associated-token-account program account
+    CreateBankAta,
+
+    /// freeze bank-account, every withdrawal fails with `AccountFrozen` until it's unfrozen (e.g. the ed25519 signing key is suspected leaked)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    FreezeAccount,
+
+    /// unfreeze bank-account, withdrawals are allowed again
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    UnfreezeAccount,
+
+    /// withdraw lamports from bank-account using ed25519 signature (MessageV3, the message must start with the "v3" version field),
+    /// submitted by the relayer named in the message on behalf of "to"
+    ///
+    /// previous instruction must be an ed25519-signature-verification instruction
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` bank-account
+    /// 1. `[writable,signer]` relayer of the message (funder for bank-account size increase, must sign anyway)
+    /// 2. `[writable]` recepient account of lamports (the "to" account of the message)
+    /// 3. `[]` system program account
+    /// 4. `[]` instructions sysvar account
+    /// 5. `[]` memo program account (if memo message provided in the message, signed by the relayer)
+    /// 6. `[writable]` fee treasury account (if the bank-account's config has fee tiers)
+    /// 7. `[writable]` bank-account's withdrawal-receipt account (optional)
+    WithdrawLamportsRelayedUsingEd25519Signature,
+
+    /// same as `WithdrawSplToknesUsingEd25519Signature` (same accounts) but when the withdrawal empties bank-account's A.T.A
+    /// the A.T.A is closed and its rent goes to the funder
+    WithdrawSplTokensAndCloseAtaUsingEd25519Signature,
+
+    /// run the checks of an ed25519 withdrawal (message, replay, balance, ...) without recording the signature or moving funds,
+    /// writes whether the withdrawal would go through as return data (borsh `bool`)
+    ///
+    /// previous instruction must be an ed25519-signature-verification instruction
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` bank-account
+    /// 1. `[]` instructions sysvar account
+    /// 2. `[]` mint account (token withdrawals only -> MessageV2, otherwise MessageV1)
+    /// 3. `[]` bank-account's associated-token-account (token withdrawals only)
+    DryRunSignature,
+
+    /// set the cap on the lamports each recipient receives through ed25519 signatures within 24 hours (0 -> no limit)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    SetDailyLimit {
+        daily_limit: u64
+    },
+
+    /// set bank-account's label (display name for clients, all zeros -> no label)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    SetLabel {
+        label: [u8; 32]
+    },
+
+    /// withdraw wrapped SOL from bank-account's associated-token-account and unwrap it, the recipient's wSOL token-account
+    /// is closed so the recipient receives native lamports (withdrawn amount + the token-account's rent)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[]` bank-account
+    /// 2. `[writable]` bank-account's associated-token-account (native mint)
+    /// 3. `[]` native mint account
+    /// 4. `[writable]` recipient's wSOL token-account
+    /// 5. `[]` token program account (classic token program)
+    /// 6. `[writable,signer]` recipient account (owner of the wSOL token-account)
+    WithdrawAndUnwrapSol {
+        /// wrapped lamports to withdraw from bank-account's associated-token-account
+        amount: u64
+    },
+
+    /// same as `WithdrawLamportsUsingEd25519Signature` (same accounts) but the message is the `signature_index`-th signature
+    /// of the ed25519-signature-verification instruction -> one verification instruction can carry several signed messages
+    WithdrawLamportsUsingEd25519SignatureAt {
+        /// index of the signature within the ed25519-signature-verification instruction
+        signature_index: u8
+    },
+
+    /// same as `WithdrawSplToknesUsingEd25519Signature` (same accounts) but the message is the `signature_index`-th signature
+    /// of the ed25519-signature-verification instruction
+    WithdrawSplTokensUsingEd25519SignatureAt {
+        /// index of the signature within the ed25519-signature-verification instruction
+        signature_index: u8
+    },
+
+    /// set the smallest lamports amount an ed25519 lamports withdrawal may move (0 -> `MIN_ED25519_WITHDRAW_LAMPORTS`)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` bank-account's authority account
+    /// 1. `[writable]` bank-account
+    SetMinWithdrawal {
+        min_withdrawal: u64
+    },
+
+    /// create another on-chain bank account of the authority, seeds -> ["user_bank_account", authority, index]
+    /// (e.g. "savings" and "checking" accounts of one user)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable,signer]` funding account for new bank-account creation
+    /// 1. `[signer]` authority of the newly created bank-account (may be the funding account)
+    /// 2. `[writable]` new bank-account
+    /// 3. `[]` system-program account
+    /// 4. `[writable]` program-config account (optional, creations of the funder are counted once it's initialized)
+    CreateBankAccountWithIndex {
+        index: u16
+    }
 }
 
 impl BankInstruction {
+    /// versioned format -> `[INSTRUCTION_VERSION_FLAG | version, ...borsh(instruction)]`
+    ///
+    /// unversioned (legacy) format is still accepted during the transition
     pub fn unpack(instruction_data: &[u8]) -> Result<BankInstruction, ProgramError> {
-        BankInstruction::try_from_slice(instruction_data)
+        let (
+            tag,
+            rest
+        ) = instruction_data
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        if tag & INSTRUCTION_VERSION_FLAG == 0 {
+            return BankInstruction::try_from_slice(instruction_data)
+                .map_err(|_| ProgramError::InvalidInstructionData);
+        };
+
+        if tag & !INSTRUCTION_VERSION_FLAG != CURRENT_INSTRUCTION_VERSION {
+            return Err(
+                BankError::UnsupportedInstructionVersion.into()
+            );
+        };
+
+        BankInstruction::try_from_slice(rest)
             .map_err(|_| ProgramError::InvalidInstructionData)
     }
+
+    /// variant index (borsh enum index) of the instruction
+    pub fn discriminant(&self) -> u8 {
+        match self {
+            BankInstruction::CreateBankAccount => 0,
+            BankInstruction::WithdrawLamports { .. } => 1,
+            BankInstruction::WithdrawSplTokens { .. } => 2,
+            BankInstruction::WithdrawLamportsUsingEd25519Signature => 3,
+            BankInstruction::WithdrawSplToknesUsingEd25519Signature => 4,
+            BankInstruction::InitReceipt => 5,
+            BankInstruction::UpdateConfig { .. } => 6,
+            BankInstruction::CheckRentExempt => 7,
+            BankInstruction::CloseBankAccountFull { .. } => 8,
+            BankInstruction::WithdrawLamportsPartiallyUsingEd25519Signature { .. } => 9,
+            BankInstruction::InitPaymentEscrow { .. } => 10,
+            BankInstruction::RedeemPaymentEscrow => 11,
+            BankInstruction::WithdrawWithSavings { .. } => 12,
+            BankInstruction::RevokeAll => 13,
+            BankInstruction::EmergencyWithdraw { .. } => 14,
+            BankInstruction::CompactAccount => 15,
+            BankInstruction::GetEffectiveWithdrawable => 16,
+            BankInstruction::MigrateBankAccount => 17,
+            BankInstruction::Deposit { .. } => 18,
+            BankInstruction::VerifyIntegrity => 19,
+            BankInstruction::SweepMultipleAtas { .. } => 20,
+            BankInstruction::InitProgramConfig { .. } => 21,
+            BankInstruction::UpdateProgramConfig { .. } => 22,
+            BankInstruction::TransferAuthority { .. } => 23,
+            BankInstruction::GetAccountInfo => 24,
+            BankInstruction::WithdrawLamportsBatch { .. } => 25,
+            BankInstruction::WithdrawAllLamports => 26,
+            BankInstruction::ReclaimSignatureRent { .. } => 27,
+            BankInstruction::CreateBankAta => 28,
+            BankInstruction::FreezeAccount => 29,
+            BankInstruction::UnfreezeAccount => 30,
+            BankInstruction::WithdrawLamportsRelayedUsingEd25519Signature => 31,
+            BankInstruction::WithdrawSplTokensAndCloseAtaUsingEd25519Signature => 32,
+            BankInstruction::DryRunSignature => 33,
+            BankInstruction::SetDailyLimit { .. } => 34,
+            BankInstruction::SetLabel { .. } => 35,
+            BankInstruction::WithdrawAndUnwrapSol { .. } => 36,
+            BankInstruction::WithdrawLamportsUsingEd25519SignatureAt { .. } => 37,
+            BankInstruction::WithdrawSplTokensUsingEd25519SignatureAt { .. } => 38,
+            BankInstruction::SetMinWithdrawal { .. } => 39,
+            BankInstruction::CreateBankAccountWithIndex { .. } => 40
+        }
+    }
+
+    /// variant index of the instruction-data in either format without deserializing it (`None` for empty data)
+    pub fn unpack_discriminant(instruction_data: &[u8]) -> Option<u8> {
+        let tag = *instruction_data.first()?;
+        if tag & INSTRUCTION_VERSION_FLAG == 0 {
+            return Some(tag);
+        };
+
+        instruction_data.get(1).copied()
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut instruction_data = vec![INSTRUCTION_VERSION_FLAG | CURRENT_INSTRUCTION_VERSION];
+        instruction_data.extend_from_slice(
+            &self.try_to_vec().unwrap()
+        );
+
+        instruction_data
+    }
 }
 
 pub fn create_create_initialize_bank_account_instruction(
@@ -105,57 +657,111 @@ pub fn create_create_initialize_bank_account_instruction(
 ) -> Instruction {
     Instruction {
         program_id: *program_id,
-        data: BankInstruction::CreateBankAccount.try_to_vec().unwrap(),
+        data: BankInstruction::CreateBankAccount.pack(),
         accounts: vec![
             AccountMeta::new(*funding_account, true),
-            AccountMeta::new_readonly(*authority_account, false),
+            AccountMeta::new_readonly(*authority_account, true),
             AccountMeta::new(*solana_bank_account, false),
             AccountMeta::new_readonly(*system_program_account, false)
         ]
     }
 }
 
+/// same as `create_create_initialize_bank_account_instruction`, the creation is counted against the funder's cap
+pub fn create_create_initialize_bank_account_with_program_config_instruction(
+    funding_account: &Pubkey,
+    authority_account: &Pubkey,
+    solana_bank_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_config_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut instruction = create_create_initialize_bank_account_instruction(
+        funding_account,
+        authority_account,
+        solana_bank_account,
+        system_program_account,
+        program_id
+    );
+    instruction.accounts.push(
+        AccountMeta::new(*program_config_account, false)
+    );
+
+    instruction
+}
+
 pub fn create_withdraw_lamports(
     authority_account: &Pubkey,
     bank_account: &Pubkey,
     recepient_account: &Pubkey,
+    receipt_account: Option<&Pubkey>,
     program_id: &Pubkey,
-    lamports: &u64
+    lamports: &u64,
+    memo: Option<&str>
 ) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority_account, true),
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new(*recepient_account, false)
+    ];
+    if memo.filter(|memo| !memo.is_empty()).is_some() {
+        accounts.push(
+            AccountMeta::new_readonly(MEMO_PROGRAM_ID, false)
+        );
+    };
+    if let Some(receipt_addr) = receipt_account {
+        accounts.push(
+            AccountMeta::new(*receipt_addr, false)
+        );
+    };
+
     Instruction {
         program_id: *program_id,
-        data: BankInstruction::WithdrawLamports { lamports: *lamports }.try_to_vec().unwrap(),
-        accounts: vec![
-            AccountMeta::new_readonly(*authority_account, true),
-            AccountMeta::new(*bank_account, false),
-            AccountMeta::new(*recepient_account, false)
-        ]
+        data: BankInstruction::WithdrawLamports { lamports: *lamports, memo: memo.map(str::to_owned) }.pack(),
+        accounts
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_withdraw_spl_tokens(
     authority_account: &Pubkey,
     bank_account: &Pubkey,
     bank_account_associated_token_account: &Pubkey,
     mint_account: &Pubkey,
     destination_token_account: &Pubkey,
+    token_program_account: &Pubkey,
+    receipt_account: Option<&Pubkey>,
     program_id: &Pubkey,
-    amount: &u64
+    amount: &u64,
+    memo: Option<&str>
 ) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority_account, true),
+        AccountMeta::new_readonly(*bank_account, false),
+        AccountMeta::new(*bank_account_associated_token_account, false),
+        AccountMeta::new_readonly(*mint_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*token_program_account, false)
+    ];
+    if memo.filter(|memo| !memo.is_empty()).is_some() {
+        accounts.push(
+            AccountMeta::new_readonly(MEMO_PROGRAM_ID, false)
+        );
+    };
+    if let Some(receipt_addr) = receipt_account {
+        accounts.push(
+            AccountMeta::new(*receipt_addr, false)
+        );
+    };
+
     Instruction {
         program_id: *program_id,
-        data: BankInstruction::WithdrawSplTokens { amount: *amount }.try_to_vec().unwrap(),
-        accounts: vec![
-            AccountMeta::new_readonly(*authority_account, true),
-            AccountMeta::new_readonly(*bank_account, false),
-            AccountMeta::new(*bank_account_associated_token_account, false),
-            AccountMeta::new_readonly(*mint_account, false),
-            AccountMeta::new(*destination_token_account, false),
-            AccountMeta::new_readonly(spl_token::ID, false)
-        ]
+        data: BankInstruction::WithdrawSplTokens { amount: *amount, memo: memo.map(str::to_owned) }.pack(),
+        accounts
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_withdraw_lamports_using_ed25519_signature(
     bank_account: &Pubkey,
     funder_account: &Pubkey,
@@ -163,35 +769,74 @@ pub fn create_withdraw_lamports_using_ed25519_signature(
     recepient_account: &Pubkey,
     system_program_account: &Pubkey,
     memo_program_account: Option<&Pubkey>,
+    fee_treasury_account: Option<&Pubkey>,
+    receipt_account: Option<&Pubkey>,
     program_id: &Pubkey
 ) -> Instruction {
-    match memo_program_account {
-        Some(memo_program_addr) => Instruction {
-            program_id: *program_id,
-            data: BankInstruction::WithdrawLamportsUsingEd25519Signature.try_to_vec().unwrap(),
-            accounts: vec![
-                AccountMeta::new(*bank_account, false),
-                AccountMeta::new(*funder_account, true),
-                AccountMeta::new_readonly(*withdrawer_account, true),
-                AccountMeta::new(*recepient_account, false),
-                AccountMeta::new_readonly(*system_program_account, false),
-                AccountMeta::new_readonly(*memo_program_addr, false)
-            ]
-        },
-        None => Instruction {
-            program_id: *program_id,
-            data: BankInstruction::WithdrawLamportsUsingEd25519Signature.try_to_vec().unwrap(),
-            accounts: vec![
-                AccountMeta::new(*bank_account, false),
-                AccountMeta::new(*funder_account, true),
-                AccountMeta::new_readonly(*withdrawer_account, true),
-                AccountMeta::new(*recepient_account, false),
-                AccountMeta::new_readonly(*system_program_account, false)
-            ]
-        }
+    let mut accounts = vec![
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new(*funder_account, true),
+        AccountMeta::new_readonly(*withdrawer_account, true),
+        AccountMeta::new(*recepient_account, false),
+        AccountMeta::new_readonly(*system_program_account, false),
+        AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false)
+    ];
+    if let Some(memo_program_addr) = memo_program_account {
+        accounts.push(
+            AccountMeta::new_readonly(*memo_program_addr, false)
+        );
+    };
+    if let Some(fee_treasury_addr) = fee_treasury_account {
+        accounts.push(
+            AccountMeta::new(*fee_treasury_addr, false)
+        );
+    };
+    if let Some(receipt_addr) = receipt_account {
+        accounts.push(
+            AccountMeta::new(*receipt_addr, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::WithdrawLamportsUsingEd25519Signature.pack(),
+        accounts
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_withdraw_lamports_partially_using_ed25519_signature(
+    bank_account: &Pubkey,
+    funder_account: &Pubkey,
+    withdrawer_account: &Pubkey,
+    recepient_account: &Pubkey,
+    system_program_account: &Pubkey,
+    receipt_account: Option<&Pubkey>,
+    program_id: &Pubkey,
+    lamports: u64
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new(*funder_account, true),
+        AccountMeta::new_readonly(*withdrawer_account, true),
+        AccountMeta::new(*recepient_account, false),
+        AccountMeta::new_readonly(*system_program_account, false),
+        AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false)
+    ];
+    if let Some(receipt_addr) = receipt_account {
+        accounts.push(
+            AccountMeta::new(*receipt_addr, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::WithdrawLamportsPartiallyUsingEd25519Signature { lamports }.pack(),
+        accounts
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_withdraw_spl_tokens_using_ed25519_signature(
     mint_account: &Pubkey,
     bank_account: &Pubkey,
@@ -202,37 +847,1349 @@ pub fn create_withdraw_spl_tokens_using_ed25519_signature(
     token_program_account: &Pubkey,
     system_program_account: &Pubkey,
     memo_program_account: Option<&Pubkey>,
+    receipt_account: Option<&Pubkey>,
     program_id: &Pubkey
 ) -> Instruction {
-    match memo_program_account {
-        Some(memo_program_addr) => Instruction {
-            program_id: *program_id,
-            data: BankInstruction::WithdrawSplToknesUsingEd25519Signature.try_to_vec().unwrap(),
-            accounts: vec![
-                AccountMeta::new_readonly(*mint_account, false),
-                AccountMeta::new(*bank_account, false),
-                AccountMeta::new(*bank_associated_token_account, false),
-                AccountMeta::new(*funder_account, true),
-                AccountMeta::new_readonly(*withdrawer_account, true),
-                AccountMeta::new(*destination_token_account, false),
-                AccountMeta::new_readonly(*token_program_account, false),
-                AccountMeta::new_readonly(*system_program_account, false),
-                AccountMeta::new_readonly(*memo_program_addr, false)
-            ]
-        },
-        None => Instruction {
-            program_id: *program_id,
-            data: BankInstruction::WithdrawSplToknesUsingEd25519Signature.try_to_vec().unwrap(),
-            accounts: vec![
-                AccountMeta::new_readonly(*mint_account, false),
-                AccountMeta::new(*bank_account, false),
-                AccountMeta::new(*bank_associated_token_account, false),
-                AccountMeta::new(*funder_account, true),
-                AccountMeta::new_readonly(*withdrawer_account, true),
-                AccountMeta::new(*destination_token_account, false),
-                AccountMeta::new_readonly(*token_program_account, false),
-                AccountMeta::new_readonly(*system_program_account, false)
-            ]
-        }
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*mint_account, false),
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new(*bank_associated_token_account, false),
+        AccountMeta::new(*funder_account, true),
+        AccountMeta::new_readonly(*withdrawer_account, true),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*token_program_account, false),
+        AccountMeta::new_readonly(*system_program_account, false),
+        AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false)
+    ];
+    if let Some(memo_program_addr) = memo_program_account {
+        accounts.push(
+            AccountMeta::new_readonly(*memo_program_addr, false)
+        );
+    };
+    if let Some(receipt_addr) = receipt_account {
+        accounts.push(
+            AccountMeta::new(*receipt_addr, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::WithdrawSplToknesUsingEd25519Signature.pack(),
+        accounts
+    }
+}
+
+/// same as `create_withdraw_lamports_using_ed25519_signature` but the "to" account doesn't sign (funder relays the withdrawal)
+#[allow(clippy::too_many_arguments)]
+pub fn create_gasless_withdraw_lamports_using_ed25519_signature(
+    bank_account: &Pubkey,
+    relayer_account: &Pubkey,
+    to_account: &Pubkey,
+    system_program_account: &Pubkey,
+    memo_program_account: Option<&Pubkey>,
+    fee_treasury_account: Option<&Pubkey>,
+    receipt_account: Option<&Pubkey>,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut instruction = create_withdraw_lamports_using_ed25519_signature(
+        bank_account,
+        relayer_account,
+        to_account,
+        to_account,
+        system_program_account,
+        memo_program_account,
+        fee_treasury_account,
+        receipt_account,
+        program_id
+    );
+    instruction.accounts[2].is_signer = false;
+
+    instruction
+}
+
+/// same as `create_withdraw_spl_tokens_using_ed25519_signature` but the "to" account doesn't sign (funder relays the withdrawal)
+#[allow(clippy::too_many_arguments)]
+pub fn create_gasless_withdraw_spl_tokens_using_ed25519_signature(
+    mint_account: &Pubkey,
+    bank_account: &Pubkey,
+    bank_associated_token_account: &Pubkey,
+    relayer_account: &Pubkey,
+    to_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    token_program_account: &Pubkey,
+    system_program_account: &Pubkey,
+    memo_program_account: Option<&Pubkey>,
+    receipt_account: Option<&Pubkey>,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut instruction = create_withdraw_spl_tokens_using_ed25519_signature(
+        mint_account,
+        bank_account,
+        bank_associated_token_account,
+        relayer_account,
+        to_account,
+        destination_token_account,
+        token_program_account,
+        system_program_account,
+        memo_program_account,
+        receipt_account,
+        program_id
+    );
+    instruction.accounts[4].is_signer = false;
+
+    instruction
+}
+
+/// same as `create_withdraw_spl_tokens_using_ed25519_signature` but an emptied bank-account's A.T.A is closed (rent goes to the funder)
+#[allow(clippy::too_many_arguments)]
+pub fn create_withdraw_spl_tokens_and_close_ata_using_ed25519_signature(
+    mint_account: &Pubkey,
+    bank_account: &Pubkey,
+    bank_associated_token_account: &Pubkey,
+    funder_account: &Pubkey,
+    withdrawer_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    token_program_account: &Pubkey,
+    system_program_account: &Pubkey,
+    memo_program_account: Option<&Pubkey>,
+    receipt_account: Option<&Pubkey>,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut instruction = create_withdraw_spl_tokens_using_ed25519_signature(
+        mint_account,
+        bank_account,
+        bank_associated_token_account,
+        funder_account,
+        withdrawer_account,
+        destination_token_account,
+        token_program_account,
+        system_program_account,
+        memo_program_account,
+        receipt_account,
+        program_id
+    );
+    instruction.data = BankInstruction::WithdrawSplTokensAndCloseAtaUsingEd25519Signature.pack();
+
+    instruction
+}
+
+/// `token_accounts` -> (mint, bank-account's associated-token-account) when dry-running a token withdrawal
+pub fn create_dry_run_signature(
+    bank_account: &Pubkey,
+    token_accounts: Option<(&Pubkey, &Pubkey)>,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*bank_account, false),
+        AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false)
+    ];
+    if let Some((mint_addr, bank_associated_token_addr)) = token_accounts {
+        accounts.push(
+            AccountMeta::new_readonly(*mint_addr, false)
+        );
+        accounts.push(
+            AccountMeta::new_readonly(*bank_associated_token_addr, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::DryRunSignature.pack(),
+        accounts
+    }
+}
+
+pub fn create_update_config(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    funder_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_id: &Pubkey,
+    config: &BankAccountConfig
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::UpdateConfig { config: config.clone() }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false),
+            AccountMeta::new(*funder_account, true),
+            AccountMeta::new_readonly(*system_program_account, false)
+        ]
+    }
+}
+
+pub fn create_check_rent_exempt(
+    bank_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::CheckRentExempt.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*bank_account, false)
+        ]
+    }
+}
+
+/// `token_accounts` -> (bank-account's token-account, (mint, authority's destination token-account))
+pub fn create_close_bank_account_full(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    token_program_account: &Pubkey,
+    token_accounts: &[(Pubkey, Option<(Pubkey, Pubkey)>)],
+    program_id: &Pubkey,
+    memo: Option<&str>
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*authority_account, true),
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new_readonly(*token_program_account, false)
+    ];
+    if memo.filter(|memo| !memo.is_empty()).is_some() {
+        accounts.push(
+            AccountMeta::new_readonly(MEMO_PROGRAM_ID, false)
+        );
+    };
+
+    let mut sweep = vec![];
+    for (bank_token_account, destination_token_account) in token_accounts {
+        accounts.push(
+            AccountMeta::new(*bank_token_account, false)
+        );
+
+        if let Some((mint_account, destination_token_account)) = destination_token_account {
+            accounts.push(
+                AccountMeta::new_readonly(*mint_account, false)
+            );
+            accounts.push(
+                AccountMeta::new(*destination_token_account, false)
+            );
+        };
+
+        sweep.push(destination_token_account.is_some());
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::CloseBankAccountFull { sweep, memo: memo.map(str::to_owned) }.pack(),
+        accounts
+    }
+}
+
+pub fn create_init_receipt_instruction(
+    funding_account: &Pubkey,
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    receipt_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::InitReceipt.pack(),
+        accounts: vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new_readonly(*bank_account, false),
+            AccountMeta::new(*receipt_account, false),
+            AccountMeta::new_readonly(*system_program_account, false)
+        ]
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_init_payment_escrow(
+    funding_account: &Pubkey,
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    payment_escrow_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_id: &Pubkey,
+    nonce: u64,
+    lamports: u64,
+    claim_signer: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::InitPaymentEscrow {
+            nonce,
+            lamports,
+            claim_signer: *claim_signer
+        }.pack(),
+        accounts: vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false),
+            AccountMeta::new(*payment_escrow_account, false),
+            AccountMeta::new_readonly(*system_program_account, false)
+        ]
+    }
+}
+
+pub fn create_redeem_payment_escrow(
+    payment_escrow_account: &Pubkey,
+    bank_account: &Pubkey,
+    recepient_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::RedeemPaymentEscrow.pack(),
+        accounts: vec![
+            AccountMeta::new(*payment_escrow_account, false),
+            AccountMeta::new(*bank_account, false),
+            AccountMeta::new(*recepient_account, false),
+            AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false)
+        ]
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_withdraw_with_savings(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    recepient_account: &Pubkey,
+    savings_bank_account: &Pubkey,
+    program_id: &Pubkey,
+    amount: u64,
+    savings_bps: u16,
+    memo: Option<&str>
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority_account, true),
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new(*recepient_account, false),
+        AccountMeta::new(*savings_bank_account, false)
+    ];
+    if memo.filter(|memo| !memo.is_empty()).is_some() {
+        accounts.push(
+            AccountMeta::new_readonly(MEMO_PROGRAM_ID, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::WithdrawWithSavings { amount, savings_bps, memo: memo.map(str::to_owned) }.pack(),
+        accounts
+    }
+}
+
+pub fn create_revoke_all(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::RevokeAll.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false)
+        ]
+    }
+}
+
+pub fn create_emergency_withdraw(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    recepient_account: &Pubkey,
+    emergency_signer_accounts: &[Pubkey],
+    program_id: &Pubkey,
+    lamports: u64,
+    memo: Option<&str>
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority_account, true),
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new(*recepient_account, false)
+    ];
+    if memo.filter(|memo| !memo.is_empty()).is_some() {
+        accounts.push(
+            AccountMeta::new_readonly(MEMO_PROGRAM_ID, false)
+        );
+    };
+    for emergency_signer in emergency_signer_accounts {
+        accounts.push(
+            AccountMeta::new_readonly(*emergency_signer, true)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::EmergencyWithdraw { lamports, memo: memo.map(str::to_owned) }.pack(),
+        accounts
+    }
+}
+
+pub fn create_compact_account(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::CompactAccount.pack(),
+        accounts: vec![
+            AccountMeta::new(*authority_account, true),
+            AccountMeta::new(*bank_account, false)
+        ]
+    }
+}
+
+/// `token_or_recepient_account` -> bank-account's token-account, or the recipient of the lamports
+pub fn create_get_effective_withdrawable(
+    bank_account: &Pubkey,
+    token_or_recepient_account: Option<&Pubkey>,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*bank_account, false)
+    ];
+    if let Some(account_addr) = token_or_recepient_account {
+        accounts.push(
+            AccountMeta::new_readonly(*account_addr, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::GetEffectiveWithdrawable.pack(),
+        accounts
+    }
+}
+
+pub fn create_migrate_bank_account(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    funder_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::MigrateBankAccount.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false),
+            AccountMeta::new(*funder_account, true),
+            AccountMeta::new_readonly(*system_program_account, false)
+        ]
+    }
+}
+
+pub fn create_deposit(
+    funding_account: &Pubkey,
+    bank_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_id: &Pubkey,
+    lamports: u64
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::Deposit { lamports }.pack(),
+        accounts: vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*bank_account, false),
+            AccountMeta::new_readonly(*system_program_account, false)
+        ]
+    }
+}
+
+pub fn create_verify_integrity(
+    bank_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::VerifyIntegrity.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*bank_account, false)
+        ]
+    }
+}
+
+/// `token_accounts` -> (mint, bank-account's associated token-account, destination token-account)
+pub fn create_sweep_multiple_atas(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    token_program_account: &Pubkey,
+    token_accounts: &[(Pubkey, Pubkey, Pubkey)],
+    program_id: &Pubkey,
+    memo: Option<&str>
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority_account, true),
+        AccountMeta::new_readonly(*bank_account, false),
+        AccountMeta::new_readonly(*token_program_account, false)
+    ];
+    if memo.filter(|memo| !memo.is_empty()).is_some() {
+        accounts.push(
+            AccountMeta::new_readonly(MEMO_PROGRAM_ID, false)
+        );
+    };
+
+    for (mint_account, bank_token_account, destination_token_account) in token_accounts {
+        accounts.push(
+            AccountMeta::new_readonly(*mint_account, false)
+        );
+        accounts.push(
+            AccountMeta::new(*bank_token_account, false)
+        );
+        accounts.push(
+            AccountMeta::new(*destination_token_account, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::SweepMultipleAtas { memo: memo.map(str::to_owned) }.pack(),
+        accounts
+    }
+}
+
+pub fn create_init_program_config(
+    admin_account: &Pubkey,
+    program_config_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_id: &Pubkey,
+    max_creations_per_funder: u16,
+    creation_window_secs: u32
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::InitProgramConfig {
+            max_creations_per_funder,
+            creation_window_secs
+        }.pack(),
+        accounts: vec![
+            AccountMeta::new(*admin_account, true),
+            AccountMeta::new(*program_config_account, false),
+            AccountMeta::new_readonly(*system_program_account, false)
+        ]
+    }
+}
+
+pub fn create_update_program_config(
+    admin_account: &Pubkey,
+    program_config_account: &Pubkey,
+    program_id: &Pubkey,
+    max_creations_per_funder: u16,
+    creation_window_secs: u32
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::UpdateProgramConfig {
+            max_creations_per_funder,
+            creation_window_secs
+        }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*admin_account, true),
+            AccountMeta::new(*program_config_account, false)
+        ]
+    }
+}
+
+pub fn create_transfer_authority(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_id: &Pubkey,
+    new_authority: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::TransferAuthority {
+            new_authority: *new_authority
+        }.pack(),
+        accounts: vec![
+            AccountMeta::new(*authority_account, true),
+            AccountMeta::new(*bank_account, false),
+            AccountMeta::new_readonly(*system_program_account, false)
+        ]
+    }
+}
+
+pub fn create_get_account_info(
+    bank_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::GetAccountInfo.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*bank_account, false)
+        ]
+    }
+}
+
+/// `recepients` -> (recepient account, lamports)
+pub fn create_withdraw_lamports_batch(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    recepients: &[(Pubkey, u64)],
+    program_id: &Pubkey
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority_account, true),
+        AccountMeta::new(*bank_account, false)
+    ];
+    for (recepient_account, _) in recepients {
+        accounts.push(
+            AccountMeta::new(*recepient_account, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::WithdrawLamportsBatch {
+            amounts: recepients
+                .iter()
+                .map(|(_, lamports)| *lamports)
+                .collect()
+        }.pack(),
+        accounts
+    }
+}
+
+pub fn create_withdraw_all_lamports(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    recepient_account: &Pubkey,
+    receipt_account: Option<&Pubkey>,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority_account, true),
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new(*recepient_account, false)
+    ];
+    if let Some(receipt_addr) = receipt_account {
+        accounts.push(
+            AccountMeta::new(*receipt_addr, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::WithdrawAllLamports.pack(),
+        accounts
+    }
+}
+
+pub fn create_reclaim_signature_rent(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    program_id: &Pubkey,
+    cutoff: i64
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::ReclaimSignatureRent { cutoff }.pack(),
+        accounts: vec![
+            AccountMeta::new(*authority_account, true),
+            AccountMeta::new(*bank_account, false)
+        ]
+    }
+}
+
+pub fn create_bank_ata(
+    funder_account: &Pubkey,
+    bank_account: &Pubkey,
+    mint_account: &Pubkey,
+    token_program_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::CreateBankAta.pack(),
+        accounts: vec![
+            AccountMeta::new(*funder_account, true),
+            AccountMeta::new_readonly(*bank_account, false),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    bank_account,
+                    mint_account,
+                    token_program_account
+                ),
+                false
+            ),
+            AccountMeta::new_readonly(*mint_account, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*token_program_account, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false)
+        ]
+    }
+}
+
+pub fn create_freeze_account(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::FreezeAccount.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false)
+        ]
+    }
+}
+
+pub fn create_unfreeze_account(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::UnfreezeAccount.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false)
+        ]
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_withdraw_lamports_relayed_using_ed25519_signature(
+    bank_account: &Pubkey,
+    relayer_account: &Pubkey,
+    recepient_account: &Pubkey,
+    system_program_account: &Pubkey,
+    memo_program_account: Option<&Pubkey>,
+    fee_treasury_account: Option<&Pubkey>,
+    receipt_account: Option<&Pubkey>,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*bank_account, false),
+        AccountMeta::new(*relayer_account, true),
+        AccountMeta::new(*recepient_account, false),
+        AccountMeta::new_readonly(*system_program_account, false),
+        AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false)
+    ];
+    if let Some(memo_program_addr) = memo_program_account {
+        accounts.push(
+            AccountMeta::new_readonly(*memo_program_addr, false)
+        );
+    };
+    if let Some(fee_treasury_addr) = fee_treasury_account {
+        accounts.push(
+            AccountMeta::new(*fee_treasury_addr, false)
+        );
+    };
+    if let Some(receipt_addr) = receipt_account {
+        accounts.push(
+            AccountMeta::new(*receipt_addr, false)
+        );
+    };
+
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::WithdrawLamportsRelayedUsingEd25519Signature.pack(),
+        accounts
+    }
+}
+
+pub fn create_set_daily_limit(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    daily_limit: u64,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::SetDailyLimit { daily_limit }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false)
+        ]
+    }
+}
+
+pub fn create_set_label(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    label: [u8; 32],
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::SetLabel { label }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false)
+        ]
+    }
+}
+
+pub fn create_withdraw_and_unwrap_sol(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    bank_account_associated_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    recepient_account: &Pubkey,
+    amount: u64,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::WithdrawAndUnwrapSol { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new_readonly(*bank_account, false),
+            AccountMeta::new(*bank_account_associated_token_account, false),
+            AccountMeta::new_readonly(spl_token::native_mint::ID, false),
+            AccountMeta::new(*destination_token_account, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(*recepient_account, true)
+        ]
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_withdraw_lamports_using_ed25519_signature_at(
+    bank_account: &Pubkey,
+    funder_account: &Pubkey,
+    withdrawer_account: &Pubkey,
+    recepient_account: &Pubkey,
+    system_program_account: &Pubkey,
+    memo_program_account: Option<&Pubkey>,
+    fee_treasury_account: Option<&Pubkey>,
+    receipt_account: Option<&Pubkey>,
+    signature_index: u8,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut instruction = create_withdraw_lamports_using_ed25519_signature(
+        bank_account,
+        funder_account,
+        withdrawer_account,
+        recepient_account,
+        system_program_account,
+        memo_program_account,
+        fee_treasury_account,
+        receipt_account,
+        program_id
+    );
+    instruction.data = BankInstruction::WithdrawLamportsUsingEd25519SignatureAt { signature_index }.pack();
+
+    instruction
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_withdraw_spl_tokens_using_ed25519_signature_at(
+    mint_account: &Pubkey,
+    bank_account: &Pubkey,
+    bank_associated_token_account: &Pubkey,
+    funder_account: &Pubkey,
+    withdrawer_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    token_program_account: &Pubkey,
+    system_program_account: &Pubkey,
+    memo_program_account: Option<&Pubkey>,
+    receipt_account: Option<&Pubkey>,
+    signature_index: u8,
+    program_id: &Pubkey
+) -> Instruction {
+    let mut instruction = create_withdraw_spl_tokens_using_ed25519_signature(
+        mint_account,
+        bank_account,
+        bank_associated_token_account,
+        funder_account,
+        withdrawer_account,
+        destination_token_account,
+        token_program_account,
+        system_program_account,
+        memo_program_account,
+        receipt_account,
+        program_id
+    );
+    instruction.data = BankInstruction::WithdrawSplTokensUsingEd25519SignatureAt { signature_index }.pack();
+
+    instruction
+}
+
+pub fn create_set_min_withdrawal(
+    authority_account: &Pubkey,
+    bank_account: &Pubkey,
+    min_withdrawal: u64,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::SetMinWithdrawal { min_withdrawal }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*bank_account, false)
+        ]
+    }
+}
+
+pub fn create_create_bank_account_with_index_instruction(
+    funding_account: &Pubkey,
+    authority_account: &Pubkey,
+    solana_bank_account: &Pubkey,
+    system_program_account: &Pubkey,
+    program_config_account: &Pubkey,
+    index: u16,
+    program_id: &Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: BankInstruction::CreateBankAccountWithIndex { index }.pack(),
+        accounts: vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new_readonly(*authority_account, true),
+            AccountMeta::new(*solana_bank_account, false),
+            AccountMeta::new_readonly(*system_program_account, false),
+            AccountMeta::new(*program_config_account, false)
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test_instruction {
+    use super::*;
+
+    #[test]
+    fn unpack_versioned_instruction_success() {
+        let instruction_data = BankInstruction::WithdrawLamports { lamports: 1000, memo: None }.pack();
+
+        assert_eq!(
+            INSTRUCTION_VERSION_FLAG | CURRENT_INSTRUCTION_VERSION,
+            instruction_data[0],
+            "Version byte mismatch."
+        );
+        assert_eq!(
+            BankInstruction::WithdrawLamports { lamports: 1000, memo: None },
+            BankInstruction::unpack(&instruction_data).unwrap(),
+            "Instruction mismatch."
+        );
+    }
+
+    #[test]
+    fn unpack_legacy_instruction_success() {
+        let instruction_data = BankInstruction::WithdrawSplTokens { amount: 1000, memo: None }
+            .try_to_vec()
+            .unwrap();
+
+        assert_eq!(
+            BankInstruction::WithdrawSplTokens { amount: 1000, memo: None },
+            BankInstruction::unpack(&instruction_data).unwrap(),
+            "Instruction mismatch."
+        );
+    }
+
+    #[test]
+    fn unpack_instruction_fail_unsupported_version() {
+        let mut instruction_data = BankInstruction::CreateBankAccount.pack();
+        instruction_data[0] = INSTRUCTION_VERSION_FLAG | (CURRENT_INSTRUCTION_VERSION + 1);
+
+        let error = BankInstruction::unpack(&instruction_data).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::UnsupportedInstructionVersion as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn unpack_discriminant_success() {
+        let withdraw_spl_tokens = BankInstruction::WithdrawSplTokens { amount: 1000, memo: None };
+
+        assert_eq!(
+            Some(2u8),
+            BankInstruction::unpack_discriminant(&withdraw_spl_tokens.pack()),
+            "Discriminant mismatch."
+        );
+        assert_eq!(
+            Some(2u8),
+            BankInstruction::unpack_discriminant(&withdraw_spl_tokens.try_to_vec().unwrap()),
+            "Discriminant mismatch."
+        );
+        assert_eq!(
+            None,
+            BankInstruction::unpack_discriminant(&[]),
+            "Discriminant mismatch."
+        );
+    }
+
+    #[test]
+    fn discriminant_matches_borsh_variant_index() {
+        let instructions = vec![
+            BankInstruction::CreateBankAccount,
+            BankInstruction::WithdrawLamports { lamports: 1000, memo: None },
+            BankInstruction::WithdrawSplTokens { amount: 1000, memo: Some("memo".to_owned()) },
+            BankInstruction::WithdrawLamportsUsingEd25519Signature,
+            BankInstruction::WithdrawSplToknesUsingEd25519Signature,
+            BankInstruction::InitReceipt,
+            BankInstruction::UpdateConfig { config: BankAccountConfig::default() },
+            BankInstruction::CheckRentExempt,
+            BankInstruction::CloseBankAccountFull { sweep: vec![true, false], memo: None },
+            BankInstruction::WithdrawLamportsPartiallyUsingEd25519Signature { lamports: 1000 },
+            BankInstruction::InitPaymentEscrow { nonce: 1, lamports: 1000, claim_signer: Pubkey::new_unique() },
+            BankInstruction::RedeemPaymentEscrow,
+            BankInstruction::WithdrawWithSavings { amount: 1000, savings_bps: 100, memo: None },
+            BankInstruction::RevokeAll,
+            BankInstruction::EmergencyWithdraw { lamports: 1000, memo: None },
+            BankInstruction::CompactAccount,
+            BankInstruction::GetEffectiveWithdrawable,
+            BankInstruction::MigrateBankAccount,
+            BankInstruction::Deposit { lamports: 1000 },
+            BankInstruction::VerifyIntegrity,
+            BankInstruction::SweepMultipleAtas { memo: None },
+            BankInstruction::InitProgramConfig { max_creations_per_funder: 3, creation_window_secs: 3_600 },
+            BankInstruction::UpdateProgramConfig { max_creations_per_funder: 3, creation_window_secs: 3_600 },
+            BankInstruction::TransferAuthority { new_authority: Pubkey::new_unique() },
+            BankInstruction::GetAccountInfo,
+            BankInstruction::WithdrawLamportsBatch { amounts: vec![1000, 2000] },
+            BankInstruction::WithdrawAllLamports,
+            BankInstruction::ReclaimSignatureRent { cutoff: 1_700_000_000 },
+            BankInstruction::CreateBankAta,
+            BankInstruction::FreezeAccount,
+            BankInstruction::UnfreezeAccount,
+            BankInstruction::WithdrawLamportsRelayedUsingEd25519Signature,
+            BankInstruction::WithdrawSplTokensAndCloseAtaUsingEd25519Signature,
+            BankInstruction::DryRunSignature,
+            BankInstruction::SetDailyLimit { daily_limit: 1_000_000_000 },
+            BankInstruction::SetLabel { label: [7; 32] },
+            BankInstruction::WithdrawAndUnwrapSol { amount: 1000 },
+            BankInstruction::WithdrawLamportsUsingEd25519SignatureAt { signature_index: 1 },
+            BankInstruction::WithdrawSplTokensUsingEd25519SignatureAt { signature_index: 1 },
+            BankInstruction::SetMinWithdrawal { min_withdrawal: 10_000 },
+            BankInstruction::CreateBankAccountWithIndex { index: 1 }
+        ];
+
+        for instruction in instructions {
+            assert_eq!(
+                instruction.try_to_vec().unwrap()[0],
+                instruction.discriminant(),
+                "Discriminant mismatch."
+            );
+            assert_eq!(
+                BankInstruction::unpack_discriminant(&instruction.pack()),
+                Some(instruction.discriminant()),
+                "Discriminant mismatch."
+            );
+        };
+    }
+}
+
+#[cfg(test)]
+mod test_instruction_builders {
+    use super::*;
+
+    /// expected metas as documented in `BankInstruction` -> (pubkey, is_signer, is_writable)
+    fn assert_account_metas(
+        instruction: &Instruction,
+        expected_account_metas: &[(Pubkey, bool, bool)]
+    ) {
+        assert_eq!(
+            expected_account_metas.len(),
+            instruction.accounts.len(),
+            "Accounts count mismatch."
+        );
+
+        for (index, (account_meta, (pubkey, is_signer, is_writable))) in instruction.accounts
+            .iter()
+            .zip(expected_account_metas.iter())
+            .enumerate() {
+            assert_eq!(account_meta.pubkey, *pubkey, "Account #{} pubkey mismatch.", index);
+            assert_eq!(account_meta.is_signer, *is_signer, "Account #{} signer flag mismatch.", index);
+            assert_eq!(account_meta.is_writable, *is_writable, "Account #{} writable flag mismatch.", index);
+        };
+    }
+
+    #[test]
+    fn create_bank_account_round_trip() {
+        let program_id = Pubkey::new_unique();
+        let funding_account = Pubkey::new_unique();
+        let authority_account = Pubkey::new_unique();
+        let bank_account = Pubkey::new_unique();
+        let system_program_account = Pubkey::new_unique();
+        let program_config_account = Pubkey::new_unique();
+
+        let instruction = create_create_initialize_bank_account_instruction(
+            &funding_account,
+            &authority_account,
+            &bank_account,
+            &system_program_account,
+            &program_id
+        );
+
+        assert_eq!(instruction.program_id, program_id, "Program id mismatch.");
+        assert_eq!(
+            BankInstruction::CreateBankAccount,
+            BankInstruction::unpack(&instruction.data).unwrap(),
+            "Instruction mismatch."
+        );
+        assert_account_metas(
+            &instruction,
+            &[
+                (funding_account, true, true),
+                (authority_account, true, false),
+                (bank_account, false, true),
+                (system_program_account, false, false)
+            ]
+        );
+
+        let instruction = create_create_initialize_bank_account_with_program_config_instruction(
+            &funding_account,
+            &authority_account,
+            &bank_account,
+            &system_program_account,
+            &program_config_account,
+            &program_id
+        );
+
+        assert_eq!(
+            BankInstruction::CreateBankAccount,
+            BankInstruction::unpack(&instruction.data).unwrap(),
+            "Instruction mismatch."
+        );
+        assert_account_metas(
+            &instruction,
+            &[
+                (funding_account, true, true),
+                (authority_account, true, false),
+                (bank_account, false, true),
+                (system_program_account, false, false),
+                (program_config_account, false, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn withdraw_lamports_round_trip() {
+        let program_id = Pubkey::new_unique();
+        let authority_account = Pubkey::new_unique();
+        let bank_account = Pubkey::new_unique();
+        let recepient_account = Pubkey::new_unique();
+        let receipt_account = Pubkey::new_unique();
+
+        let instruction = create_withdraw_lamports(
+            &authority_account,
+            &bank_account,
+            &recepient_account,
+            None,
+            &program_id,
+            &1000,
+            Some("rent")
+        );
+
+        assert_eq!(
+            BankInstruction::WithdrawLamports { lamports: 1000, memo: Some("rent".to_owned()) },
+            BankInstruction::unpack(&instruction.data).unwrap(),
+            "Instruction mismatch."
+        );
+        assert_account_metas(
+            &instruction,
+            &[
+                (authority_account, true, false),
+                (bank_account, false, true),
+                (recepient_account, false, true),
+                (MEMO_PROGRAM_ID, false, false)
+            ]
+        );
+
+        let instruction = create_withdraw_lamports(
+            &authority_account,
+            &bank_account,
+            &recepient_account,
+            Some(&receipt_account),
+            &program_id,
+            &1000,
+            None
+        );
+
+        assert_account_metas(
+            &instruction,
+            &[
+                (authority_account, true, false),
+                (bank_account, false, true),
+                (recepient_account, false, true),
+                (receipt_account, false, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn withdraw_spl_tokens_round_trip() {
+        let program_id = Pubkey::new_unique();
+        let authority_account = Pubkey::new_unique();
+        let bank_account = Pubkey::new_unique();
+        let bank_account_associated_token_account = Pubkey::new_unique();
+        let mint_account = Pubkey::new_unique();
+        let destination_token_account = Pubkey::new_unique();
+
+        let instruction = create_withdraw_spl_tokens(
+            &authority_account,
+            &bank_account,
+            &bank_account_associated_token_account,
+            &mint_account,
+            &destination_token_account,
+            &spl_token::ID,
+            None,
+            &program_id,
+            &1000,
+            None
+        );
+
+        assert_eq!(
+            BankInstruction::WithdrawSplTokens { amount: 1000, memo: None },
+            BankInstruction::unpack(&instruction.data).unwrap(),
+            "Instruction mismatch."
+        );
+        assert_account_metas(
+            &instruction,
+            &[
+                (authority_account, true, false),
+                (bank_account, false, false),
+                (bank_account_associated_token_account, false, true),
+                (mint_account, false, false),
+                (destination_token_account, false, true),
+                (spl_token::ID, false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn withdraw_lamports_using_ed25519_signature_round_trip() {
+        let program_id = Pubkey::new_unique();
+        let bank_account = Pubkey::new_unique();
+        let funder_account = Pubkey::new_unique();
+        let withdrawer_account = Pubkey::new_unique();
+        let recepient_account = Pubkey::new_unique();
+        let system_program_account = Pubkey::new_unique();
+        let memo_program_account = Pubkey::new_unique();
+
+        let expected_account_metas = vec![
+            (bank_account, false, true),
+            (funder_account, true, true),
+            (withdrawer_account, true, false),
+            (recepient_account, false, true),
+            (system_program_account, false, false),
+            (INSTRUCTIONS_SYSVAR_ID, false, false)
+        ];
+
+        // memo-absent message
+        let instruction = create_withdraw_lamports_using_ed25519_signature(
+            &bank_account,
+            &funder_account,
+            &withdrawer_account,
+            &recepient_account,
+            &system_program_account,
+            None,
+            None,
+            None,
+            &program_id
+        );
+
+        assert_eq!(
+            BankInstruction::WithdrawLamportsUsingEd25519Signature,
+            BankInstruction::unpack(&instruction.data).unwrap(),
+            "Instruction mismatch."
+        );
+        assert_account_metas(&instruction, &expected_account_metas);
+
+        // memo-present message
+        let instruction = create_withdraw_lamports_using_ed25519_signature(
+            &bank_account,
+            &funder_account,
+            &withdrawer_account,
+            &recepient_account,
+            &system_program_account,
+            Some(&memo_program_account),
+            None,
+            None,
+            &program_id
+        );
+
+        let mut expected_account_metas = expected_account_metas;
+        expected_account_metas.push((memo_program_account, false, false));
+
+        assert_eq!(
+            BankInstruction::WithdrawLamportsUsingEd25519Signature,
+            BankInstruction::unpack(&instruction.data).unwrap(),
+            "Instruction mismatch."
+        );
+        assert_account_metas(&instruction, &expected_account_metas);
+    }
+
+    #[test]
+    fn withdraw_spl_tokens_using_ed25519_signature_round_trip() {
+        let program_id = Pubkey::new_unique();
+        let mint_account = Pubkey::new_unique();
+        let bank_account = Pubkey::new_unique();
+        let bank_associated_token_account = Pubkey::new_unique();
+        let funder_account = Pubkey::new_unique();
+        let withdrawer_account = Pubkey::new_unique();
+        let destination_token_account = Pubkey::new_unique();
+        let token_program_account = Pubkey::new_unique();
+        let system_program_account = Pubkey::new_unique();
+        let memo_program_account = Pubkey::new_unique();
+
+        let expected_account_metas = vec![
+            (mint_account, false, false),
+            (bank_account, false, true),
+            (bank_associated_token_account, false, true),
+            (funder_account, true, true),
+            (withdrawer_account, true, false),
+            (destination_token_account, false, true),
+            (token_program_account, false, false),
+            (system_program_account, false, false),
+            (INSTRUCTIONS_SYSVAR_ID, false, false)
+        ];
+
+        // memo-absent message
+        let instruction = create_withdraw_spl_tokens_using_ed25519_signature(
+            &mint_account,
+            &bank_account,
+            &bank_associated_token_account,
+            &funder_account,
+            &withdrawer_account,
+            &destination_token_account,
+            &token_program_account,
+            &system_program_account,
+            None,
+            None,
+            &program_id
+        );
+
+        assert_eq!(
+            BankInstruction::WithdrawSplToknesUsingEd25519Signature,
+            BankInstruction::unpack(&instruction.data).unwrap(),
+            "Instruction mismatch."
+        );
+        assert_account_metas(&instruction, &expected_account_metas);
+
+        // memo-present message
+        let instruction = create_withdraw_spl_tokens_using_ed25519_signature(
+            &mint_account,
+            &bank_account,
+            &bank_associated_token_account,
+            &funder_account,
+            &withdrawer_account,
+            &destination_token_account,
+            &token_program_account,
+            &system_program_account,
+            Some(&memo_program_account),
+            None,
+            &program_id
+        );
+
+        let mut expected_account_metas = expected_account_metas;
+        expected_account_metas.push((memo_program_account, false, false));
+
+        assert_eq!(
+            BankInstruction::WithdrawSplToknesUsingEd25519Signature,
+            BankInstruction::unpack(&instruction.data).unwrap(),
+            "Instruction mismatch."
+        );
+        assert_account_metas(&instruction, &expected_account_metas);
     }
 }
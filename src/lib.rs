@@ -4,6 +4,8 @@ pub mod state;
 pub mod error;
 pub mod program;
 pub mod validator;
+#[cfg(any(feature = "client", test))]
+pub mod client;
 
 #[cfg(not(feature = "no-entrypoint"))]
 mod entrypoint;
\ No newline at end of file
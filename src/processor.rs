@@ -3,14 +3,37 @@ use {
         error::BankError,
         state::{
             UserBankAccount,
-            VerifiedSignature
+            BankAccountConfig,
+            RentExemptStatus,
+            EffectiveWithdrawable,
+            WithdrawableLimit,
+            BankAccountSummary,
+            WithdrawEvent,
+            WithdrawKind,
+            MAX_WITHDRAW_EVENTS,
+            VerifiedSignature,
+            SignatureFailReason,
+            WithdrawalReceipt,
+            PaymentEscrow,
+            ProgramConfig
         },
         validator::{
             validate_bank_account,
-            validate_message_v1,
-            validate_message_v2,
+            validate_signer_bank_account,
+            get_bank_account_seeds,
+            validate_recipient,
+            validate_memo,
+            MAX_MEMO_LEN,
+            parse_ed25519_instruction_data,
+            validate_signer_pubkey,
+            split_message_version,
+            split_message_revocation_epoch,
+            validate_message,
+            Message,
             MessageV1,
-            MessageV2
+            MessageV2,
+            MessageV3,
+            MessageV4
         },
         instruction::BankInstruction
     },
@@ -27,10 +50,12 @@ use {
         },
         borsh0_10::try_from_slice_unchecked,
         entrypoint::ProgramResult,
+        clock::UnixTimestamp,
         msg,
         program::{
             invoke_signed,
-            invoke
+            invoke,
+            set_return_data
         },
         program_error::ProgramError,
         pubkey::{
@@ -46,11 +71,15 @@ use {
         sysvar::{
             clock::Clock,
             rent::Rent,
+            instructions::{
+                load_current_index_checked,
+                load_instruction_at_checked,
+                ID as INSTRUCTIONS_SYSVAR_ID
+            },
             Sysvar
         },
         program_pack::Pack,
         instruction::{
-            get_processed_sibling_instruction,
             Instruction,
             AccountMeta
         },
@@ -62,8 +91,23 @@ use {
             Mint,
             Account as TokenAccount
         },
-        instruction::transfer_checked as transfer_spl_token_checked,
+        instruction::{
+            close_account as close_spl_token_account
+        },
+        native_mint::ID as NATIVE_MINT_ID,
         ID as SPL_TOKEN_PROGRAM_ID
+    },
+    spl_token_2022::{
+        // accepts both the classic token program and Token-2022
+        instruction::{
+            transfer_checked as transfer_spl_token_checked,
+            close_account as close_token_account
+        },
+        ID as SPL_TOKEN_2022_PROGRAM_ID
+    },
+    spl_associated_token_account::{
+        instruction::create_associated_token_account,
+        ID as ASSOCIATED_TOKEN_PROGRAM_ID
     }
 };
 
@@ -73,16 +117,64 @@ pub const AUTHORITY_SIZE: usize = PUBKEY_BYTES;
 pub const NEGATIVE_SIGNATURES_SIZE: usize = 2;
 pub const BUMP_SIZE: usize = 1;
 pub const CREATION_TIME_SIZE: usize = 8;
-pub const SIGNATURES_SIZE: usize = 4 + 0;
+pub const SIGNATURES_SIZE: usize = 4;
+pub const CONFIG_SIZE: usize = 1 + 4 + 1 + 1 + 4 + 1 + 1 + 4 + 1 + 1 + 1 + 1 + 4; // gasless + token_max_per_tx + savings_account + max_distinct_recipients + fee_tiers + fee_treasury + withdrawal_grace_secs + emergency_signers + emergency_threshold + allow_freezable_mints + require_memo + ed25519_enabled + blocked_recipients
+/// basis points of a whole amount
+pub const MAX_BPS: u16 = 10_000;
+pub const MAX_BLOCKED_RECIPIENTS: usize = 32;
+pub const PARTIAL_AUTHORIZATIONS_SIZE: usize = 4;
+pub const RECIPIENTS_SIZE: usize = 4;
+pub const REVOCATION_EPOCH_SIZE: usize = 8;
+pub const TOTAL_DEPOSITED_SIZE: usize = 8;
+pub const ORIGINAL_OWNER_SIZE: usize = 1;
+pub const IS_FROZEN_SIZE: usize = 1;
+pub const DAILY_LIMIT_SIZE: usize = 8;
+pub const DAILY_WITHDRAWALS_SIZE: usize = 4;
+pub const LABEL_SIZE: usize = 32;
+pub const MIN_WITHDRAWAL_SIZE: usize = 8;
+pub const SEED_INDEX_SIZE: usize = 1;
+
+// `VerifyIntegrity` issue flags
+pub const INTEGRITY_INVALID_DISCRIMINATOR: u8 = 1 << 0;
+pub const INTEGRITY_INVALID_BUMP: u8 = 1 << 1;
+pub const INTEGRITY_ZERO_AUTHORITY: u8 = 1 << 2;
+pub const INTEGRITY_INCONSISTENT_SIGNATURES: u8 = 1 << 3;
+pub const INTEGRITY_BELOW_RENT_MINIMUM: u8 = 1 << 4;
 //////////////////////////////////////////
 pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 pub const RENT_EXEMPT_YEARS_REQUIRED: u8 = 2;
+pub const ED25519_MESSAGE_OFFSET: usize = 112;
 
 pub struct Processor {}
 impl Processor {
     fn process_create_initialize_bank_account(
         program_id: &Pubkey,
         accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        Self::_create_bank_account(
+            program_id,
+            accounts_info,
+            None
+        )
+    }
+
+    pub fn process_create_bank_account_with_index(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        index: u16
+    ) -> ProgramResult {
+        Self::_create_bank_account(
+            program_id,
+            accounts_info,
+            Some(index)
+        )
+    }
+
+    /// shared by `CreateBankAccount` and `CreateBankAccountWithIndex`, `seed_index` is appended to the PDA's seeds when present
+    fn _create_bank_account(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        seed_index: Option<u16>
     ) -> ProgramResult {
         let accounts_info = &mut accounts_info.iter();
 
@@ -90,6 +182,14 @@ impl Processor {
         let authority_account_info = next_account_info(accounts_info)?;
         let solana_bank_account_info = next_account_info(accounts_info)?;
         let system_program_account_info = next_account_info(accounts_info)?;
+        let program_config_account_info = next_account_info(accounts_info).ok();
+
+        // the authority has to consent to the creation unless it's the funder itself
+        if !authority_account_info.is_signer && authority_account_info.key != funding_account_info.key {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
 
         if solana_bank_account_info.data_len() > 0_usize {
             return Err(
@@ -106,10 +206,17 @@ impl Processor {
         let (
             bank_account_addr,
             bump
-        ) = UserBankAccount::get_user_bank_account_using_fpa(
-            authority_account_info.key,
-            program_id
-        );
+        ) = match seed_index {
+            Some(index) => UserBankAccount::get_user_bank_account_with_index(
+                authority_account_info.key,
+                index,
+                program_id
+            ),
+            None => UserBankAccount::get_user_bank_account_using_fpa(
+                authority_account_info.key,
+                program_id
+            )
+        };
 
         if bank_account_addr != *solana_bank_account_info.key {
             return Err(
@@ -117,14 +224,21 @@ impl Processor {
             );
         };
 
-        let space = 
-            DISCRIMINATOR_SIZE +
-            AUTHORITY_SIZE + 
-            NEGATIVE_SIGNATURES_SIZE +
-            BUMP_SIZE + 
-            CREATION_TIME_SIZE +
-            SIGNATURES_SIZE;
-        let rent = Rent::get().unwrap().minimum_balance(space);
+        if let Some(program_config_account_info) = program_config_account_info {
+            Self::_record_bank_account_creation(
+                program_id,
+                program_config_account_info,
+                funding_account_info,
+                system_program_account_info
+            )?;
+        };
+
+        // `SEED_INDEX_SIZE` only covers a None index
+        let space = UserBankAccount::space_for(0, 0) + seed_index.map_or(0, |_| std::mem::size_of::<u16>());
+        let rent = Rent::get()?.minimum_balance(space);
+        let seed_index_bytes = seed_index
+            .map(|index| index.to_le_bytes().to_vec())
+            .unwrap_or_default();
         
         invoke_signed(
             &create_solana_account(
@@ -143,6 +257,7 @@ impl Processor {
                 &[
                     b"user_bank_account",
                     authority_account_info.key.to_bytes().as_slice(),
+                    seed_index_bytes.as_slice(),
                     &[bump]
                 ]
             ]
@@ -150,21 +265,28 @@ impl Processor {
 
         msg!("new bank-account created.");
 
+        // freshly created accounts must be zeroed, anything else means the account wasn't truly fresh
+        if solana_bank_account_info.try_borrow_data()?.iter().any(|byte| *byte != 0) {
+            return Err(
+                ProgramError::AccountAlreadyInitialized
+            );
+        };
+
+        // errors are propagated (no panics) so callers composing this instruction through CPI can handle them
         let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
-            &solana_bank_account_info
-                .data
-                .try_borrow()
-                .unwrap()[..]
-        ).unwrap();
+            &solana_bank_account_info.try_borrow_data()?[..]
+        )?;
 
         bank_account.discriminator = UserBankAccount::get_bank_account_discriminator();
         bank_account.authority = *authority_account_info.key;
         bank_account.bump = bump;
-        bank_account.account_created_at = Clock::get().unwrap().unix_timestamp;
+        bank_account.account_created_at = Clock::get()?.unix_timestamp;
+        bank_account.config = BankAccountConfig::default();
+        bank_account.seed_index = seed_index;
 
         bank_account.serialize(
-            &mut &mut solana_bank_account_info.data.try_borrow_mut().unwrap()[..]
-        ).unwrap();
+            &mut &mut solana_bank_account_info.try_borrow_mut_data()?[..]
+        )?;
 
         msg!("new bank-account initialized.");
 
@@ -174,44 +296,132 @@ impl Processor {
     pub fn process_withdraw_lamports(
         program_id: &Pubkey,
         accounts_info: &[AccountInfo],
-        lamports: &u64
+        lamports: &u64,
+        memo: &Option<String>
+    ) -> ProgramResult {
+        Self::_withdraw_lamports(
+            program_id,
+            accounts_info,
+            Some(*lamports),
+            memo
+        )
+    }
+
+    pub fn process_withdraw_all_lamports(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        Self::_withdraw_lamports(
+            program_id,
+            accounts_info,
+            None,
+            &None
+        )
+    }
+
+    pub fn process_withdraw_lamports_batch(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        amounts: &[u64]
     ) -> ProgramResult {
         let accounts_info = &mut accounts_info.iter();
 
         let authority_account_info = next_account_info(accounts_info)?;
         let bank_account_info = next_account_info(accounts_info)?;
-        let recepient_account_info = next_account_info(accounts_info)?;
+        let recepient_accounts_info = accounts_info.collect::<Vec<&AccountInfo>>();
 
-        if authority_account_info.is_signer == false {
+        if !authority_account_info.is_signer {
             return Err(
                 ProgramError::MissingRequiredSignature
             )
         };
 
-        let validation_result = validate_bank_account(
+        if recepient_accounts_info.len() != amounts.len() {
+            return Err(
+                BankError::BatchRecipientsMismatch.into()
+            );
+        };
+
+        if amounts.len() > MAX_WITHDRAW_EVENTS {
+            return Err(
+                BankError::TooManyWithdrawals.into()
+            );
+        };
+
+        validate_bank_account(
             program_id,
             authority_account_info.key,
             bank_account_info
-        );
-        if let Err(err) = validation_result {
-            return Err(err);
+        )?;
+
+        Self::_check_not_frozen(bank_account_info)?;
+        Self::_check_withdrawal_grace_period(bank_account_info)?;
+        Self::_check_memo_requirement(bank_account_info, &None)?;
+
+        for recepient_account_info in recepient_accounts_info.iter() {
+            Self::_check_recipient_not_blocked(
+                bank_account_info,
+                recepient_account_info.key
+            )?;
+            validate_recipient(
+                program_id,
+                recepient_account_info
+            )?;
+
+            Self::_track_recipient(
+                bank_account_info,
+                recepient_account_info.key
+            )?;
         };
 
+        let total_lamports = amounts
+            .iter()
+            .try_fold(0u64, |total, lamports| total.checked_add(*lamports))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
         let space = bank_account_info.data_len();
-        let rent = Rent::get().unwrap().minimum_balance(space);
-        let balance = bank_account_info.lamports() - rent;
-        if *lamports > balance {
+        let rent = Rent::get()?.minimum_balance(space);
+        // floors at the rent-exempt minimum (nothing is withdrawable if the account holds less than that)
+        let balance = bank_account_info
+            .lamports()
+            .checked_sub(rent)
+            .ok_or(
+                ProgramError::from(BankError::InsufficientLamportBalance)
+            )?;
+        if total_lamports > balance {
             return Err(
-                ProgramError::Custom(
-                    BankError::InsufficientLamportBalance as u32
-                )
+                BankError::InsufficientLamportBalance.into()
             );
         };
 
-        **bank_account_info.try_borrow_mut_lamports()? -= lamports;
-        **recepient_account_info.try_borrow_mut_lamports()? += lamports;
+        let mut events = Vec::with_capacity(amounts.len());
+        for (recepient_account_info, lamports) in recepient_accounts_info.iter().zip(amounts.iter()) {
+            let bank_account_lamports = bank_account_info
+                .lamports()
+                .checked_sub(*lamports)
+                .ok_or(
+                    ProgramError::from(BankError::InsufficientLamportBalance)
+                )?;
+            **bank_account_info.try_borrow_mut_lamports()? = bank_account_lamports;
 
-        msg!("Lamports withdrawed.");
+            let recepient_lamports = recepient_account_info
+                .lamports()
+                .checked_add(*lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            **recepient_account_info.try_borrow_mut_lamports()? = recepient_lamports;
+
+            events.push(
+                WithdrawEvent::new(
+                    WithdrawKind::LamportsBatch,
+                    *lamports,
+                    recepient_account_info.key
+                )?
+            );
+        };
+
+        WithdrawEvent::emit_all(&events)?;
+
+        msg!("Lamports withdrawed to {} recepients.", amounts.len());
 
         Ok(())
     }
@@ -220,7 +430,8 @@ impl Processor {
     pub fn process_withdraw_spl_tokens(
         program_id: &Pubkey,
         accounts_info: &[AccountInfo],
-        token_amount: &u64
+        token_amount: &u64,
+        memo: &Option<String>
     ) -> ProgramResult {
         let accounts_info = &mut accounts_info.iter();
 
@@ -231,24 +442,32 @@ impl Processor {
         let destination_token_account_info = next_account_info(accounts_info)?;
         let token_program_account_info = next_account_info(accounts_info)?;
 
-        if authority_account_info.is_signer == false {
+        if !authority_account_info.is_signer {
             return Err(
                 ProgramError::MissingRequiredSignature
             );
         };
 
-        let validation_result = validate_bank_account(
+        validate_bank_account(
             program_id,
             authority_account_info.key,
             bank_account_info
-        );
-        if let Err(err) = validation_result {
-            return Err(err);
-        };
+        )?;
+
+        Self::_check_not_frozen(bank_account_info)?;
+        Self::_check_withdrawal_grace_period(bank_account_info)?;
+        Self::_check_memo_requirement(bank_account_info, memo)?;
+
+        Self::_track_recipient(
+            bank_account_info,
+            destination_token_account_info.key
+        )?;
+
+        Self::_validate_token_program(token_program_account_info)?;
 
         let expected_bank_account_token_account = Self::_get_associated_token_account(
             bank_account_info.key,
-            &spl_token::id(),
+            token_program_account_info.key,
             mint_account_info.key
         );
         if *bank_account_token_account_info.key != expected_bank_account_token_account {
@@ -257,12 +476,55 @@ impl Processor {
             );
         };
 
-        let bank_account_data = &bank_account_info.data.try_borrow().unwrap()[..];
-        let mint_account_data = &mint_account_info.data.try_borrow().unwrap()[..];
+        let bank_account_data = &bank_account_info.try_borrow_data()?[..];
+        let mint_account_data = &mint_account_info.try_borrow_data()?[..];
 
-        let decimals = Mint::unpack(
+        let invalid_mint_data = || {
+            ProgramError::from(BankError::InvalidMintData)
+        };
+        let mint = Mint::unpack_unchecked(
             mint_account_data
-        ).unwrap().decimals;
+                .get(..Mint::LEN)
+                .ok_or_else(invalid_mint_data)?
+        ).map_err(|_| invalid_mint_data())?;
+        if !mint.is_initialized {
+            return Err(
+                BankError::InvalidMintAccount.into()
+            );
+        };
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            bank_account_data
+        )?;
+        Self::_validate_mint_freezability(
+            &bank_account.config,
+            &mint
+        )?;
+        Self::_check_token_recipient_not_blocked(
+            &bank_account,
+            destination_token_account_info
+        )?;
+
+        let bank_account_token_account = Self::_unpack_token_account(
+            &bank_account_token_account_info.try_borrow_data()?[..]
+        )?;
+        if *token_amount > bank_account_token_account.amount {
+            return Err(
+                BankError::InsufficientTokenBalance.into()
+            );
+        };
+
+        // any token-account can receive the tokens, as long as it holds the withdrawn mint
+        let destination_token_account = Self::_unpack_token_account(
+            &destination_token_account_info.try_borrow_data()?[..]
+        )?;
+        if destination_token_account.mint != *mint_account_info.key {
+            return Err(
+                BankError::DestinationMintMismatch.into()
+            );
+        };
+
+        let decimals = mint.decimals;
 
         invoke_signed(
             &transfer_spl_token_checked(
@@ -274,7 +536,7 @@ impl Processor {
                 &[],
                 *token_amount,
                 decimals
-            ).unwrap(),
+            )?,
             &[
                 bank_account_token_account_info.clone(),
                 mint_account_info.clone(),
@@ -284,320 +546,3574 @@ impl Processor {
             &[
                 &[
                     b"user_bank_account",
-                    authority_account_info.key.to_bytes().as_slice(),
+                    bank_account.seed_owner().to_bytes().as_slice(),
+                    bank_account.seed_index_bytes().as_slice(),
                     &[
-                        *bank_account_data.get(40).unwrap() // bump_offset
+                        *bank_account_data.get(40).ok_or(ProgramError::InvalidAccountData)? // bump_offset
                     ]
                 ]
             ]
         )?;
 
+        if let Some(memo) = memo.as_ref().filter(|memo| !memo.is_empty()) {
+            let memo_program_account_info = next_account_info(accounts_info)?;
+
+            Self::_invoke_memo_program(
+                memo_program_account_info,
+                authority_account_info,
+                memo.as_bytes().to_vec()
+            )?;
+        };
+
+        if let Some(receipt_account_info) = accounts_info.next() {
+            Self::_record_withdrawal_receipt(
+                program_id,
+                receipt_account_info,
+                bank_account_info,
+                destination_token_account_info.key,
+                mint_account_info.key,
+                *token_amount
+            )?;
+        };
+
+        WithdrawEvent::emit(
+            WithdrawKind::SplTokens,
+            *token_amount,
+            destination_token_account_info.key
+        )?;
+
         msg!("Tokens withdrawed.");
 
         Ok(())
     }
 
-    pub fn process_withdraw_lamports_using_ed25519_signature(
+    pub fn process_withdraw_and_unwrap_sol(
         program_id: &Pubkey,
-        accounts_info: &[AccountInfo]
+        accounts_info: &[AccountInfo],
+        amount: &u64
     ) -> ProgramResult {
-        let ed25519_svi = get_processed_sibling_instruction(0)
-            .ok_or(
-                ProgramError::Custom(
-                    BankError::FailedToGetEd25519Instruction as u32
-                )
-            )?;
-
-        if ed25519_svi.program_id != ED25519_PROGRAM_ID {
-            return Err(
-                ProgramError::Custom(
-                    BankError::InvalidEd25519SignatureVerificationInstruction as u32
-                )
-            );
-        };
-
-        let ed25519_data = ed25519_svi.data;
-        let message_v1 = validate_message_v1(&ed25519_data)?;
-        let MessageV1 {
-            signer,
-            signature,
-            to,
-            lamports,
-            memo
-        } = message_v1;
-
+        let withdraw_accounts_info = accounts_info
+            .get(..6)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
         let accounts_info = &mut accounts_info.iter();
 
-        let bank_account_info = next_account_info(accounts_info)?;
-        let fund_account_info = next_account_info(accounts_info)?;
-        let withdrawer_account_info = next_account_info(accounts_info)?;
+        let _authority_account_info = next_account_info(accounts_info)?;
+        let _bank_account_info = next_account_info(accounts_info)?;
+        let _bank_account_token_account_info = next_account_info(accounts_info)?;
+        let mint_account_info = next_account_info(accounts_info)?;
+        let destination_token_account_info = next_account_info(accounts_info)?;
+        let token_program_account_info = next_account_info(accounts_info)?;
         let recepient_account_info = next_account_info(accounts_info)?;
-        let system_program_account_info = next_account_info(accounts_info)?;
-
-        if to != *withdrawer_account_info.key {
-            return Err(
-                ProgramError::Custom(
-                    BankError::InvalidToPubkey as u32
-                )
-            );
-        };
 
-        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+        if *mint_account_info.key != NATIVE_MINT_ID {
             return Err(
-                ProgramError::Custom(
-                    BankError::InvalidSystemProgramAccount as u32
-                )
+                BankError::NotNativeMint.into()
             );
         };
 
-        if withdrawer_account_info.is_signer == false {
+        // wrapped SOL of the native mint above lives in the classic token program only
+        if *token_program_account_info.key != SPL_TOKEN_PROGRAM_ID {
             return Err(
-                ProgramError::MissingRequiredSignature
+                BankError::InvalidSplTokenProgramAccount.into()
             );
         };
 
-        validate_bank_account(
+        Self::process_withdraw_spl_tokens(
             program_id,
-            &signer,
-            bank_account_info
+            withdraw_accounts_info,
+            amount,
+            &None
         )?;
 
-        let bank_account_data = bank_account_info
-            .data
-            .try_borrow()
-            .unwrap();
-        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
-            &bank_account_data
-        ).unwrap();
-
-        let bank_account_data_size = bank_account_data.len();
-        let bank_account_balance = bank_account_info.lamports() - Rent::get().unwrap().minimum_balance(bank_account_data_size);
-        let is_ok: bool = if lamports > bank_account_balance {
-            false
-        } else {
-            true
-        };
-
-        let sig_info = VerifiedSignature {
-            signature,
-            is_ok,
-            message: ed25519_data
-                .get(112..)
-                .unwrap()
-                .to_vec(),
-            time: Clock::get().unwrap().unix_timestamp
-        };
-
-        bank_account.add_signature(&sig_info)?;
-
-        let space_to_add = sig_info
-            .try_to_vec()
-            .unwrap()
-            .len();
-        let rent_for_space_increase = (
-            Rent::get().unwrap().lamports_per_byte_year * (space_to_add as u64)
-        ) * RENT_EXEMPT_YEARS_REQUIRED as u64;
-
-        drop(bank_account_data);
-
+        // the recipient owns the wSOL account -> closing it delivers its lamports (withdrawn amount + rent) natively
         invoke(
-            &transfer_lamports(
-                fund_account_info.key,
-                bank_account_info.key,
-                rent_for_space_increase
-            ),
+            &close_spl_token_account(
+                token_program_account_info.key,
+                destination_token_account_info.key,
+                recepient_account_info.key,
+                recepient_account_info.key,
+                &[]
+            )?,
             &[
-                fund_account_info.clone(),
-                bank_account_info.clone(),
-                system_program_account_info.clone()
+                destination_token_account_info.clone(),
+                recepient_account_info.clone(),
+                token_program_account_info.clone()
             ]
         )?;
 
-        let bank_acc = bank_account_info.clone();
-        let current_size = bank_acc.data_len();
+        msg!("wrapped SOL unwrapped.");
 
-        drop(bank_acc);
+        Ok(())
+    }
 
-        bank_account_info.realloc(
-            current_size + space_to_add,
-            false
-        ).unwrap();
+    pub fn process_withdraw_lamports_using_ed25519_signature(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        signature_index: u8
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
 
-        if is_ok == false {
-            msg!("Insufficient lamport balance!");
-            
-            bank_account.serialize(
-                &mut &mut bank_account_info
-                    .data
-                    .try_borrow_mut()
-                    .unwrap()[..]
-            ).unwrap();
+        let bank_account_info = next_account_info(accounts_info)?;
+        let fund_account_info = next_account_info(accounts_info)?;
+        let withdrawer_account_info = next_account_info(accounts_info)?;
+        let recepient_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+        let instructions_sysvar_account_info = next_account_info(accounts_info)?;
 
-            return Ok(());
-        };
+        Self::_check_ed25519_enabled(program_id, bank_account_info)?;
 
-        if memo.len() > 0 {
-            let memo_program_account_info = next_account_info(accounts_info)?;
+        let ed25519_data = Self::_get_ed25519_instruction_data(instructions_sysvar_account_info, signature_index)?;
+        let message_v1 = Self::_get_message_v1(&ed25519_data)?;
 
-            Self::_invoke_memo_program(
-                memo_program_account_info,
-                withdrawer_account_info,
-                memo.as_bytes().to_vec()
-            )?;
+        if message_v1.to != *withdrawer_account_info.key {
+            return Err(
+                BankError::InvalidToPubkey.into()
+            );
         };
 
-        **bank_account_info.try_borrow_mut_lamports()? -= lamports;
-        **recepient_account_info.try_borrow_mut_lamports()? += lamports;
-
-        bank_account.serialize(
-            &mut &mut bank_account_info
-                .data
-                .try_borrow_mut()
-                .unwrap()[..]
-        ).unwrap();
+        // "to" account didn't sign the transaction -> withdrawal is relayed by the funder (gasless)
+        let is_gasless = !withdrawer_account_info.is_signer;
 
-        msg!("Withdraw compeleted. v1");
-
-        Ok(())
+        Self::_withdraw_lamports_using_ed25519(
+            program_id,
+            accounts_info,
+            bank_account_info,
+            fund_account_info,
+            withdrawer_account_info,
+            recepient_account_info,
+            system_program_account_info,
+            &ed25519_data,
+            message_v1,
+            is_gasless
+        )
     }
 
-    pub fn process_withdraw_spl_tokens_using_ed25519_signature(
+    pub fn process_withdraw_lamports_relayed_using_ed25519_signature(
         program_id: &Pubkey,
         accounts_info: &[AccountInfo]
     ) -> ProgramResult {
-        let ed25519_svi = get_processed_sibling_instruction(0)
-            .ok_or(
-                ProgramError::Custom(
-                    BankError::FailedToGetEd25519Instruction as u32
-                )
-            )?;
+        let accounts_info = &mut accounts_info.iter();
 
-        if ed25519_svi.program_id != ED25519_PROGRAM_ID {
-            return Err(
-                ProgramError::Custom(
-                    BankError::InvalidEd25519SignatureVerificationInstruction as u32
-                )
-            );
-        };
+        let bank_account_info = next_account_info(accounts_info)?;
+        let relayer_account_info = next_account_info(accounts_info)?;
+        let recepient_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+        let instructions_sysvar_account_info = next_account_info(accounts_info)?;
 
-        let ed25519_data = ed25519_svi.data;
-        let message_v2 = validate_message_v2(&ed25519_data)?;
-        let MessageV2 {
+        Self::_check_ed25519_enabled(program_id, bank_account_info)?;
+
+        let ed25519_data = Self::_get_ed25519_instruction_data(instructions_sysvar_account_info, 0)?;
+        let MessageV3 {
             signer,
             signature,
             to,
-            amount,
+            relayer,
+            lamports,
             memo,
-            mint
-        } = message_v2;
+            expires_at,
+            revocation_epoch
+        } = Self::_get_message_v3(&ed25519_data)?;
 
-        let accounts_info = &mut accounts_info.iter();
+        if !relayer_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
 
-        let mint_account_account = next_account_info(accounts_info)?;
-        let bank_account_info = next_account_info(accounts_info)?;
+        if relayer != *relayer_account_info.key {
+            return Err(
+                BankError::InvalidRelayer.into()
+            );
+        };
+
+        if to != *recepient_account_info.key {
+            return Err(
+                BankError::InvalidToPubkey.into()
+            );
+        };
+
+        // the relayer is named by the signed message -> it stands in for the "to" account (funds and signs the memo),
+        // no need for the bank-account's gasless opt-in
+        Self::_withdraw_lamports_using_ed25519(
+            program_id,
+            accounts_info,
+            bank_account_info,
+            relayer_account_info,
+            relayer_account_info,
+            recepient_account_info,
+            system_program_account_info,
+            &ed25519_data,
+            MessageV1 {
+                signer,
+                signature,
+                to,
+                lamports,
+                memo,
+                expires_at,
+                revocation_epoch
+            },
+            false
+        )
+    }
+
+    pub fn process_withdraw_spl_tokens_using_ed25519_signature(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        signature_index: u8
+    ) -> ProgramResult {
+        Self::_withdraw_spl_tokens_using_ed25519(program_id, accounts_info, false, signature_index)
+    }
+
+    pub fn process_withdraw_spl_tokens_and_close_ata_using_ed25519_signature(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        Self::_withdraw_spl_tokens_using_ed25519(program_id, accounts_info, true, 0)
+    }
+
+    pub fn process_withdraw_lamports_partially_using_ed25519_signature(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        amount: u64
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let bank_account_info = next_account_info(accounts_info)?;
+        let fund_account_info = next_account_info(accounts_info)?;
+        let withdrawer_account_info = next_account_info(accounts_info)?;
+        let recepient_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+        let instructions_sysvar_account_info = next_account_info(accounts_info)?;
+
+        Self::_check_ed25519_enabled(program_id, bank_account_info)?;
+
+        let ed25519_data = Self::_get_ed25519_instruction_data(instructions_sysvar_account_info, 0)?;
+        let MessageV1 {
+            signer,
+            signature,
+            to,
+            lamports,
+            memo,
+            revocation_epoch,
+            ..
+        } = Self::_get_message_v1(&ed25519_data)?;
+
+        if to != *withdrawer_account_info.key {
+            return Err(
+                BankError::InvalidToPubkey.into()
+            );
+        };
+
+        if !withdrawer_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if *fund_account_info.key == *bank_account_info.key {
+            return Err(
+                BankError::InvalidFunderAccount.into()
+            );
+        };
+
+        if to == *bank_account_info.key {
+            return Err(
+                BankError::SelfWithdrawalNotAllowed.into()
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        validate_signer_bank_account(
+            program_id,
+            &signer,
+            bank_account_info
+        )?;
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        if revocation_epoch != bank_account.revocation_epoch {
+            return Err(
+                BankError::StaleRevocationEpoch.into()
+            );
+        };
+
+        bank_account.check_not_frozen()?;
+        bank_account.check_withdrawal_grace_period(
+            Clock::get()?.unix_timestamp
+        )?;
+        bank_account.check_memo_requirement(Some(&memo))?;
+        bank_account.check_min_withdrawal(amount)?;
+
+        bank_account.check_recipient_not_blocked(recepient_account_info.key)?;
+        validate_recipient(
+            program_id,
+            recepient_account_info
+        )?;
+
+        bank_account.redeem_partial_authorization(
+            &VerifiedSignature {
+                signature,
+                is_ok: true,
+                fail_reason: SignatureFailReason::None,
+                message: ed25519_data
+                    .get(ED25519_MESSAGE_OFFSET..)
+                    .ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?
+                    .to_vec(),
+                time: Clock::get()?.unix_timestamp,
+                slot: Clock::get()?.slot
+            },
+            lamports,
+            amount
+        )?;
+
+        bank_account.track_recipient(recepient_account_info.key)?;
+        bank_account.track_daily_withdrawal(
+            Clock::get()?.unix_timestamp,
+            recepient_account_info.key,
+            amount
+        )?;
+
+        let new_size = bank_account
+            .try_to_vec()?
+            .len();
+
+        Self::_increase_bank_account_size(
+            bank_account_info,
+            fund_account_info,
+            system_program_account_info,
+            new_size
+        )?;
+        Self::_shrink_bank_account_size(
+            bank_account_info,
+            new_size
+        )?;
+
+        // funds trickle in -> the relayer retries later instead of recording a failed entry
+        let bank_account_balance = bank_account_info.lamports().saturating_sub(Rent::get()?.minimum_balance(bank_account_info.data_len()));
+        if amount > bank_account_balance {
+            return Err(
+                BankError::InsufficientLamportBalance.into()
+            );
+        };
+
+        **bank_account_info.try_borrow_mut_lamports()? -= amount;
+        **recepient_account_info.try_borrow_mut_lamports()? += amount;
+
+        if let Some(receipt_account_info) = accounts_info.next() {
+            Self::_record_withdrawal_receipt(
+                program_id,
+                receipt_account_info,
+                bank_account_info,
+                recepient_account_info.key,
+                &Pubkey::default(),
+                amount
+            )?;
+        };
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        WithdrawEvent::emit(
+            WithdrawKind::PartialLamportsUsingEd25519,
+            amount,
+            recepient_account_info.key
+        )?;
+
+        msg!("Partial withdraw compeleted.");
+
+        Ok(())
+    }
+
+    pub fn process_withdraw_with_savings(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        amount: u64,
+        savings_bps: u16,
+        memo: &Option<String>
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let recepient_account_info = next_account_info(accounts_info)?;
+        let savings_bank_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if savings_bps > MAX_BPS {
+            return Err(
+                BankError::InvalidSavingsBps.into()
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.check_not_frozen()?;
+        bank_account.check_withdrawal_grace_period(
+            Clock::get()?.unix_timestamp
+        )?;
+        bank_account.check_memo_requirement(memo.as_deref())?;
+
+        // the savings bank-account must be the one designated in bank-account's config
+        if bank_account.config.savings_account != Some(*savings_bank_account_info.key) || savings_bank_account_info.key == bank_account_info.key {
+            return Err(
+                BankError::InvalidSavingsAccount.into()
+            );
+        };
+
+        if savings_bank_account_info.owner != program_id {
+            return Err(
+                BankError::InvalidSavingsAccount.into()
+            );
+        };
+
+        let savings_bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &savings_bank_account_info.try_borrow_data()?[..]
+        ).map_err(|_| {
+            ProgramError::from(BankError::InvalidSavingsAccount)
+        })?;
+        if savings_bank_account.discriminator != UserBankAccount::get_bank_account_discriminator() {
+            return Err(
+                BankError::InvalidSavingsAccount.into()
+            );
+        };
+
+        // both bank-accounts must belong to the authority
+        if savings_bank_account.authority != *authority_account_info.key {
+            return Err(
+                BankError::InvalidSavingsAccount.into()
+            );
+        };
+
+        Self::_check_recipient_not_blocked(
+            bank_account_info,
+            recepient_account_info.key
+        )?;
+        validate_recipient(
+            program_id,
+            recepient_account_info
+        )?;
+
+        Self::_track_recipient(
+            bank_account_info,
+            recepient_account_info.key
+        )?;
+
+        let balance = bank_account_info.lamports().saturating_sub(
+            Rent::get()?.minimum_balance(bank_account_info.data_len())
+        );
+        if amount > balance {
+            return Err(
+                BankError::InsufficientLamportBalance.into()
+            );
+        };
+
+        if let Some(memo) = memo.as_ref().filter(|memo| !memo.is_empty()) {
+            let memo_program_account_info = next_account_info(accounts_info)?;
+
+            Self::_invoke_memo_program(
+                memo_program_account_info,
+                authority_account_info,
+                memo.as_bytes().to_vec()
+            )?;
+        };
+
+        let savings_amount = ((amount as u128) * (savings_bps as u128) / (MAX_BPS as u128)) as u64;
+        let recepient_amount = amount - savings_amount;
+
+        **bank_account_info.try_borrow_mut_lamports()? -= amount;
+        **recepient_account_info.try_borrow_mut_lamports()? += recepient_amount;
+        **savings_bank_account_info.try_borrow_mut_lamports()? += savings_amount;
+
+        WithdrawEvent::emit(
+            WithdrawKind::LamportsWithSavings,
+            recepient_amount,
+            recepient_account_info.key
+        )?;
+
+        msg!("Lamports withdrawed, {} lamports saved.", savings_amount);
+
+        Ok(())
+    }
+
+    pub fn process_revoke_all(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.revocation_epoch = bank_account.revocation_epoch
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("all authorizations revoked, revocation epoch: {}", bank_account.revocation_epoch);
+
+        Ok(())
+    }
+
+    pub fn process_emergency_withdraw(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        lamports: u64,
+        memo: &Option<String>
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let recepient_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.check_not_frozen()?;
+        bank_account.check_memo_requirement(memo.as_deref())?;
+        if let Some(memo) = memo.as_ref().filter(|memo| !memo.is_empty()) {
+            let memo_program_account_info = next_account_info(accounts_info)?;
+
+            Self::_invoke_memo_program(
+                memo_program_account_info,
+                authority_account_info,
+                memo.as_bytes().to_vec()
+            )?;
+        };
+
+        let mut emergency_signers: Vec<Pubkey> = vec![];
+        for emergency_signer_account_info in accounts_info {
+            if emergency_signer_account_info.is_signer
+                && bank_account.config.emergency_signers.contains(emergency_signer_account_info.key)
+                && !emergency_signers.contains(emergency_signer_account_info.key) {
+                emergency_signers.push(*emergency_signer_account_info.key);
+            };
+        };
+
+        let emergency_threshold = bank_account.config.emergency_threshold;
+        if emergency_threshold == 0 || emergency_signers.len() < emergency_threshold as usize {
+            return Err(
+                BankError::EmergencyThresholdNotMet.into()
+            );
+        };
+
+        bank_account.check_recipient_not_blocked(recepient_account_info.key)?;
+        validate_recipient(
+            program_id,
+            recepient_account_info
+        )?;
+
+        let rent = Rent::get()?.minimum_balance(bank_account_info.data_len());
+        let balance = bank_account_info.lamports().saturating_sub(rent);
+        if lamports > balance {
+            return Err(
+                BankError::InsufficientLamportBalance.into()
+            );
+        };
+
+        **bank_account_info.try_borrow_mut_lamports()? -= lamports;
+        **recepient_account_info.try_borrow_mut_lamports()? += lamports;
+
+        WithdrawEvent::emit(
+            WithdrawKind::EmergencyLamports,
+            lamports,
+            recepient_account_info.key
+        )?;
+
+        msg!("Emergency withdrawal approved by {} emergency signers.", emergency_signers.len());
+
+        Ok(())
+    }
+
+    pub fn process_compact_account(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        let current_size = bank_account_info.data_len();
+        let new_size = bank_account
+            .try_to_vec()?
+            .len();
+        if new_size >= current_size {
+            msg!("bank-account is already compact.");
+
+            return Ok(());
+        };
+
+        bank_account_info.realloc(
+            new_size,
+            false
+        )?;
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        let freed_rent = Self::_refund_freed_rent(
+            bank_account_info,
+            authority_account_info,
+            current_size
+        )?;
+
+        msg!("bank-account compacted from {} to {} bytes, {} lamports refunded.", current_size, new_size, freed_rent);
+
+        Ok(())
+    }
+
+    pub fn process_reclaim_signature_rent(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        cutoff: i64
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        // entries of messages that can still be redeemed keep guarding against replays
+        let now = Clock::get()?.unix_timestamp;
+        let signatures_count = bank_account.signatures.len();
+        bank_account.signatures.retain(|sig_info| sig_info.time >= cutoff || !sig_info.is_expired(now));
+
+        let removed_count = signatures_count - bank_account.signatures.len();
+        if removed_count == 0 {
+            msg!("no signatures to reclaim.");
+
+            return Ok(());
+        };
+
+        let current_size = bank_account_info.data_len();
+        let new_size = bank_account
+            .try_to_vec()?
+            .len();
+
+        bank_account_info.realloc(
+            new_size,
+            false
+        )?;
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        let freed_rent = Self::_refund_freed_rent(
+            bank_account_info,
+            authority_account_info,
+            current_size
+        )?;
+
+        msg!("{} signatures removed, {} lamports refunded.", removed_count, freed_rent);
+
+        Ok(())
+    }
+
+    pub fn process_create_bank_ata(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let funder_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let bank_associated_token_account_info = next_account_info(accounts_info)?;
+        let mint_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+        let token_program_account_info = next_account_info(accounts_info)?;
+        let associated_token_program_account_info = next_account_info(accounts_info)?;
+
+        if !funder_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if bank_account_info.owner != program_id {
+            return Err(
+                ProgramError::InvalidAccountOwner
+            );
+        };
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+        if bank_account.discriminator != UserBankAccount::get_bank_account_discriminator() {
+            return Err(
+                ProgramError::InvalidAccountData
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+        Self::_validate_token_program(token_program_account_info)?;
+        if *associated_token_program_account_info.key != ASSOCIATED_TOKEN_PROGRAM_ID {
+            return Err(
+                BankError::InvalidAssociatedTokenProgram.into()
+            );
+        };
+
+        let expected_bank_associated_token_account = Self::_get_associated_token_account(
+            bank_account_info.key,
+            token_program_account_info.key,
+            mint_account_info.key
+        );
+        if expected_bank_associated_token_account != *bank_associated_token_account_info.key {
+            return Err(
+                BankError::InvalidBankAssociatedTokenAccount.into()
+            );
+        };
+
+        // the A.T.A program only needs the owner's address, the bank PDA doesn't have to sign
+        invoke(
+            &create_associated_token_account(
+                funder_account_info.key,
+                bank_account_info.key,
+                mint_account_info.key,
+                token_program_account_info.key
+            ),
+            &[
+                funder_account_info.clone(),
+                bank_associated_token_account_info.clone(),
+                bank_account_info.clone(),
+                mint_account_info.clone(),
+                system_program_account_info.clone(),
+                token_program_account_info.clone(),
+                associated_token_program_account_info.clone()
+            ]
+        )?;
+
+        msg!("bank-account's associated-token-account created.");
+
+        Ok(())
+    }
+
+    pub fn process_freeze_account(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        Self::_set_frozen(program_id, accounts_info, true)?;
+
+        msg!("bank-account frozen.");
+
+        Ok(())
+    }
+
+    pub fn process_unfreeze_account(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        Self::_set_frozen(program_id, accounts_info, false)?;
+
+        msg!("bank-account unfrozen.");
+
+        Ok(())
+    }
+
+    pub fn process_set_daily_limit(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        daily_limit: u64
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.daily_limit = daily_limit;
+        if daily_limit == 0 {
+            bank_account.daily_withdrawals.clear();
+        };
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("bank-account's daily limit set to {}.", daily_limit);
+
+        Ok(())
+    }
+
+    pub fn process_set_label(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        label: [u8; 32]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.label = label;
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("bank-account's label set.");
+
+        Ok(())
+    }
+
+    pub fn process_set_min_withdrawal(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        min_withdrawal: u64
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.min_withdrawal = min_withdrawal;
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("bank-account's minimum withdrawal set to {}.", min_withdrawal);
+
+        Ok(())
+    }
+
+    pub fn process_update_config(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        config: &BankAccountConfig
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let fund_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        let fee_tiers_sorted = config.fee_tiers
+            .windows(2)
+            .all(|tiers| tiers[0].0 < tiers[1].0);
+        let fee_tiers_bounded = config.fee_tiers
+            .iter()
+            .all(|(_, bps)| *bps <= MAX_BPS);
+        if !fee_tiers_sorted || !fee_tiers_bounded || (!config.fee_tiers.is_empty() && config.fee_treasury.is_none()) {
+            return Err(
+                BankError::InvalidFeeTiers.into()
+            );
+        };
+
+        if config.blocked_recipients.len() > MAX_BLOCKED_RECIPIENTS {
+            return Err(
+                BankError::TooManyBlockedRecipients.into()
+            );
+        };
+
+        bank_account.config = config.clone();
+
+        let new_size = bank_account
+            .try_to_vec()?
+            .len();
+        Self::_increase_bank_account_size(
+            bank_account_info,
+            fund_account_info,
+            system_program_account_info,
+            new_size
+        )?;
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("bank-account config updated.");
+
+        Ok(())
+    }
+
+    pub fn process_deposit(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        lamports: u64
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let funding_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+
+        if !funding_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        if bank_account_info.owner != program_id {
+            return Err(
+                ProgramError::InvalidAccountOwner
+            );
+        };
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+        validate_bank_account(
+            program_id,
+            &bank_account.authority,
+            bank_account_info
+        )?;
+
+        bank_account.total_deposited = bank_account.total_deposited
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        invoke(
+            &transfer_lamports(
+                funding_account_info.key,
+                bank_account_info.key,
+                lamports
+            ),
+            &[
+                funding_account_info.clone(),
+                bank_account_info.clone(),
+                system_program_account_info.clone()
+            ]
+        )?;
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("deposited {} lamports.", lamports);
+
+        Ok(())
+    }
+
+    pub fn process_migrate_bank_account(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let fund_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        // only reads the discriminator (offset 0) and the bump (offset 40) -> holds for the minimal layout too
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let bank_account_data = bank_account_info.try_borrow_data()?;
+        if try_from_slice_unchecked::<UserBankAccount>(&bank_account_data[..]).is_ok() {
+            msg!("bank-account already migrated.");
+            return Ok(());
+        };
+
+        let bank_account = UserBankAccount {
+            discriminator: UserBankAccount::get_bank_account_discriminator(),
+            authority: *authority_account_info.key,
+            bump: bank_account_data[40],
+            account_created_at: Clock::get()?.unix_timestamp,
+            config: BankAccountConfig::default(),
+            ..UserBankAccount::default()
+        };
+        drop(bank_account_data);
+
+        let new_size = bank_account
+            .try_to_vec()?
+            .len();
+        Self::_increase_bank_account_size(
+            bank_account_info,
+            fund_account_info,
+            system_program_account_info,
+            new_size
+        )?;
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("bank-account migrated.");
+
+        Ok(())
+    }
+
+    pub fn process_check_rent_exempt(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if bank_account_info.owner != program_id {
+            return Err(
+                ProgramError::InvalidAccountOwner
+            );
+        };
+
+        let lamports = bank_account_info.lamports();
+        let minimum_balance = Rent::get()?.minimum_balance(
+            bank_account_info.data_len()
+        );
+
+        let status = RentExemptStatus {
+            lamports,
+            minimum_balance,
+            surplus: (lamports as i64) - (minimum_balance as i64)
+        };
+
+        msg!("rent-exempt surplus: {}", status.surplus);
+
+        set_return_data(
+            &status.try_to_vec()?
+        );
+
+        Ok(())
+    }
+
+    pub fn process_dry_run_signature(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let bank_account_info = next_account_info(accounts_info)?;
+        let instructions_sysvar_account_info = next_account_info(accounts_info)?;
+        // both present -> the signature authorizes a token withdrawal (MessageV2), otherwise a lamports one (MessageV1)
+        let token_accounts_info = match (accounts_info.next(), accounts_info.next()) {
+            (Some(mint_account_info), Some(bank_associated_token_account_info)) => Some((mint_account_info, bank_associated_token_account_info)),
+            _ => None
+        };
+
+        let is_accepted = match Self::_dry_run_signature(
+            program_id,
+            bank_account_info,
+            instructions_sysvar_account_info,
+            token_accounts_info
+        ) {
+            Ok(is_ok) => is_ok,
+            Err(err) => {
+                msg!("signature would be rejected: {}", err);
+                false
+            }
+        };
+
+        msg!("dry-run result: {}", is_accepted);
+
+        set_return_data(
+            &is_accepted.try_to_vec()?
+        );
+
+        Ok(())
+    }
+
+    pub fn process_get_account_info(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if bank_account_info.owner != program_id {
+            return Err(
+                ProgramError::InvalidAccountOwner
+            );
+        };
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+        if bank_account.discriminator != UserBankAccount::get_bank_account_discriminator() {
+            return Err(
+                ProgramError::InvalidAccountData
+            );
+        };
+
+        let summary = BankAccountSummary {
+            authority: bank_account.authority,
+            account_created_at: bank_account.account_created_at,
+            signatures_count: bank_account.signatures.len() as u32
+        };
+
+        set_return_data(
+            &summary.try_to_vec()?
+        );
+
+        Ok(())
+    }
+
+    pub fn process_verify_integrity(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if bank_account_info.owner != program_id {
+            return Err(
+                ProgramError::InvalidAccountOwner
+            );
+        };
+
+        let bank_account_data = bank_account_info.try_borrow_data()?;
+        let mut issues = 0u8;
+
+        if bank_account_data.get(..DISCRIMINATOR_SIZE) != Some(UserBankAccount::get_bank_account_discriminator().as_slice()) {
+            issues |= INTEGRITY_INVALID_DISCRIMINATOR;
+        };
+
+        match (
+            bank_account_data.get(DISCRIMINATOR_SIZE..DISCRIMINATOR_SIZE + AUTHORITY_SIZE),
+            bank_account_data.get(DISCRIMINATOR_SIZE + AUTHORITY_SIZE)
+        ) {
+            (Some(authority), Some(bump)) => {
+                let authority = Pubkey::try_from(authority).map_err(|_| ProgramError::InvalidAccountData)?;
+                if authority == Pubkey::default() {
+                    issues |= INTEGRITY_ZERO_AUTHORITY;
+                };
+
+                let bank_account_addr = get_bank_account_seeds(program_id, &authority, bank_account_info)
+                    .and_then(|(seed_owner, seed_index)| UserBankAccount::get_user_bank_account_with_index_using_cpa(&seed_owner, &seed_index, bump, program_id));
+                match bank_account_addr {
+                    Ok(bank_account_addr) if bank_account_addr == *bank_account_info.key => {},
+                    _ => issues |= INTEGRITY_INVALID_BUMP
+                };
+            },
+            _ => issues |= INTEGRITY_ZERO_AUTHORITY | INTEGRITY_INVALID_BUMP
+        };
+
+        if Self::_check_signatures_layout(&bank_account_data[..]).is_err() {
+            issues |= INTEGRITY_INCONSISTENT_SIGNATURES;
+        };
+
+        if bank_account_info.lamports() < Rent::get()?.minimum_balance(bank_account_data.len()) {
+            issues |= INTEGRITY_BELOW_RENT_MINIMUM;
+        };
+
+        msg!("integrity issues: {:#07b}", issues);
+
+        set_return_data(&[issues]);
+
+        Ok(())
+    }
+
+    pub fn process_get_effective_withdrawable(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if bank_account_info.owner != program_id {
+            return Err(
+                ProgramError::InvalidAccountOwner
+            );
+        };
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        // any other account than a token-account is the recipient of the lamports withdrawal
+        let mut effective_withdrawable = match accounts_info.next() {
+            Some(token_account_info) if *token_account_info.owner == SPL_TOKEN_PROGRAM_ID || *token_account_info.owner == SPL_TOKEN_2022_PROGRAM_ID => {
+                let token_account = Self::_unpack_token_account(
+                    &token_account_info.try_borrow_data()?[..]
+                )?;
+                if token_account.owner != *bank_account_info.key {
+                    return Err(
+                        BankError::InvalidBankAssociatedTokenAccount.into()
+                    );
+                };
+
+                match bank_account.config.get_token_max_per_tx(&token_account.mint) {
+                    Some(max_amount) if max_amount < token_account.amount => EffectiveWithdrawable {
+                        amount: max_amount,
+                        limit: WithdrawableLimit::TokenMaxPerTx
+                    },
+                    _ => EffectiveWithdrawable {
+                        amount: token_account.amount,
+                        limit: WithdrawableLimit::TokenBalance
+                    }
+                }
+            },
+            recepient_account_info => {
+                let rent = Rent::get()?.minimum_balance(
+                    bank_account_info.data_len()
+                );
+                let balance = bank_account_info.lamports().saturating_sub(rent);
+
+                match bank_account.daily_limit_remaining(now, recepient_account_info.map(|account_info| account_info.key)) {
+                    Some(daily_limit_remaining) if daily_limit_remaining < balance => EffectiveWithdrawable {
+                        amount: daily_limit_remaining,
+                        limit: WithdrawableLimit::DailyLimit
+                    },
+                    _ => EffectiveWithdrawable {
+                        amount: balance,
+                        limit: WithdrawableLimit::RentFloor
+                    }
+                }
+            }
+        };
+
+        if bank_account.check_withdrawal_grace_period(now).is_err() {
+            effective_withdrawable = EffectiveWithdrawable {
+                amount: 0,
+                limit: WithdrawableLimit::WithdrawalGracePeriod
+            };
+        };
+
+        if bank_account.check_not_frozen().is_err() {
+            effective_withdrawable = EffectiveWithdrawable {
+                amount: 0,
+                limit: WithdrawableLimit::AccountFrozen
+            };
+        };
+
+        msg!("effective withdrawable: {}", effective_withdrawable.amount);
+
+        set_return_data(
+            &effective_withdrawable.try_to_vec()?
+        );
+
+        Ok(())
+    }
+
+    pub fn process_close_bank_account_full(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        sweep: &[bool],
+        memo: &Option<String>
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let token_program_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        Self::_check_not_frozen(bank_account_info)?;
+        Self::_check_withdrawal_grace_period(bank_account_info)?;
+
+        Self::_validate_token_program(token_program_account_info)?;
+
+        Self::_check_memo_requirement(bank_account_info, memo)?;
+        if let Some(memo) = memo.as_ref().filter(|memo| !memo.is_empty()) {
+            let memo_program_account_info = next_account_info(accounts_info)?;
+
+            Self::_invoke_memo_program(
+                memo_program_account_info,
+                authority_account_info,
+                memo.as_bytes().to_vec()
+            )?;
+        };
+
+        let bump = *bank_account_info.try_borrow_data()?
+            .get(40) // bump_offset
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let (seed_owner, seed_index) = get_bank_account_seeds(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+        let seed_index_bytes = seed_index
+            .map(|seed_index| seed_index.to_le_bytes().to_vec())
+            .unwrap_or_default();
+        let signer_seeds: &[&[u8]] = &[
+            b"user_bank_account",
+            seed_owner.as_ref(),
+            seed_index_bytes.as_slice(),
+            &[bump]
+        ];
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        for has_destination in sweep {
+            let bank_account_token_account_info = next_account_info(accounts_info)?;
+
+            if bank_account_token_account_info.owner != token_program_account_info.key {
+                return Err(
+                    ProgramError::InvalidAccountOwner
+                );
+            };
+
+            let bank_account_token_account = Self::_unpack_token_account(
+                &bank_account_token_account_info.try_borrow_data()?[..]
+            )?;
+            if bank_account_token_account.owner != *bank_account_info.key {
+                return Err(
+                    BankError::InvalidBankAssociatedTokenAccount.into()
+                );
+            };
+
+            if *has_destination {
+                let mint_account_info = next_account_info(accounts_info)?;
+                let destination_token_account_info = next_account_info(accounts_info)?;
+
+                if *mint_account_info.key != bank_account_token_account.mint {
+                    return Err(
+                        BankError::InvalidMintAccount.into()
+                    );
+                };
+
+                Self::_check_token_recipient_not_blocked(
+                    &bank_account,
+                    destination_token_account_info
+                )?;
+
+                if bank_account_token_account.amount > 0 {
+                    let mint = Self::_unpack_mint(
+                        &mint_account_info.try_borrow_data()?[..]
+                    )?;
+
+                    invoke_signed(
+                        &transfer_spl_token_checked(
+                            token_program_account_info.key,
+                            bank_account_token_account_info.key,
+                            mint_account_info.key,
+                            destination_token_account_info.key,
+                            bank_account_info.key,
+                            &[],
+                            bank_account_token_account.amount,
+                            mint.decimals
+                        )?,
+                        &[
+                            bank_account_token_account_info.clone(),
+                            mint_account_info.clone(),
+                            destination_token_account_info.clone(),
+                            bank_account_info.clone()
+                        ],
+                        &[signer_seeds]
+                    )?;
+                };
+            } else if bank_account_token_account.amount > 0 {
+                return Err(
+                    BankError::UnsweptTokens.into()
+                );
+            };
+
+            invoke_signed(
+                &close_token_account(
+                    token_program_account_info.key,
+                    bank_account_token_account_info.key,
+                    authority_account_info.key,
+                    bank_account_info.key,
+                    &[]
+                )?,
+                &[
+                    bank_account_token_account_info.clone(),
+                    authority_account_info.clone(),
+                    bank_account_info.clone()
+                ],
+                &[signer_seeds]
+            )?;
+        };
+
+        let bank_account_lamports = bank_account_info.lamports();
+        **bank_account_info.try_borrow_mut_lamports()? -= bank_account_lamports;
+        **authority_account_info.try_borrow_mut_lamports()? += bank_account_lamports;
+
+        bank_account_info.realloc(0, false)?;
+        bank_account_info.assign(&SYSTEM_PROGRAM_ID);
+
+        WithdrawEvent::emit(
+            WithdrawKind::CloseBankAccountFull,
+            bank_account_lamports,
+            authority_account_info.key
+        )?;
+
+        msg!("Bank-account closed.");
+
+        Ok(())
+    }
+
+    pub fn process_sweep_multiple_atas(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        memo: &Option<String>
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let token_program_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        Self::_check_not_frozen(bank_account_info)?;
+        Self::_check_withdrawal_grace_period(bank_account_info)?;
+
+        Self::_validate_token_program(token_program_account_info)?;
+
+        Self::_check_memo_requirement(bank_account_info, memo)?;
+        if let Some(memo) = memo.as_ref().filter(|memo| !memo.is_empty()) {
+            let memo_program_account_info = next_account_info(accounts_info)?;
+
+            Self::_invoke_memo_program(
+                memo_program_account_info,
+                authority_account_info,
+                memo.as_bytes().to_vec()
+            )?;
+        };
+
+        let bump = *bank_account_info.try_borrow_data()?
+            .get(40) // bump_offset
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let (seed_owner, seed_index) = get_bank_account_seeds(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+        let seed_index_bytes = seed_index
+            .map(|seed_index| seed_index.to_le_bytes().to_vec())
+            .unwrap_or_default();
+        let signer_seeds: &[&[u8]] = &[
+            b"user_bank_account",
+            seed_owner.as_ref(),
+            seed_index_bytes.as_slice(),
+            &[bump]
+        ];
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        let token_accounts_info = accounts_info.as_slice();
+        if !token_accounts_info.len().is_multiple_of(3) {
+            return Err(
+                ProgramError::NotEnoughAccountKeys
+            );
+        };
+
+        if token_accounts_info.len() / 3 > MAX_WITHDRAW_EVENTS {
+            return Err(
+                BankError::TooManyWithdrawals.into()
+            );
+        };
+
+        let mut events = Vec::with_capacity(token_accounts_info.len() / 3);
+        for sweep_accounts_info in token_accounts_info.chunks(3) {
+            let mint_account_info = &sweep_accounts_info[0];
+            let bank_account_token_account_info = &sweep_accounts_info[1];
+            let destination_token_account_info = &sweep_accounts_info[2];
+
+            let expected_bank_account_token_account = Self::_get_associated_token_account(
+                bank_account_info.key,
+                token_program_account_info.key,
+                mint_account_info.key
+            );
+            if *bank_account_token_account_info.key != expected_bank_account_token_account {
+                return Err(
+                    ProgramError::InvalidSeeds
+                );
+            };
+
+            let mint = Self::_unpack_mint(
+                &mint_account_info.try_borrow_data()?[..]
+            )?;
+
+            let bank_account_token_account = Self::_unpack_token_account(
+                &bank_account_token_account_info.try_borrow_data()?[..]
+            )?;
+            if bank_account_token_account.amount == 0 {
+                continue;
+            };
+
+            Self::_check_token_recipient_not_blocked(
+                &bank_account,
+                destination_token_account_info
+            )?;
+
+            invoke_signed(
+                &transfer_spl_token_checked(
+                    token_program_account_info.key,
+                    bank_account_token_account_info.key,
+                    mint_account_info.key,
+                    destination_token_account_info.key,
+                    bank_account_info.key,
+                    &[],
+                    bank_account_token_account.amount,
+                    mint.decimals
+                )?,
+                &[
+                    bank_account_token_account_info.clone(),
+                    mint_account_info.clone(),
+                    destination_token_account_info.clone(),
+                    bank_account_info.clone()
+                ],
+                &[signer_seeds]
+            )?;
+
+            events.push(
+                WithdrawEvent::new(
+                    WithdrawKind::SweepSplTokens,
+                    bank_account_token_account.amount,
+                    destination_token_account_info.key
+                )?
+            );
+
+            msg!("swept {} tokens of mint {}", bank_account_token_account.amount, mint_account_info.key);
+        };
+
+        WithdrawEvent::emit_all(&events)?;
+
+        Ok(())
+    }
+
+    pub fn process_init_receipt(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let funding_account_info = next_account_info(accounts_info)?;
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let receipt_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        if receipt_account_info.data_len() > 0_usize {
+            return Err(
+                ProgramError::AccountAlreadyInitialized
+            );
+        };
+
+        let (
+            receipt_account_addr,
+            bump
+        ) = WithdrawalReceipt::get_receipt_account_using_fpa(
+            bank_account_info.key,
+            program_id
+        );
+        if receipt_account_addr != *receipt_account_info.key {
+            return Err(
+                ProgramError::InvalidSeeds
+            );
+        };
+
+        let rent = Rent::get()?.minimum_balance(WithdrawalReceipt::LEN);
+
+        invoke_signed(
+            &create_solana_account(
+                funding_account_info.key,
+                receipt_account_info.key,
+                rent,
+                WithdrawalReceipt::LEN as u64,
+                program_id
+            ),
+            &[
+                funding_account_info.clone(),
+                receipt_account_info.clone(),
+                system_program_account_info.clone()
+            ],
+            &[
+                &[
+                    b"receipt",
+                    bank_account_info.key.to_bytes().as_slice(),
+                    &[bump]
+                ]
+            ]
+        )?;
+
+        let receipt = WithdrawalReceipt {
+            discriminator: WithdrawalReceipt::get_receipt_discriminator(),
+            bank_account: *bank_account_info.key,
+            bump,
+            withdrawals_count: 0,
+            hash: [0; 32]
+        };
+
+        receipt.serialize(
+            &mut &mut receipt_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("withdrawal-receipt initialized.");
+
+        Ok(())
+    }
+
+    pub fn process_transfer_authority(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        new_authority: &Pubkey
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        if *new_authority == Pubkey::default() {
+            return Err(
+                ProgramError::InvalidArgument
+            );
+        };
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        // the PDA stays seeded by the first owner
+        bank_account.original_owner = Some(bank_account.seed_owner());
+        bank_account.authority = *new_authority;
+
+        let new_size = bank_account
+            .try_to_vec()?
+            .len();
+        Self::_increase_bank_account_size(
+            bank_account_info,
+            authority_account_info,
+            system_program_account_info,
+            new_size
+        )?;
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("bank-account authority transferred.");
+
+        Ok(())
+    }
+
+    pub fn process_init_program_config(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        max_creations_per_funder: u16,
+        creation_window_secs: u32
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let admin_account_info = next_account_info(accounts_info)?;
+        let program_config_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+
+        if !admin_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        if program_config_account_info.data_len() > 0_usize {
+            return Err(
+                ProgramError::AccountAlreadyInitialized
+            );
+        };
+
+        let (
+            program_config_addr,
+            bump
+        ) = ProgramConfig::get_program_config_using_fpa(program_id);
+        if program_config_addr != *program_config_account_info.key {
+            return Err(
+                ProgramError::InvalidSeeds
+            );
+        };
+
+        let rent = Rent::get()?.minimum_balance(ProgramConfig::LEN);
+
+        invoke_signed(
+            &create_solana_account(
+                admin_account_info.key,
+                program_config_account_info.key,
+                rent,
+                ProgramConfig::LEN as u64,
+                program_id
+            ),
+            &[
+                admin_account_info.clone(),
+                program_config_account_info.clone(),
+                system_program_account_info.clone()
+            ],
+            &[
+                &[
+                    b"program_config",
+                    &[bump]
+                ]
+            ]
+        )?;
+
+        let program_config = ProgramConfig {
+            discriminator: ProgramConfig::get_program_config_discriminator(),
+            bump,
+            admin: *admin_account_info.key,
+            max_creations_per_funder,
+            creation_window_secs,
+            funder_creations: vec![]
+        };
+
+        program_config.serialize(
+            &mut &mut program_config_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("program-config initialized.");
+
+        Ok(())
+    }
+
+    pub fn process_update_program_config(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        max_creations_per_funder: u16,
+        creation_window_secs: u32
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let admin_account_info = next_account_info(accounts_info)?;
+        let program_config_account_info = next_account_info(accounts_info)?;
+
+        if !admin_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        let mut program_config = Self::_load_program_config(
+            program_id,
+            program_config_account_info
+        )?;
+
+        if program_config.admin != *admin_account_info.key {
+            return Err(
+                BankError::InvalidProgramConfigAdmin.into()
+            );
+        };
+
+        program_config.max_creations_per_funder = max_creations_per_funder;
+        program_config.creation_window_secs = creation_window_secs;
+
+        program_config.serialize(
+            &mut &mut program_config_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("program-config updated.");
+
+        Ok(())
+    }
+
+    pub fn process_init_payment_escrow(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        nonce: u64,
+        lamports: u64,
+        claim_signer: &Pubkey
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let funding_account_info = next_account_info(accounts_info)?;
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let payment_escrow_account_info = next_account_info(accounts_info)?;
+        let system_program_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        Self::_check_not_frozen(bank_account_info)?;
+        Self::_check_withdrawal_grace_period(bank_account_info)?;
+
+        if payment_escrow_account_info.data_len() > 0_usize {
+            return Err(
+                ProgramError::AccountAlreadyInitialized
+            );
+        };
+
+        let (
+            payment_escrow_account_addr,
+            bump
+        ) = PaymentEscrow::get_payment_escrow_using_fpa(
+            bank_account_info.key,
+            nonce,
+            program_id
+        );
+        if payment_escrow_account_addr != *payment_escrow_account_info.key {
+            return Err(
+                ProgramError::InvalidSeeds
+            );
+        };
+
+        let balance = bank_account_info.lamports().saturating_sub(
+            Rent::get()?.minimum_balance(bank_account_info.data_len())
+        );
+        if lamports > balance {
+            return Err(
+                BankError::InsufficientLamportBalance.into()
+            );
+        };
+
+        let rent = Rent::get()?.minimum_balance(PaymentEscrow::LEN);
+
+        invoke_signed(
+            &create_solana_account(
+                funding_account_info.key,
+                payment_escrow_account_info.key,
+                rent,
+                PaymentEscrow::LEN as u64,
+                program_id
+            ),
+            &[
+                funding_account_info.clone(),
+                payment_escrow_account_info.clone(),
+                system_program_account_info.clone()
+            ],
+            &[
+                &[
+                    b"payment_escrow",
+                    bank_account_info.key.to_bytes().as_slice(),
+                    nonce.to_le_bytes().as_slice(),
+                    &[bump]
+                ]
+            ]
+        )?;
+
+        **bank_account_info.try_borrow_mut_lamports()? -= lamports;
+        **payment_escrow_account_info.try_borrow_mut_lamports()? += lamports;
+
+        let payment_escrow = PaymentEscrow {
+            discriminator: PaymentEscrow::get_payment_escrow_discriminator(),
+            bank_account: *bank_account_info.key,
+            nonce,
+            bump,
+            claim_signer: *claim_signer,
+            amount: lamports
+        };
+
+        payment_escrow.serialize(
+            &mut &mut payment_escrow_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        msg!("payment-escrow initialized.");
+
+        Ok(())
+    }
+
+    pub fn process_redeem_payment_escrow(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo]
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let payment_escrow_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let recepient_account_info = next_account_info(accounts_info)?;
+        let instructions_sysvar_account_info = next_account_info(accounts_info)?;
+
+        let ed25519_data = Self::_get_ed25519_instruction_data(instructions_sysvar_account_info, 0)?;
+
+        let MessageV4 {
+            signer,
+            payment_escrow: message_payment_escrow,
+            to,
+            lamports,
+            ..
+        } = Self::_get_message_v4(&ed25519_data)?;
+
+        // a redeemed escrow is closed -> no longer owned by the program
+        if payment_escrow_account_info.owner != program_id {
+            return Err(
+                BankError::InvalidPaymentEscrow.into()
+            );
+        };
+
+        let payment_escrow = try_from_slice_unchecked::<PaymentEscrow>(
+            &payment_escrow_account_info.try_borrow_data()?[..]
+        ).map_err(|_| {
+            ProgramError::from(BankError::InvalidPaymentEscrow)
+        })?;
+
+        if payment_escrow.discriminator != PaymentEscrow::get_payment_escrow_discriminator() || payment_escrow.bank_account != *bank_account_info.key {
+            return Err(
+                BankError::InvalidPaymentEscrow.into()
+            );
+        };
+
+        let expected_payment_escrow_account = PaymentEscrow::get_payment_escrow_using_cpa(
+            bank_account_info.key,
+            payment_escrow.nonce,
+            &payment_escrow.bump,
+            program_id
+        )?;
+        if expected_payment_escrow_account != *payment_escrow_account_info.key {
+            return Err(
+                ProgramError::InvalidSeeds
+            );
+        };
+
+        // the message binds the claim to this escrow -> one signature can't redeem other escrows of the claim-signer
+        if message_payment_escrow != *payment_escrow_account_info.key {
+            return Err(
+                BankError::PaymentEscrowMessageMismatch.into()
+            );
+        };
+
+        if signer != payment_escrow.claim_signer {
+            return Err(
+                BankError::SignerAccountMismatch.into()
+            );
+        };
+
+        if to != *recepient_account_info.key {
+            return Err(
+                BankError::InvalidToPubkey.into()
+            );
+        };
+
+        validate_recipient(
+            program_id,
+            recepient_account_info
+        )?;
+
+        if lamports != payment_escrow.amount {
+            return Err(
+                BankError::InvalidLamports.into()
+            );
+        };
+
+        let escrow_lamports = payment_escrow_account_info.lamports();
+
+        **payment_escrow_account_info.try_borrow_mut_lamports()? -= escrow_lamports;
+        **recepient_account_info.try_borrow_mut_lamports()? += lamports;
+        **bank_account_info.try_borrow_mut_lamports()? += escrow_lamports - lamports;
+
+        payment_escrow_account_info.realloc(0, false)?;
+        payment_escrow_account_info.assign(&SYSTEM_PROGRAM_ID);
+
+        WithdrawEvent::emit(
+            WithdrawKind::RedeemPaymentEscrow,
+            lamports,
+            recepient_account_info.key
+        )?;
+
+        msg!("payment-escrow redeemed.");
+
+        Ok(())
+    }
+
+    pub fn processor(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        instruction_data: &[u8]
+    ) -> ProgramResult {
+        let bank_instruction = BankInstruction::unpack(instruction_data)?;
+
+        match bank_instruction {
+            BankInstruction::CreateBankAccount => {
+                msg!("Instruction: CreateBankAccount");
+                Self::process_create_initialize_bank_account(program_id, accounts_info)
+            },
+            BankInstruction::WithdrawLamports { lamports, memo } => {
+                msg!("Instruction: WithdrawLamports");
+                Self::process_withdraw_lamports(program_id, accounts_info, &lamports, &memo)
+            },
+            BankInstruction::WithdrawSplTokens { amount, memo } => {
+                msg!("Instruction: WithdrawSplTokens");
+                Self::process_withdraw_spl_tokens(program_id, accounts_info, &amount, &memo)
+            },
+            BankInstruction::WithdrawLamportsUsingEd25519Signature => {
+                msg!("Instruction: WithdrawLamportsUsingEd25519Signature");
+                Self::process_withdraw_lamports_using_ed25519_signature(program_id, accounts_info, 0)
+            },
+            BankInstruction::WithdrawSplToknesUsingEd25519Signature => {
+                msg!("Instruction: WithdrawSplToknesUsingEd25519Signature");
+                Self::process_withdraw_spl_tokens_using_ed25519_signature(program_id, accounts_info, 0)
+            },
+            BankInstruction::InitReceipt => {
+                msg!("Instruction: InitReceipt");
+                Self::process_init_receipt(program_id, accounts_info)
+            },
+            BankInstruction::UpdateConfig { config } => {
+                msg!("Instruction: UpdateConfig");
+                Self::process_update_config(program_id, accounts_info, &config)
+            },
+            BankInstruction::CheckRentExempt => {
+                msg!("Instruction: CheckRentExempt");
+                Self::process_check_rent_exempt(program_id, accounts_info)
+            },
+            BankInstruction::CloseBankAccountFull { sweep, memo } => {
+                msg!("Instruction: CloseBankAccountFull");
+                Self::process_close_bank_account_full(program_id, accounts_info, &sweep, &memo)
+            },
+            BankInstruction::WithdrawLamportsPartiallyUsingEd25519Signature { lamports } => {
+                msg!("Instruction: WithdrawLamportsPartiallyUsingEd25519Signature");
+                Self::process_withdraw_lamports_partially_using_ed25519_signature(program_id, accounts_info, lamports)
+            },
+            BankInstruction::InitPaymentEscrow { nonce, lamports, claim_signer } => {
+                msg!("Instruction: InitPaymentEscrow");
+                Self::process_init_payment_escrow(program_id, accounts_info, nonce, lamports, &claim_signer)
+            },
+            BankInstruction::RedeemPaymentEscrow => {
+                msg!("Instruction: RedeemPaymentEscrow");
+                Self::process_redeem_payment_escrow(program_id, accounts_info)
+            },
+            BankInstruction::WithdrawWithSavings { amount, savings_bps, memo } => {
+                msg!("Instruction: WithdrawWithSavings");
+                Self::process_withdraw_with_savings(program_id, accounts_info, amount, savings_bps, &memo)
+            },
+            BankInstruction::RevokeAll => {
+                msg!("Instruction: RevokeAll");
+                Self::process_revoke_all(program_id, accounts_info)
+            },
+            BankInstruction::EmergencyWithdraw { lamports, memo } => {
+                msg!("Instruction: EmergencyWithdraw");
+                Self::process_emergency_withdraw(program_id, accounts_info, lamports, &memo)
+            },
+            BankInstruction::CompactAccount => {
+                msg!("Instruction: CompactAccount");
+                Self::process_compact_account(program_id, accounts_info)
+            },
+            BankInstruction::GetEffectiveWithdrawable => {
+                msg!("Instruction: GetEffectiveWithdrawable");
+                Self::process_get_effective_withdrawable(program_id, accounts_info)
+            },
+            BankInstruction::MigrateBankAccount => {
+                msg!("Instruction: MigrateBankAccount");
+                Self::process_migrate_bank_account(program_id, accounts_info)
+            },
+            BankInstruction::Deposit { lamports } => {
+                msg!("Instruction: Deposit");
+                Self::process_deposit(program_id, accounts_info, lamports)
+            },
+            BankInstruction::VerifyIntegrity => {
+                msg!("Instruction: VerifyIntegrity");
+                Self::process_verify_integrity(program_id, accounts_info)
+            },
+            BankInstruction::SweepMultipleAtas { memo } => {
+                msg!("Instruction: SweepMultipleAtas");
+                Self::process_sweep_multiple_atas(program_id, accounts_info, &memo)
+            },
+            BankInstruction::InitProgramConfig { max_creations_per_funder, creation_window_secs } => {
+                msg!("Instruction: InitProgramConfig");
+                Self::process_init_program_config(program_id, accounts_info, max_creations_per_funder, creation_window_secs)
+            },
+            BankInstruction::UpdateProgramConfig { max_creations_per_funder, creation_window_secs } => {
+                msg!("Instruction: UpdateProgramConfig");
+                Self::process_update_program_config(program_id, accounts_info, max_creations_per_funder, creation_window_secs)
+            },
+            BankInstruction::TransferAuthority { new_authority } => {
+                msg!("Instruction: TransferAuthority");
+                Self::process_transfer_authority(program_id, accounts_info, &new_authority)
+            },
+            BankInstruction::GetAccountInfo => {
+                msg!("Instruction: GetAccountInfo");
+                Self::process_get_account_info(program_id, accounts_info)
+            },
+            BankInstruction::WithdrawLamportsBatch { amounts } => {
+                msg!("Instruction: WithdrawLamportsBatch");
+                Self::process_withdraw_lamports_batch(program_id, accounts_info, &amounts)
+            },
+            BankInstruction::WithdrawAllLamports => {
+                msg!("Instruction: WithdrawAllLamports");
+                Self::process_withdraw_all_lamports(program_id, accounts_info)
+            },
+            BankInstruction::ReclaimSignatureRent { cutoff } => {
+                msg!("Instruction: ReclaimSignatureRent");
+                Self::process_reclaim_signature_rent(program_id, accounts_info, cutoff)
+            },
+            BankInstruction::CreateBankAta => {
+                msg!("Instruction: CreateBankAta");
+                Self::process_create_bank_ata(program_id, accounts_info)
+            },
+            BankInstruction::FreezeAccount => {
+                msg!("Instruction: FreezeAccount");
+                Self::process_freeze_account(program_id, accounts_info)
+            },
+            BankInstruction::UnfreezeAccount => {
+                msg!("Instruction: UnfreezeAccount");
+                Self::process_unfreeze_account(program_id, accounts_info)
+            },
+            BankInstruction::WithdrawLamportsRelayedUsingEd25519Signature => {
+                msg!("Instruction: WithdrawLamportsRelayedUsingEd25519Signature");
+                Self::process_withdraw_lamports_relayed_using_ed25519_signature(program_id, accounts_info)
+            },
+            BankInstruction::WithdrawSplTokensAndCloseAtaUsingEd25519Signature => {
+                msg!("Instruction: WithdrawSplTokensAndCloseAtaUsingEd25519Signature");
+                Self::process_withdraw_spl_tokens_and_close_ata_using_ed25519_signature(program_id, accounts_info)
+            },
+            BankInstruction::DryRunSignature => {
+                msg!("Instruction: DryRunSignature");
+                Self::process_dry_run_signature(program_id, accounts_info)
+            },
+            BankInstruction::SetDailyLimit { daily_limit } => {
+                msg!("Instruction: SetDailyLimit");
+                Self::process_set_daily_limit(program_id, accounts_info, daily_limit)
+            },
+            BankInstruction::SetLabel { label } => {
+                msg!("Instruction: SetLabel");
+                Self::process_set_label(program_id, accounts_info, label)
+            },
+            BankInstruction::WithdrawAndUnwrapSol { amount } => {
+                msg!("Instruction: WithdrawAndUnwrapSol");
+                Self::process_withdraw_and_unwrap_sol(program_id, accounts_info, &amount)
+            },
+            BankInstruction::WithdrawLamportsUsingEd25519SignatureAt { signature_index } => {
+                msg!("Instruction: WithdrawLamportsUsingEd25519SignatureAt");
+                Self::process_withdraw_lamports_using_ed25519_signature(program_id, accounts_info, signature_index)
+            },
+            BankInstruction::WithdrawSplTokensUsingEd25519SignatureAt { signature_index } => {
+                msg!("Instruction: WithdrawSplTokensUsingEd25519SignatureAt");
+                Self::process_withdraw_spl_tokens_using_ed25519_signature(program_id, accounts_info, signature_index)
+            },
+            BankInstruction::SetMinWithdrawal { min_withdrawal } => {
+                msg!("Instruction: SetMinWithdrawal");
+                Self::process_set_min_withdrawal(program_id, accounts_info, min_withdrawal)
+            },
+            BankInstruction::CreateBankAccountWithIndex { index } => {
+                msg!("Instruction: CreateBankAccountWithIndex");
+                Self::process_create_bank_account_with_index(program_id, accounts_info, index)
+            }
+        }
+    }
+
+    /// memo program account of the ed25519 withdrawals, it's expected right after the fixed accounts only when the signed memo is non-empty
+    /// (missing -> `InvalidMemoProgramAccount`), a memo program account passed along an empty memo is skipped
+    fn _next_memo_program_account<'a, 'b>(
+        accounts_info: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        memo: &str
+    ) -> Result<Option<&'a AccountInfo<'b>>, ProgramError> {
+        if !memo.is_empty() {
+            let memo_program_account_info = accounts_info
+                .next()
+                .ok_or(
+                    ProgramError::from(BankError::InvalidMemoProgramAccount)
+                )?;
+
+            return Ok(Some(memo_program_account_info));
+        };
+
+        if accounts_info.clone().next().filter(|account_info| *account_info.key == MEMO_PROGRAM_ID).is_some() {
+            accounts_info.next();
+        };
+
+        Ok(None)
+    }
+
+    fn _invoke_memo_program(
+        memo_program_account_info: &AccountInfo,
+        message_sender_account_info: &AccountInfo,
+        memo_message: Vec<u8>
+    ) -> ProgramResult {
+        Self::_invoke_memo_program_signed(
+            memo_program_account_info,
+            message_sender_account_info,
+            memo_message,
+            &[]
+        )
+    }
+
+    /// the message sender may be a PDA of the program signing through `signer_seeds`
+    fn _invoke_memo_program_signed(
+        memo_program_account_info: &AccountInfo,
+        message_sender_account_info: &AccountInfo,
+        memo_message: Vec<u8>,
+        signer_seeds: &[&[&[u8]]]
+    ) -> ProgramResult {
+        if *memo_program_account_info.key != MEMO_PROGRAM_ID {
+            return Err(
+                BankError::InvalidMemoProgramAccount.into()
+            );
+        };
+
+        // the memo program would fail the CPI with a less precise error
+        if memo_message.len() > MAX_MEMO_LEN {
+            return Err(
+                BankError::MemoTooLong.into()
+            );
+        };
+        validate_memo(&memo_message)?;
+
+        invoke_signed(
+            &Instruction {
+                program_id: *memo_program_account_info.key,
+                data: memo_message,
+                accounts: vec![
+                    AccountMeta::new_readonly(*message_sender_account_info.key, true)
+                ]
+            },
+            std::slice::from_ref(message_sender_account_info),
+            signer_seeds
+        )?;
+
+        Ok(())
+    }
+
+    /// data of the ed25519-signature-verification instruction that precedes the bank instruction
+    /// ed25519 instruction must precede the top-level instruction being executed,
+    /// the instructions sysvar always reports the top-level index -> works for both direct calls and CPI
+    /// `signature_index` selects the signature within the ed25519 instruction (it may verify several)
+    fn _get_ed25519_instruction_data(
+        instructions_sysvar_account_info: &AccountInfo,
+        signature_index: u8
+    ) -> Result<Vec<u8>, ProgramError> {
+        if *instructions_sysvar_account_info.key != INSTRUCTIONS_SYSVAR_ID {
+            return Err(
+                BankError::InvalidInstructionsSysvarAccount.into()
+            );
+        };
+
+        let current_index = load_current_index_checked(instructions_sysvar_account_info)?;
+
+        // nearest preceding ed25519 instruction -> other instructions (compute-budget, ...) may sit in between
+        let mut ed25519_svi = None;
+        for index in (0..current_index as usize).rev() {
+            let instruction = load_instruction_at_checked(
+                index,
+                instructions_sysvar_account_info
+            )?;
+            if instruction.program_id == ED25519_PROGRAM_ID {
+                ed25519_svi = Some((index as u16, instruction));
+                break;
+            };
+        };
+        let (
+            ed25519_svi_index,
+            ed25519_svi
+        ) = ed25519_svi.ok_or(
+            ProgramError::from(BankError::FailedToGetEd25519Instruction)
+        )?;
+
+        // validators slice the signer, signature and message at fixed offsets -> follow the instruction's own offsets
+        parse_ed25519_instruction_data(
+            &ed25519_svi.data,
+            ed25519_svi_index,
+            signature_index
+        )
+    }
+
+    /// lamports withdrawals accept V1 messages (implied version when the message has no version field)
+    fn _get_message_v1(
+        ed25519_data: &[u8]
+    ) -> Result<MessageV1, ProgramError> {
+        let (version, message_data) = split_message_version(ed25519_data, 1)?;
+        let (revocation_epoch, message_data) = split_message_revocation_epoch(&message_data)?;
+
+        match validate_message(version, &message_data)? {
+            Message::V1(message_v1) => {
+                Self::_check_message_expiry(message_v1.expires_at)?;
+                validate_signer_pubkey(ed25519_data, &message_v1.signer)?;
+
+                Ok(
+                    MessageV1 {
+                        revocation_epoch,
+                        ..message_v1
+                    }
+                )
+            },
+            _ => Err(
+                BankError::MessageV1ValidationFailed.into()
+            )
+        }
+    }
+
+    /// spl-token withdrawals accept V2 messages (implied version when the message has no version field)
+    fn _get_message_v2(
+        ed25519_data: &[u8]
+    ) -> Result<MessageV2, ProgramError> {
+        let (version, message_data) = split_message_version(ed25519_data, 2)?;
+        let (revocation_epoch, message_data) = split_message_revocation_epoch(&message_data)?;
+
+        match validate_message(version, &message_data)? {
+            Message::V2(message_v2) => {
+                Self::_check_message_expiry(message_v2.expires_at)?;
+                validate_signer_pubkey(ed25519_data, &message_v2.signer)?;
+
+                Ok(
+                    MessageV2 {
+                        revocation_epoch,
+                        ..message_v2
+                    }
+                )
+            },
+            _ => Err(
+                BankError::MessageV2ValidationFailed.into()
+            )
+        }
+    }
+
+    /// relayed lamports withdrawals accept V3 messages only with an explicit "v3" version field,
+    /// an unversioned V2 message without decimals has as many fields and would be read as a V3 one
+    fn _get_message_v3(
+        ed25519_data: &[u8]
+    ) -> Result<MessageV3, ProgramError> {
+        let (version, message_data) = split_message_version(ed25519_data, 0)?;
+        if version != 3 {
+            return Err(
+                BankError::MessageV3ValidationFailed.into()
+            );
+        };
+        let (revocation_epoch, message_data) = split_message_revocation_epoch(&message_data)?;
+
+        match validate_message(version, &message_data)? {
+            Message::V3(message_v3) => {
+                Self::_check_message_expiry(message_v3.expires_at)?;
+                validate_signer_pubkey(ed25519_data, &message_v3.signer)?;
+
+                Ok(
+                    MessageV3 {
+                        revocation_epoch,
+                        ..message_v3
+                    }
+                )
+            },
+            _ => Err(
+                BankError::MessageV3ValidationFailed.into()
+            )
+        }
+    }
+
+    /// payment-escrow claims accept V4 messages only with an explicit "v4" version field,
+    /// so a claim can't be replayed as an ed25519 withdrawal from the claim-signer's bank-account (nor the other way around)
+    fn _get_message_v4(
+        ed25519_data: &[u8]
+    ) -> Result<MessageV4, ProgramError> {
+        let (version, message_data) = split_message_version(ed25519_data, 0)?;
+        if version != 4 {
+            return Err(
+                BankError::MessageV4ValidationFailed.into()
+            );
+        };
+
+        match validate_message(version, &message_data)? {
+            Message::V4(message_v4) => {
+                Self::_check_message_expiry(message_v4.expires_at)?;
+                validate_signer_pubkey(ed25519_data, &message_v4.signer)?;
+
+                Ok(message_v4)
+            },
+            _ => Err(
+                BankError::MessageV4ValidationFailed.into()
+            )
+        }
+    }
+
+    /// signatures vector (and the config before it) must deserialize within bank-account's data
+    fn _check_signatures_layout(
+        bank_account_data: &[u8]
+    ) -> ProgramResult {
+        let mut data = bank_account_data
+            .get(DISCRIMINATOR_SIZE + AUTHORITY_SIZE + BUMP_SIZE + CREATION_TIME_SIZE..)
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        BankAccountConfig::deserialize(&mut data)?;
+        Vec::<VerifiedSignature>::deserialize(&mut data)?;
+
+        Ok(())
+    }
+
+    /// the message's signature is only valid up to (and including) its `expires_at` unix-timestamp
+    fn _check_message_expiry(
+        expires_at: UnixTimestamp
+    ) -> ProgramResult {
+        if Clock::get()?.unix_timestamp > expires_at {
+            return Err(
+                BankError::SignatureExpired.into()
+            );
+        };
+
+        Ok(())
+    }
+
+    fn _validate_mint_freezability(
+        config: &BankAccountConfig,
+        mint: &Mint
+    ) -> ProgramResult {
+        if !config.allow_freezable_mints && mint.freeze_authority.is_some() {
+            return Err(
+                BankError::FreezableMintNotAllowed.into()
+            );
+        };
+
+        Ok(())
+    }
+
+    fn _check_memo_requirement(
+        bank_account_info: &AccountInfo,
+        memo: &Option<String>
+    ) -> ProgramResult {
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.check_memo_requirement(memo.as_deref())
+    }
+
+    /// rejects before the ed25519 instruction is even looked at, accounts not owned by the program are left to the bank-account validation
+    fn _check_ed25519_enabled(
+        program_id: &Pubkey,
+        bank_account_info: &AccountInfo
+    ) -> ProgramResult {
+        if bank_account_info.owner != program_id {
+            return Ok(());
+        };
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.check_ed25519_enabled()
+    }
+
+    fn _check_not_frozen(
+        bank_account_info: &AccountInfo
+    ) -> ProgramResult {
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.check_not_frozen()
+    }
+
+    fn _check_withdrawal_grace_period(
+        bank_account_info: &AccountInfo
+    ) -> ProgramResult {
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.check_withdrawal_grace_period(
+            Clock::get()?.unix_timestamp
+        )
+    }
+
+    fn _check_recipient_not_blocked(
+        bank_account_info: &AccountInfo,
+        recepient: &Pubkey
+    ) -> ProgramResult {
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.check_recipient_not_blocked(recepient)
+    }
+
+    /// both the destination token-account and its owner are matched against the blocked recipients
+    fn _check_token_recipient_not_blocked(
+        bank_account: &UserBankAccount,
+        destination_token_account_info: &AccountInfo
+    ) -> ProgramResult {
+        bank_account.check_recipient_not_blocked(destination_token_account_info.key)?;
+
+        let destination_token_account = Self::_unpack_token_account(
+            &destination_token_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.check_recipient_not_blocked(&destination_token_account.owner)
+    }
+
+    /// runs the checks of the ed25519 withdrawals without recording the signature or moving funds,
+    /// `Ok(false)` -> the withdrawal would be recorded as failed (insufficient balance)
+    fn _dry_run_signature(
+        program_id: &Pubkey,
+        bank_account_info: &AccountInfo,
+        instructions_sysvar_account_info: &AccountInfo,
+        token_accounts_info: Option<(&AccountInfo, &AccountInfo)>
+    ) -> Result<bool, ProgramError> {
+        Self::_check_ed25519_enabled(program_id, bank_account_info)?;
+
+        let ed25519_data = Self::_get_ed25519_instruction_data(instructions_sysvar_account_info, 0)?;
+        let (signer, signature, to, amount, memo, revocation_epoch, mint) = match token_accounts_info {
+            Some(_) => {
+                let message_v2 = Self::_get_message_v2(&ed25519_data)?;
+                (message_v2.signer, message_v2.signature, message_v2.to, message_v2.amount, message_v2.memo, message_v2.revocation_epoch, Some(message_v2.mint))
+            },
+            None => {
+                let message_v1 = Self::_get_message_v1(&ed25519_data)?;
+                (message_v1.signer, message_v1.signature, message_v1.to, message_v1.lamports, message_v1.memo, message_v1.revocation_epoch, None)
+            }
+        };
+
+        validate_signer_bank_account(
+            program_id,
+            &signer,
+            bank_account_info
+        )?;
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        if revocation_epoch != bank_account.revocation_epoch {
+            return Err(
+                BankError::StaleRevocationEpoch.into()
+            );
+        };
+
+        bank_account.check_not_frozen()?;
+        bank_account.check_withdrawal_grace_period(
+            Clock::get()?.unix_timestamp
+        )?;
+        bank_account.check_memo_requirement(Some(&memo))?;
+
+        if bank_account.find_signature(&signature).is_some() || bank_account.get_partial_authorization(&signature).is_some() {
+            return Err(
+                BankError::SignatureAlreadyUsed.into()
+            );
+        };
+
+        match (token_accounts_info, mint) {
+            (Some((mint_account_info, bank_associated_token_account_info)), Some(mint)) => {
+                // the mint's owner is the token program the withdrawal would go through
+                if *mint_account_info.key != mint || (*mint_account_info.owner != SPL_TOKEN_PROGRAM_ID && *mint_account_info.owner != SPL_TOKEN_2022_PROGRAM_ID) {
+                    return Err(
+                        BankError::InvalidMintAccount.into()
+                    );
+                };
+                let expected_bank_associated_token_account = Self::_get_associated_token_account(
+                    bank_account_info.key,
+                    mint_account_info.owner,
+                    mint_account_info.key
+                );
+                if expected_bank_associated_token_account != *bank_associated_token_account_info.key {
+                    return Err(
+                        BankError::InvalidBankAssociatedTokenAccount.into()
+                    );
+                };
+
+                if let Some(max_amount) = bank_account.config.get_token_max_per_tx(&mint) {
+                    if amount > max_amount {
+                        return Err(
+                            BankError::ExceedsTokenMaxPerTx.into()
+                        );
+                    };
+                };
+
+                let bank_token_account = Self::_unpack_token_account(
+                    &bank_associated_token_account_info.try_borrow_data()?[..]
+                )?;
+
+                Ok(amount <= bank_token_account.amount)
+            },
+            _ => {
+                bank_account.check_min_withdrawal(amount)?;
+                bank_account.check_recipient_not_blocked(&to)?;
+
+                let bank_account_balance = bank_account_info.lamports().saturating_sub(
+                    Rent::get()?.minimum_balance(bank_account_info.data_len())
+                );
+
+                Ok(amount <= bank_account_balance)
+            }
+        }
+    }
+
+    fn _set_frozen(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        is_frozen: bool
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
+
+        validate_bank_account(
+            program_id,
+            authority_account_info.key,
+            bank_account_info
+        )?;
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        bank_account.is_frozen = is_frozen;
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        Ok(())
+    }
+
+    /// shared by the ed25519 token withdrawals, `close_emptied_ata` closes bank-account's A.T.A when the withdrawal empties it
+    /// (its rent goes to the funder)
+    fn _withdraw_spl_tokens_using_ed25519(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        close_emptied_ata: bool,
+        signature_index: u8
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let mint_account_account = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
         let bank_assocoiated_token_account_info = next_account_info(accounts_info)?;
         let fund_account_info = next_account_info(accounts_info)?;
         let withdrawer_account_info = next_account_info(accounts_info)?;
         let destination_token_account_info = next_account_info(accounts_info)?;
         let token_standard_program_account_info = next_account_info(accounts_info)?;
         let system_program_account_info = next_account_info(accounts_info)?;
+        let instructions_sysvar_account_info = next_account_info(accounts_info)?;
+
+        // cheap account-key comparisons run before the ed25519 instruction is fetched and any account is deserialized
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
+            );
+        };
+
+        Self::_validate_token_program(token_standard_program_account_info)?;
+
+        if *fund_account_info.key == *bank_account_info.key {
+            return Err(
+                BankError::InvalidFunderAccount.into()
+            );
+        };
+
+        Self::_check_ed25519_enabled(program_id, bank_account_info)?;
+
+        let ed25519_data = Self::_get_ed25519_instruction_data(instructions_sysvar_account_info, signature_index)?;
+        let message_v2 = Self::_get_message_v2(&ed25519_data)?;
+        let MessageV2 {
+            signer,
+            signature,
+            to,
+            amount,
+            decimals,
+            memo,
+            mint,
+            revocation_epoch,
+            ..
+        } = message_v2;
 
         if to != *withdrawer_account_info.key {
             return Err(
-                ProgramError::Custom(
-                    BankError::InvalidToPubkey as u32
-                )
+                BankError::InvalidToPubkey.into()
+            );
+        };
+
+        // "to" account didn't sign the transaction -> withdrawal is relayed by the funder (gasless)
+        let is_gasless = !withdrawer_account_info.is_signer;
+
+        validate_signer_bank_account(
+            program_id,
+            &signer,
+            bank_account_info
+        )?;
+
+        if *mint_account_account.key != mint || mint_account_account.owner != token_standard_program_account_info.key {
+            return Err(
+                BankError::InvalidMintAccount.into()
+            );
+        };
+
+        if let Some(decimals) = decimals {
+            let mint_decimals = Self::_unpack_mint(
+                &mint_account_account.try_borrow_data()?[..]
+            )?.decimals;
+            if decimals != mint_decimals {
+                return Err(
+                    BankError::DecimalsMismatch.into()
+                );
+            };
+        };
+
+        let expected_bank_associated_token_account = Self::_get_associated_token_account(
+            bank_account_info.key,
+            token_standard_program_account_info.key,
+            mint_account_account.key
+        );
+        if expected_bank_associated_token_account != *bank_assocoiated_token_account_info.key {
+            return Err(
+                BankError::InvalidBankAssociatedTokenAccount.into()
+            );
+        };
+
+        if *destination_token_account_info.key == *bank_assocoiated_token_account_info.key {
+            return Err(
+                BankError::SelfWithdrawalNotAllowed.into()
+            );
+        };
+
+        // nothing is invoked between this read and `transfer_checked` below, and the instructions of a transaction run one after another
+        // -> the balance `is_ok` is computed from is the one transferred from, it already reflects earlier withdrawals of the same transaction
+        let bank_token_account = Self::_unpack_token_account(
+            &bank_assocoiated_token_account_info.try_borrow_data()?[..]
+        )?;
+
+        let is_ok: bool = amount <= bank_token_account.amount;
+        let fail_reason = if is_ok {
+            SignatureFailReason::None
+        } else {
+            SignatureFailReason::InsufficientTokens
+        };
+
+        let signature_info = VerifiedSignature {
+            is_ok,
+            fail_reason,
+            time: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+            signature,
+            message: ed25519_data
+                .get(ED25519_MESSAGE_OFFSET..)
+                .ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?
+                .to_vec()
+        };
+
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        if revocation_epoch != bank_account.revocation_epoch {
+            return Err(
+                BankError::StaleRevocationEpoch.into()
+            );
+        };
+
+        bank_account.check_not_frozen()?;
+        bank_account.check_withdrawal_grace_period(
+            Clock::get()?.unix_timestamp
+        )?;
+        bank_account.check_memo_requirement(Some(&memo))?;
+
+        if is_gasless {
+            if !bank_account.config.gasless {
+                return Err(
+                    BankError::GaslessDisabled.into()
+                );
+            };
+
+            let destination_token_account = Self::_unpack_token_account(
+                &destination_token_account_info.try_borrow_data()?[..]
+            )?;
+            if destination_token_account.owner != to {
+                return Err(
+                    BankError::InvalidToPubkey.into()
+                );
+            };
+        };
+
+        Self::_check_token_recipient_not_blocked(
+            &bank_account,
+            destination_token_account_info
+        )?;
+
+        if let Some(max_amount) = bank_account.config.get_token_max_per_tx(&mint) {
+            if amount > max_amount {
+                return Err(
+                    BankError::ExceedsTokenMaxPerTx.into()
+                );
+            };
+        };
+
+        Self::_validate_mint_freezability(
+            &bank_account.config,
+            &Self::_unpack_mint(
+                &mint_account_account.try_borrow_data()?[..]
+            )?
+        )?;
+
+        bank_account.add_signature(&signature_info)?;
+
+        if is_ok {
+            bank_account.track_recipient(destination_token_account_info.key)?;
+        };
+
+        let new_size = bank_account
+            .try_to_vec()?
+            .len();
+
+        // realloc requires a writable account owned by the program
+        if !bank_account_info.is_writable {
+            return Err(
+                BankError::BankAccountNotWritable.into()
+            );
+        };
+        if bank_account_info.owner != program_id {
+            return Err(
+                ProgramError::InvalidAccountOwner
+            );
+        };
+
+        Self::_increase_bank_account_size_using_slack(
+            bank_account_info,
+            fund_account_info,
+            system_program_account_info,
+            new_size,
+            0
+        )?;
+        Self::_shrink_bank_account_size(
+            bank_account_info,
+            new_size
+        )?;
+
+        if !is_ok {
+            msg!("Insufficient token balance.");
+            
+            bank_account.serialize(
+                &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+            )?;
+
+            return Ok(());
+        };
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        let decimals = Self::_unpack_mint(
+            &mint_account_account.try_borrow_data()?[..]
+        )?.decimals;
+
+        invoke_signed(
+            &transfer_spl_token_checked(
+                token_standard_program_account_info.key,
+                bank_assocoiated_token_account_info.key,
+                mint_account_account.key,
+                destination_token_account_info.key,
+                bank_account_info.key,
+                &[],
+                amount,
+                decimals
+            )?,
+            &[
+                bank_assocoiated_token_account_info.clone(),
+                mint_account_account.clone(),
+                destination_token_account_info.clone(),
+                bank_account_info.clone()
+            ],
+            &[
+                &[
+                    b"user_bank_account",
+                    bank_account.seed_owner().as_ref(),
+                    bank_account.seed_index_bytes().as_slice(),
+                    &[bank_account.bump]
+                ]
+            ]
+        )?;
+
+        if close_emptied_ata && amount == bank_token_account.amount {
+            invoke_signed(
+                &close_token_account(
+                    token_standard_program_account_info.key,
+                    bank_assocoiated_token_account_info.key,
+                    fund_account_info.key,
+                    bank_account_info.key,
+                    &[]
+                )?,
+                &[
+                    bank_assocoiated_token_account_info.clone(),
+                    fund_account_info.clone(),
+                    bank_account_info.clone()
+                ],
+                &[
+                    &[
+                        b"user_bank_account",
+                        bank_account.seed_owner().as_ref(),
+                        bank_account.seed_index_bytes().as_slice(),
+                        &[bank_account.bump]
+                    ]
+                ]
+            )?;
+
+            msg!("bank-account's associated-token-account emptied and closed.");
+        };
+
+        if let Some(memo_program_account_info) = Self::_next_memo_program_account(accounts_info, &memo)? {
+            // the funder of a gasless withdrawal doesn't have to sign -> the bank-account signs the memo instead
+            if is_gasless {
+                Self::_invoke_memo_program_signed(
+                    memo_program_account_info,
+                    bank_account_info,
+                    memo.as_bytes().to_vec(),
+                    &[
+                        &[
+                            b"user_bank_account",
+                            bank_account.seed_owner().as_ref(),
+                            bank_account.seed_index_bytes().as_slice(),
+                            &[bank_account.bump]
+                        ]
+                    ]
+                )?;
+            } else {
+                Self::_invoke_memo_program(
+                    memo_program_account_info,
+                    withdrawer_account_info,
+                    memo.as_bytes().to_vec()
+                )?;
+            };
+        };
+
+        if let Some(receipt_account_info) = accounts_info.next() {
+            Self::_record_withdrawal_receipt(
+                program_id,
+                receipt_account_info,
+                bank_account_info,
+                destination_token_account_info.key,
+                mint_account_account.key,
+                amount
+            )?;
+        };
+
+        WithdrawEvent::emit(
+            WithdrawKind::SplTokensUsingEd25519,
+            amount,
+            destination_token_account_info.key
+        )?;
+
+        msg!("Withdraw compeleted. v2");
+
+        Ok(())
+    }
+
+    /// shared by the V1 and the relayed (V3) lamports withdrawals, `withdrawer_account_info` signs the memo unless the withdrawal is gasless
+    #[allow(clippy::too_many_arguments)]
+    fn _withdraw_lamports_using_ed25519<'a, 'b>(
+        program_id: &Pubkey,
+        accounts_info: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        bank_account_info: &'a AccountInfo<'b>,
+        fund_account_info: &'a AccountInfo<'b>,
+        withdrawer_account_info: &'a AccountInfo<'b>,
+        recepient_account_info: &'a AccountInfo<'b>,
+        system_program_account_info: &'a AccountInfo<'b>,
+        ed25519_data: &[u8],
+        message_v1: MessageV1,
+        is_gasless: bool
+    ) -> ProgramResult {
+        let MessageV1 {
+            signer,
+            signature,
+            to,
+            lamports,
+            memo,
+            revocation_epoch,
+            ..
+        } = message_v1;
+
+        if *fund_account_info.key == *bank_account_info.key {
+            return Err(
+                BankError::InvalidFunderAccount.into()
+            );
+        };
+
+        // the bank-account would pay itself while the funder still covers its growth
+        if to == *bank_account_info.key {
+            return Err(
+                BankError::SelfWithdrawalNotAllowed.into()
+            );
+        };
+
+        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSystemProgramAccount.into()
             );
         };
 
-        if *system_program_account_info.key != SYSTEM_PROGRAM_ID {
-            return Err(
-                ProgramError::Custom(
-                    BankError::InvalidSystemProgramAccount as u32
-                )
-            );
+        validate_signer_bank_account(
+            program_id,
+            &signer,
+            bank_account_info
+        )?;
+
+        // the data borrow ends with the statement, nothing is held across the realloc below
+        let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
+
+        if revocation_epoch != bank_account.revocation_epoch {
+            return Err(
+                BankError::StaleRevocationEpoch.into()
+            );
+        };
+
+        bank_account.check_not_frozen()?;
+        bank_account.check_withdrawal_grace_period(
+            Clock::get()?.unix_timestamp
+        )?;
+        bank_account.check_memo_requirement(Some(&memo))?;
+        bank_account.check_min_withdrawal(lamports)?;
+
+        if is_gasless {
+            if !bank_account.config.gasless {
+                return Err(
+                    BankError::GaslessDisabled.into()
+                );
+            };
+
+            if *recepient_account_info.key != to {
+                return Err(
+                    BankError::InvalidToPubkey.into()
+                );
+            };
+        };
+
+        bank_account.check_recipient_not_blocked(recepient_account_info.key)?;
+        validate_recipient(
+            program_id,
+            recepient_account_info
+        )?;
+
+        let current_size = bank_account_info.data_len();
+        let bank_account_balance = bank_account_info.lamports().saturating_sub(Rent::get()?.minimum_balance(current_size));
+        let is_ok: bool = lamports <= bank_account_balance;
+        let fail_reason = if is_ok {
+            SignatureFailReason::None
+        } else {
+            SignatureFailReason::InsufficientLamports
+        };
+
+        let sig_info = VerifiedSignature {
+            signature,
+            is_ok,
+            fail_reason,
+            message: ed25519_data
+                .get(ED25519_MESSAGE_OFFSET..)
+                .ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?
+                .to_vec(),
+            time: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot
+        };
+
+        bank_account.add_signature(&sig_info)?;
+
+        if is_ok {
+            bank_account.track_recipient(recepient_account_info.key)?;
+            bank_account.track_daily_withdrawal(
+                Clock::get()?.unix_timestamp,
+                recepient_account_info.key,
+                lamports
+            )?;
+        };
+
+        // size the account for the serialized state (including the new entry) and fund the growth
+        // before any serialization, so both the failed and the successful branch stay rent-exempt
+        let new_size = bank_account
+            .try_to_vec()?
+            .len();
+
+        Self::_increase_bank_account_size_using_slack(
+            bank_account_info,
+            fund_account_info,
+            system_program_account_info,
+            new_size,
+            if is_ok { lamports } else { 0 }
+        )?;
+        Self::_shrink_bank_account_size(
+            bank_account_info,
+            new_size
+        )?;
+
+        if !is_ok {
+            msg!("Insufficient lamport balance!");
+            
+            bank_account.serialize(
+                &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+            )?;
+
+            return Ok(());
+        };
+
+        if let Some(memo_program_account_info) = Self::_next_memo_program_account(accounts_info, &memo)? {
+            // the funder of a gasless withdrawal doesn't have to sign -> the bank-account signs the memo instead
+            if is_gasless {
+                Self::_invoke_memo_program_signed(
+                    memo_program_account_info,
+                    bank_account_info,
+                    memo.as_bytes().to_vec(),
+                    &[
+                        &[
+                            b"user_bank_account",
+                            bank_account.seed_owner().as_ref(),
+                            bank_account.seed_index_bytes().as_slice(),
+                            &[bank_account.bump]
+                        ]
+                    ]
+                )?;
+            } else {
+                Self::_invoke_memo_program(
+                    memo_program_account_info,
+                    withdrawer_account_info,
+                    memo.as_bytes().to_vec()
+                )?;
+            };
         };
 
-        if *token_standard_program_account_info.key != SPL_TOKEN_PROGRAM_ID {
-            return Err(
-                ProgramError::Custom(
-                    BankError::InvalidSplTokenProgramAccount as u32
-                )
-            );
+        let fee_bps = bank_account.config.get_fee_bps(lamports);
+        let fee = ((lamports as u128) * (fee_bps as u128) / (MAX_BPS as u128)) as u64;
+        if !bank_account.config.fee_tiers.is_empty() {
+            let fee_treasury_account_info = next_account_info(accounts_info)?;
+
+            if bank_account.config.fee_treasury != Some(*fee_treasury_account_info.key) {
+                return Err(
+                    BankError::InvalidFeeTreasury.into()
+                );
+            };
+
+            validate_recipient(
+                program_id,
+                fee_treasury_account_info
+            )?;
+
+            **fee_treasury_account_info.try_borrow_mut_lamports()? += fee;
+        };
+
+        **bank_account_info.try_borrow_mut_lamports()? -= lamports;
+        **recepient_account_info.try_borrow_mut_lamports()? += lamports - fee;
+
+        if let Some(receipt_account_info) = accounts_info.next() {
+            Self::_record_withdrawal_receipt(
+                program_id,
+                receipt_account_info,
+                bank_account_info,
+                recepient_account_info.key,
+                &Pubkey::default(),
+                lamports - fee
+            )?;
         };
 
-        if withdrawer_account_info.is_signer == false {
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        WithdrawEvent::emit(
+            WithdrawKind::LamportsUsingEd25519,
+            lamports - fee,
+            recepient_account_info.key
+        )?;
+
+        msg!("Withdraw compeleted. v1");
+
+        Ok(())
+    }
+
+    /// withdraws `lamports` to the recipient, everything above the rent-exempt minimum when it's `None`
+    fn _withdraw_lamports(
+        program_id: &Pubkey,
+        accounts_info: &[AccountInfo],
+        lamports: Option<u64>,
+        memo: &Option<String>
+    ) -> ProgramResult {
+        let accounts_info = &mut accounts_info.iter();
+
+        let authority_account_info = next_account_info(accounts_info)?;
+        let bank_account_info = next_account_info(accounts_info)?;
+        let recepient_account_info = next_account_info(accounts_info)?;
+
+        if !authority_account_info.is_signer {
             return Err(
                 ProgramError::MissingRequiredSignature
-            );
+            )
         };
 
         validate_bank_account(
             program_id,
-            &signer,
+            authority_account_info.key,
             bank_account_info
         )?;
 
-        if *mint_account_account.key != mint {
-            return Err(
-                ProgramError::Custom(
-                    BankError::InvalidMintAccount as u32
-                )
-            );
-        };
+        Self::_check_not_frozen(bank_account_info)?;
+        Self::_check_withdrawal_grace_period(bank_account_info)?;
+        Self::_check_memo_requirement(bank_account_info, memo)?;
 
-        let expected_bank_associated_token_account = Self::_get_associated_token_account(
-            bank_account_info.key,
-            token_standard_program_account_info.key,
-            mint_account_account.key
-        );
-        if expected_bank_associated_token_account != *bank_assocoiated_token_account_info.key {
+        Self::_check_recipient_not_blocked(
+            bank_account_info,
+            recepient_account_info.key
+        )?;
+        validate_recipient(
+            program_id,
+            recepient_account_info
+        )?;
+
+        Self::_track_recipient(
+            bank_account_info,
+            recepient_account_info.key
+        )?;
+
+        let space = bank_account_info.data_len();
+        let rent = Rent::get()?.minimum_balance(space);
+        // floors at the rent-exempt minimum (nothing is withdrawable if the account holds less than that)
+        let balance = bank_account_info
+            .lamports()
+            .checked_sub(rent)
+            .ok_or(
+                ProgramError::from(BankError::InsufficientLamportBalance)
+            )?;
+        let lamports = lamports.unwrap_or(balance);
+        if lamports > balance {
             return Err(
-                ProgramError::Custom(
-                    BankError::InvalidBankAssociatedTokenAccount as u32
-                )
+                BankError::InsufficientLamportBalance.into()
             );
         };
 
-        let bank_token_account = TokenAccount::unpack(
-        &bank_assocoiated_token_account_info
-                .data
-                .try_borrow()
-                .unwrap()[..]
-        ).unwrap();
+        if let Some(memo) = memo.as_ref().filter(|memo| !memo.is_empty()) {
+            let memo_program_account_info = next_account_info(accounts_info)?;
 
-        let is_ok: bool = if amount > bank_token_account.amount {
-            false
-        } else {
-            true
+            Self::_invoke_memo_program(
+                memo_program_account_info,
+                authority_account_info,
+                memo.as_bytes().to_vec()
+            )?;
         };
 
-        let signature_info = VerifiedSignature {
-            is_ok,
-            time: Clock::get().unwrap().unix_timestamp,
-            signature,
-            message: ed25519_data
-                .get(112..)
-                .unwrap()
-                .to_vec()
+        let bank_account_lamports = bank_account_info
+            .lamports()
+            .checked_sub(lamports)
+            .ok_or(
+                ProgramError::from(BankError::InsufficientLamportBalance)
+            )?;
+        let recepient_lamports = recepient_account_info
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        **bank_account_info.try_borrow_mut_lamports()? = bank_account_lamports;
+        **recepient_account_info.try_borrow_mut_lamports()? = recepient_lamports;
+
+        if let Some(receipt_account_info) = accounts_info.next() {
+            Self::_record_withdrawal_receipt(
+                program_id,
+                receipt_account_info,
+                bank_account_info,
+                recepient_account_info.key,
+                &Pubkey::default(),
+                lamports
+            )?;
         };
 
+        WithdrawEvent::emit(
+            WithdrawKind::Lamports,
+            lamports,
+            recepient_account_info.key
+        )?;
+
+        msg!("Lamports withdrawed.");
+
+        Ok(())
+    }
+
+    /// tracks the recipient of a withdrawal that has no funder, bank-account's own lamports cover the added bytes
+    fn _track_recipient(
+        bank_account_info: &AccountInfo,
+        recepient: &Pubkey
+    ) -> ProgramResult {
         let mut bank_account = try_from_slice_unchecked::<UserBankAccount>(
-            &bank_account_info
-                   .data
-                   .try_borrow()
-                   .unwrap()[..]
-        ).unwrap();
+            &bank_account_info.try_borrow_data()?[..]
+        )?;
 
-        bank_account.add_signature(&signature_info)?;
+        let recipients_count = bank_account.recipients.len();
+        bank_account.track_recipient(recepient)?;
+        if bank_account.recipients.len() == recipients_count {
+            return Ok(());
+        };
 
-        let space_to_add = signature_info
-            .try_to_vec()
-            .unwrap()
+        let new_size = bank_account
+            .try_to_vec()?
             .len();
-        let rent_for_space_increase = (
-            (space_to_add as u64) * Rent::get().unwrap().lamports_per_byte_year
-        ) * RENT_EXEMPT_YEARS_REQUIRED as u64;
+        if new_size > bank_account_info.data_len() {
+            bank_account_info.realloc(new_size, false)?;
+        };
+
+        bank_account.serialize(
+            &mut &mut bank_account_info.try_borrow_mut_data()?[..]
+        )?;
+
+        Ok(())
+    }
+
+    /// moves the rent of the bytes freed since bank-account was `previous_size` to the authority,
+    /// only that rent is refunded, the balance above the rent-exempt minimum stays
+    fn _refund_freed_rent(
+        bank_account_info: &AccountInfo,
+        authority_account_info: &AccountInfo,
+        previous_size: usize
+    ) -> Result<u64, ProgramError> {
+        let rent = Rent::get()?;
+        let minimum_balance = rent.minimum_balance(bank_account_info.data_len());
+        let freed_rent = rent.minimum_balance(previous_size)
+            .saturating_sub(minimum_balance)
+            .min(bank_account_info.lamports().saturating_sub(minimum_balance));
+
+        **bank_account_info.try_borrow_mut_lamports()? -= freed_rent;
+        **authority_account_info.try_borrow_mut_lamports()? += freed_rent;
+
+        Ok(freed_rent)
+    }
+
+    /// grows bank-account's data to `new_size` (if needed), the funder pays the rent for the added bytes
+    fn _increase_bank_account_size<'a>(
+        bank_account_info: &AccountInfo<'a>,
+        fund_account_info: &AccountInfo<'a>,
+        system_program_account_info: &AccountInfo<'a>,
+        new_size: usize
+    ) -> ProgramResult {
+        let current_size = bank_account_info.data_len();
+        if new_size <= current_size {
+            return Ok(());
+        };
+
+        let rent_for_space_increase = ((new_size - current_size) as u64)
+            .checked_mul(Rent::get()?.lamports_per_byte_year)
+            .and_then(|lamports| lamports.checked_mul(RENT_EXEMPT_YEARS_REQUIRED as u64))
+            .ok_or(
+                ProgramError::from(BankError::RentCalculationOverflow)
+            )?;
 
         invoke(
             &transfer_lamports(
@@ -612,156 +4128,243 @@ impl Processor {
             ]
         )?;
 
-        let bank_acc = bank_account_info.clone();
-        let current_size = bank_acc.data_len();
+        bank_account_info.realloc(
+            new_size,
+            false
+        )
+    }
+
+    /// ed25519 withdrawals -> a funder that signed pays the growth as usual, otherwise bank-account's lamports above
+    /// the new rent minimum (and the `reserved_lamports` about to leave it) must cover the growth, no transfer is made
+    fn _increase_bank_account_size_using_slack<'a>(
+        bank_account_info: &AccountInfo<'a>,
+        fund_account_info: &AccountInfo<'a>,
+        system_program_account_info: &AccountInfo<'a>,
+        new_size: usize,
+        reserved_lamports: u64
+    ) -> ProgramResult {
+        if fund_account_info.is_signer {
+            return Self::_increase_bank_account_size(
+                bank_account_info,
+                fund_account_info,
+                system_program_account_info,
+                new_size
+            );
+        };
+
+        if new_size <= bank_account_info.data_len() {
+            return Ok(());
+        };
 
-        drop(bank_acc);
+        let required_lamports = Rent::get()?
+            .minimum_balance(new_size)
+            .checked_add(reserved_lamports)
+            .ok_or(
+                ProgramError::from(BankError::RentCalculationOverflow)
+            )?;
+        if bank_account_info.lamports() < required_lamports {
+            return Err(
+                ProgramError::MissingRequiredSignature
+            );
+        };
 
         bank_account_info.realloc(
-            current_size + space_to_add,
+            new_size,
             false
-        ).unwrap();
-
-        if is_ok == false {
-            msg!("Insufficient token balance.");
-            
-            bank_account.serialize(
-                &mut &mut bank_account_info
-                    .data
-                    .try_borrow_mut()
-                    .unwrap()[..]
-            ).unwrap();
+        )
+    }
 
+    /// evicted signatures can leave the serialized state smaller than the account, the freed rent stays in bank-account's balance
+    fn _shrink_bank_account_size(
+        bank_account_info: &AccountInfo,
+        new_size: usize
+    ) -> ProgramResult {
+        if new_size >= bank_account_info.data_len() {
             return Ok(());
         };
 
-        bank_account.serialize(
-            &mut &mut bank_account_info
-                .data
-                .try_borrow_mut()
-                .unwrap()[..]
-        ).unwrap();
-
-        let decimals = Mint::unpack(
-            &mint_account_account
-                .data
-                .try_borrow()
-                .unwrap()[..]            
-        ).unwrap().decimals;
+        bank_account_info.realloc(
+            new_size,
+            false
+        )
+    }
 
-        invoke_signed(
-            &transfer_spl_token_checked(
-                token_standard_program_account_info.key,
-                bank_assocoiated_token_account_info.key,
-                mint_account_account.key,
-                destination_token_account_info.key,
-                bank_account_info.key,
-                &[],
-                amount,
-                decimals
-            ).unwrap(),
-            &[
-                bank_assocoiated_token_account_info.clone(),
-                mint_account_account.clone(),
-                destination_token_account_info.clone(),
-                bank_account_info.clone()
-            ],
-            &[
-                &[
-                    b"user_bank_account",
-                    signer.as_ref(),
-                    &[bank_account.bump]
-                ]
-            ]
+    /// counts the creation against the funder's cap, no-op until the program-config is initialized
+    fn _record_bank_account_creation<'a>(
+        program_id: &Pubkey,
+        program_config_account_info: &AccountInfo<'a>,
+        funding_account_info: &AccountInfo<'a>,
+        system_program_account_info: &AccountInfo<'a>
+    ) -> ProgramResult {
+        let (
+            program_config_addr,
+            _
+        ) = ProgramConfig::get_program_config_using_fpa(program_id);
+        if program_config_addr != *program_config_account_info.key {
+            return Err(
+                BankError::InvalidProgramConfigAccount.into()
+            );
+        };
+
+        if program_config_account_info.data_len() == 0_usize {
+            return Ok(());
+        };
+
+        let mut program_config = Self::_load_program_config(
+            program_id,
+            program_config_account_info
         )?;
 
-        if memo.len() > 0 {
-            let memo_program_account_info = next_account_info(accounts_info)?;
+        program_config.record_creation(
+            funding_account_info.key,
+            Clock::get()?.unix_timestamp
+        )?;
 
-            Self::_invoke_memo_program(
-                memo_program_account_info,
-                withdrawer_account_info,
-                memo.as_bytes().to_vec()
-            )?;
-        };
+        let new_size = program_config.try_to_vec()?.len();
+        Self::_increase_bank_account_size(
+            program_config_account_info,
+            funding_account_info,
+            system_program_account_info,
+            new_size
+        )?;
+        Self::_shrink_bank_account_size(
+            program_config_account_info,
+            new_size
+        )?;
 
-        msg!("Withdraw compeleted. v2");
+        program_config.serialize(
+            &mut &mut program_config_account_info.try_borrow_mut_data()?[..]
+        )?;
 
         Ok(())
     }
 
-    pub fn processor(
+    fn _load_program_config(
         program_id: &Pubkey,
-        accounts_info: &[AccountInfo],
-        instruction_data: &[u8]
-    ) -> ProgramResult {
-        let bank_instruction = BankInstruction::unpack(instruction_data).unwrap();
+        program_config_account_info: &AccountInfo
+    ) -> Result<ProgramConfig, ProgramError> {
+        if program_config_account_info.owner != program_id {
+            return Err(
+                BankError::InvalidProgramConfigAccount.into()
+            );
+        };
 
-        match bank_instruction {
-            BankInstruction::CreateBankAccount => {
-                msg!("Instruction: CreateBankAccount");
-                Self::process_create_initialize_bank_account(program_id, accounts_info)
-            },
-            BankInstruction::WithdrawLamports { lamports } => {
-                msg!("Instruction: WithdrawLamports");
-                Self::process_withdraw_lamports(program_id, accounts_info, &lamports)
-            },
-            BankInstruction::WithdrawSplTokens { amount } => {
-                msg!("Instruction: WithdrawSplTokens");
-                Self::process_withdraw_spl_tokens(program_id, accounts_info, &amount)
-            },
-            BankInstruction::WithdrawLamportsUsingEd25519Signature => {
-                msg!("Instruction: WithdrawLamportsUsingEd25519Signature");
-                Self::process_withdraw_lamports_using_ed25519_signature(program_id, accounts_info)
-            },
-            BankInstruction::WithdrawSplToknesUsingEd25519Signature => {
-                msg!("Instruction: WithdrawSplToknesUsingEd25519Signature");
-                Self::process_withdraw_spl_tokens_using_ed25519_signature(program_id, accounts_info)
-            }
-        }
+        let program_config = try_from_slice_unchecked::<ProgramConfig>(
+            &program_config_account_info.try_borrow_data()?[..]
+        ).map_err(|_| {
+            ProgramError::from(BankError::InvalidProgramConfigAccount)
+        })?;
+
+        if program_config.discriminator != ProgramConfig::get_program_config_discriminator() {
+            return Err(
+                BankError::InvalidProgramConfigAccount.into()
+            );
+        };
+
+        Ok(program_config)
     }
 
-    fn _invoke_memo_program(
-        memo_program_account_info: &AccountInfo,
-        message_sender_account_info: &AccountInfo,
-        memo_message: Vec<u8>
+    fn _record_withdrawal_receipt(
+        program_id: &Pubkey,
+        receipt_account_info: &AccountInfo,
+        bank_account_info: &AccountInfo,
+        recepient: &Pubkey,
+        mint: &Pubkey,
+        amount: u64
     ) -> ProgramResult {
-        if *memo_program_account_info.key != MEMO_PROGRAM_ID {
+        if receipt_account_info.owner != program_id {
             return Err(
-                ProgramError::Custom(
-                    BankError::InvalidMemoProgramAccount as u32
-                )
+                BankError::InvalidReceiptAccount.into()
             );
         };
 
-        invoke(
-            &Instruction {
-                program_id: *memo_program_account_info.key,
-                data: memo_message,
-                accounts: vec![
-                    AccountMeta::new_readonly(*message_sender_account_info.key, true)
-                ]
-            },
-            &[
-                message_sender_account_info.clone()
-            ]
+        let mut receipt = try_from_slice_unchecked::<WithdrawalReceipt>(
+            &receipt_account_info.try_borrow_data()?[..]
+        ).map_err(|_| {
+            ProgramError::from(BankError::InvalidReceiptAccount)
+        })?;
+
+        if receipt.discriminator != WithdrawalReceipt::get_receipt_discriminator() || receipt.bank_account != *bank_account_info.key {
+            return Err(
+                BankError::InvalidReceiptAccount.into()
+            );
+        };
+
+        let expected_receipt_account = WithdrawalReceipt::get_receipt_account_using_cpa(
+            bank_account_info.key,
+            &receipt.bump,
+            program_id
+        )?;
+        if expected_receipt_account != *receipt_account_info.key {
+            return Err(
+                BankError::InvalidReceiptAccount.into()
+            );
+        };
+
+        receipt.record_withdrawal(
+            recepient,
+            mint,
+            amount
+        );
+
+        receipt.serialize(
+            &mut &mut receipt_account_info.try_borrow_mut_data()?[..]
         )?;
 
         Ok(())
     }
 
+    fn _validate_token_program(
+        token_program_account_info: &AccountInfo
+    ) -> ProgramResult {
+        if *token_program_account_info.key != SPL_TOKEN_PROGRAM_ID && *token_program_account_info.key != SPL_TOKEN_2022_PROGRAM_ID {
+            return Err(
+                BankError::InvalidSplTokenProgramAccount.into()
+            );
+        };
+
+        Ok(())
+    }
+
+    /// Token-2022 accounts carry their extensions after the base state, which shares the classic token program's layout
+    fn _unpack_token_account(
+        token_account_data: &[u8]
+    ) -> Result<TokenAccount, ProgramError> {
+        let invalid_token_account_data = || {
+            BankError::InvalidTokenAccountData.into()
+        };
+
+        TokenAccount::unpack(
+            token_account_data
+                .get(..TokenAccount::LEN)
+                .ok_or_else(invalid_token_account_data)?
+        ).map_err(|_| invalid_token_account_data())
+    }
+
+    /// Token-2022 mints carry their extensions after the base state, which shares the classic token program's layout
+    fn _unpack_mint(
+        mint_data: &[u8]
+    ) -> Result<Mint, ProgramError> {
+        let invalid_mint_data = || {
+            BankError::InvalidMintData.into()
+        };
+
+        Mint::unpack(
+            mint_data
+                .get(..Mint::LEN)
+                .ok_or_else(invalid_mint_data)?
+        ).map_err(|_| invalid_mint_data())
+    }
+
     fn _get_associated_token_account(
         wallet_owner: &Pubkey,
         token_program_id: &Pubkey,
         mint_account: &Pubkey
     ) -> Pubkey {
-        Pubkey::try_find_program_address(
-            &[
-                wallet_owner.to_bytes().as_slice(),
-                token_program_id.to_bytes().as_slice(),
-                mint_account.to_bytes().as_slice()
-            ],
-            &spl_associated_token_account::id()
-        ).unwrap().0
+        UserBankAccount::associated_token_account(
+            wallet_owner,
+            token_program_id,
+            mint_account
+        ).0
     }
 }
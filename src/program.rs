@@ -1,10 +1,22 @@
-use solana_program::{
-    pubkey::Pubkey,
-    pubkey
-};
+// deployed address of the bank program -> `ID`, `id()` and `check_id()`
+solana_program::declare_id!("D18w8dw2qJQ5r8SNi89awoB2jGwuPV98T7Px9nJtQGr7");
 
-pub const PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+#[cfg(test)]
+mod test_program {
+    use {
+        super::*,
+        solana_program::{
+            pubkey::Pubkey,
+            system_program
+        }
+    };
 
-pub fn check_id(program_id: &Pubkey) -> bool {
-    *program_id == PROGRAM_ID
-}
\ No newline at end of file
+    #[test]
+    fn check_id_matches_declared_id() {
+        assert!(check_id(&ID));
+        assert!(check_id(&id()));
+
+        assert!(check_id(&Pubkey::new_unique()) == false);
+        assert!(check_id(&system_program::ID) == false, "Program id must not be the system program.");
+    }
+}
@@ -1,17 +1,53 @@
 use {
     solana_program::{
         pubkey::Pubkey,
-        hash::hash,
+        hash::{
+            hash,
+            hashv
+        },
         program_error::ProgramError,
-        entrypoint::ProgramResult
+        program_pack::{
+            IsInitialized,
+            Pack,
+            Sealed
+        },
+        entrypoint::ProgramResult,
+        program::{
+            set_return_data,
+            MAX_RETURN_DATA
+        },
+        clock::UnixTimestamp,
+        sysvar::{
+            clock::Clock,
+            Sysvar
+        }
     },
     borsh::{
         BorshDeserialize,
         BorshSerialize
     },
     crate::{
-        program::PROGRAM_ID,
-        error::BankError
+        error::BankError,
+        processor::{
+            DISCRIMINATOR_SIZE,
+            AUTHORITY_SIZE,
+            NEGATIVE_SIGNATURES_SIZE,
+            BUMP_SIZE,
+            CREATION_TIME_SIZE,
+            CONFIG_SIZE,
+            SIGNATURES_SIZE,
+            PARTIAL_AUTHORIZATIONS_SIZE,
+            RECIPIENTS_SIZE,
+            REVOCATION_EPOCH_SIZE,
+            TOTAL_DEPOSITED_SIZE,
+            ORIGINAL_OWNER_SIZE,
+            IS_FROZEN_SIZE,
+            DAILY_LIMIT_SIZE,
+            DAILY_WITHDRAWALS_SIZE,
+            LABEL_SIZE,
+            MIN_WITHDRAWAL_SIZE,
+            SEED_INDEX_SIZE
+        }
     }
 };
 
@@ -20,6 +56,15 @@ pub(crate) type Bump = u8;
 pub(crate) type Signature = [u8; 64];
 pub(crate) type Message = String; // utf-8 string
 
+/// cap on `UserBankAccount::signatures`, beyond it the oldest entries that no longer guard against replays are evicted
+pub const MAX_STORED_SIGNATURES: usize = 32;
+/// rolling window of `UserBankAccount::daily_limit`
+pub const DAILY_LIMIT_WINDOW_SECS: i64 = 24 * 60 * 60;
+/// smallest ed25519 lamports withdrawal (each one stores a signature at the funder's expense), unless the bank-account sets its own
+pub const MIN_ED25519_WITHDRAW_LAMPORTS: u64 = 5_000;
+/// most withdrawals one instruction can report, their events (kind, amount, to, timestamp) must fit in the return data
+pub const MAX_WITHDRAW_EVENTS: usize = (MAX_RETURN_DATA - 4) / (1 + 8 + 32 + 8);
+
 #[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize, Default)]
 pub struct UserBankAccount {
     /// discriminator
@@ -30,8 +75,108 @@ pub struct UserBankAccount {
     pub bump: u8,
     /// time of account creation
     pub account_created_at: i64,
+    /// bank-account's configurations
+    pub config: BankAccountConfig,
     /// signatures that bank-account's owner issued and beign used
-    pub signatures: Vec<VerifiedSignature>
+    pub signatures: Vec<VerifiedSignature>,
+    /// authorizations that are partially redeemed (fully consumed ones are moved into `signatures`)
+    pub partial_authorizations: Vec<PartialAuthorization>,
+    /// distinct recipients seen by withdrawals (only tracked when `max_distinct_recipients` is configured)
+    pub recipients: Vec<Pubkey>,
+    /// ed25519 messages must be signed for this epoch, bumped by `RevokeAll`
+    pub revocation_epoch: u64,
+    /// running total of lamports deposited through `Deposit`
+    pub total_deposited: u64,
+    /// owner whose pubkey seeds bank-account's PDA, set by the first `TransferAuthority` (None -> `authority`)
+    pub original_owner: Option<Pubkey>,
+    /// withdrawals are paused while set, toggled by `FreezeAccount`/`UnfreezeAccount`
+    pub is_frozen: bool,
+    /// cap on the lamports each recipient receives through ed25519 signatures within `DAILY_LIMIT_WINDOW_SECS` (0 -> no limit),
+    /// set by `SetDailyLimit`
+    pub daily_limit: u64,
+    /// (recipient, time, lamports) of the ed25519 lamports withdrawals within the window (only tracked when `daily_limit` is set)
+    pub daily_withdrawals: Vec<(Pubkey, i64, u64)>,
+    /// user-chosen name of the account for clients to display (e.g. "savings"), all zeros -> no label, set by `SetLabel`
+    pub label: [u8; 32],
+    /// smallest ed25519 lamports withdrawal (0 -> `MIN_ED25519_WITHDRAW_LAMPORTS`), set by `SetMinWithdrawal`
+    pub min_withdrawal: u64,
+    /// index in the seeds of bank-account's PDA, set by `CreateBankAccountWithIndex` (None -> authority's default bank-account)
+    pub seed_index: Option<u16>
+}
+
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize)]
+pub struct BankAccountConfig {
+    /// allows relayers to submit ed25519 withdrawals without the signature of the message's "to" account
+    pub gasless: bool,
+    /// per-transaction caps for ed25519 token withdrawals -> (mint, max amount)
+    pub token_max_per_tx: Vec<(Pubkey, u64)>,
+    /// bank-account (of the same authority) that receives the savings part of `WithdrawWithSavings`
+    pub savings_account: Option<Pubkey>,
+    /// cap on the number of distinct recipients of withdrawals
+    pub max_distinct_recipients: Option<u16>,
+    /// fee schedule of ed25519 lamport withdrawals -> (threshold, bps), sorted by threshold
+    pub fee_tiers: Vec<(u64, u16)>,
+    /// account that receives the fees of `fee_tiers`
+    pub fee_treasury: Option<Pubkey>,
+    /// seconds after bank-account's creation during which withdrawals are blocked
+    pub withdrawal_grace_secs: Option<u32>,
+    /// signers that can approve an `EmergencyWithdraw`
+    pub emergency_signers: Vec<Pubkey>,
+    /// number of distinct `emergency_signers` required by `EmergencyWithdraw` (0 -> disabled)
+    pub emergency_threshold: u8,
+    /// token withdrawals of mints with a freeze authority are rejected when disabled
+    pub allow_freezable_mints: bool,
+    /// every withdrawal must carry a non-empty memo
+    pub require_memo: bool,
+    /// allows withdrawals authorized by ed25519 signatures (disabled -> only authority-signed withdrawals)
+    pub ed25519_enabled: bool,
+    /// recipients every withdrawal to is rejected (bounded by `MAX_BLOCKED_RECIPIENTS`)
+    pub blocked_recipients: Vec<Pubkey>
+}
+
+impl Default for BankAccountConfig {
+    fn default() -> Self {
+        Self {
+            gasless: false,
+            token_max_per_tx: vec![],
+            savings_account: None,
+            max_distinct_recipients: None,
+            fee_tiers: vec![],
+            fee_treasury: None,
+            withdrawal_grace_secs: None,
+            emergency_signers: vec![],
+            emergency_threshold: 0,
+            allow_freezable_mints: true,
+            require_memo: false,
+            ed25519_enabled: true,
+            blocked_recipients: vec![]
+        }
+    }
+}
+
+impl BankAccountConfig {
+    pub fn get_token_max_per_tx(
+        &self,
+        mint: &Pubkey
+    ) -> Option<u64> {
+        self.token_max_per_tx
+            .iter()
+            .find(|(capped_mint, _)| capped_mint == mint)
+            .map(|(_, max_amount)| *max_amount)
+    }
+
+    /// fee of the highest tier whose threshold the amount meets (0 bps below the lowest tier)
+    pub fn get_fee_bps(
+        &self,
+        amount: u64
+    ) -> u16 {
+        self.fee_tiers
+            .iter()
+            .rev()
+            .find(|(threshold, _)| amount >= *threshold)
+            .map(|(_, bps)| *bps)
+            .unwrap_or(0)
+    }
 }
 
 #[derive(Debug, BorshDeserialize, BorshSerialize, Clone, PartialEq)]
@@ -40,13 +185,88 @@ pub struct VerifiedSignature {
     pub signature: [u8; 64],
     /// funds was sufficient or insufficient for this signature
     pub is_ok: bool,
+    /// why the signature was recorded with `is_ok == false` (`SignatureFailReason::None` when it's ok)
+    pub fail_reason: SignatureFailReason,
     /// signature activation time
     pub time: i64,
+    /// signature activation slot (ordering that doesn't depend on the cluster clock)
+    pub slot: u64,
     /// siganture's message section
     pub message: Vec<u8>
 }
 
+/// failure cause recorded on a `VerifiedSignature` (serialized as a single byte)
+#[derive(Debug, PartialEq, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub enum SignatureFailReason {
+    /// signature was redeemed successfully
+    None,
+    /// bank-account's lamports above the rent-exempt minimum didn't cover the withdrawal
+    InsufficientLamports,
+    /// bank-account's token-account didn't hold enough tokens
+    InsufficientTokens
+}
+
+impl VerifiedSignature {
+    /// the signed message can't be redeemed anymore -> its trailing `expires_at` passed,
+    /// a message without a parseable `expires_at` never expires (its entry keeps guarding against replays)
+    pub fn is_expired(
+        &self,
+        now: i64
+    ) -> bool {
+        std::str::from_utf8(&self.message)
+            .ok()
+            .and_then(|message| message.rsplit(",").next())
+            .and_then(|expires_at| expires_at.parse::<i64>().ok())
+            .is_some_and(|expires_at| now > expires_at)
+    }
+}
+
+#[derive(Debug, BorshDeserialize, BorshSerialize, Clone, PartialEq)]
+pub struct PartialAuthorization {
+    /// signature of the authorization, identifies the authorization
+    pub signature: [u8; 64],
+    /// amount of the authorization that is not redeemed yet
+    pub remaining_amount: u64
+}
+
 impl UserBankAccount {
+    /// size of bank-account's data once `n_signatures` signatures with `message_len`-byte messages are stored
+    /// (no partial authorizations, tracked recipients or transferred authority)
+    ///
+    /// a new bank-account keeps `NEGATIVE_SIGNATURES_SIZE` spare bytes, it's resized to its serialized state by the first signature
+    pub fn space_for(
+        n_signatures: usize,
+        message_len: usize
+    ) -> usize {
+        let created_space =
+            DISCRIMINATOR_SIZE +
+            AUTHORITY_SIZE +
+            NEGATIVE_SIGNATURES_SIZE +
+            BUMP_SIZE +
+            CREATION_TIME_SIZE +
+            CONFIG_SIZE +
+            SIGNATURES_SIZE +
+            PARTIAL_AUTHORIZATIONS_SIZE +
+            RECIPIENTS_SIZE +
+            REVOCATION_EPOCH_SIZE +
+            TOTAL_DEPOSITED_SIZE +
+            ORIGINAL_OWNER_SIZE +
+            IS_FROZEN_SIZE +
+            DAILY_LIMIT_SIZE +
+            DAILY_WITHDRAWALS_SIZE +
+            LABEL_SIZE +
+            MIN_WITHDRAWAL_SIZE +
+            SEED_INDEX_SIZE;
+        if n_signatures == 0 {
+            return created_space;
+        };
+
+        // signature + is_ok + fail_reason + time + slot + message (length prefix + bytes)
+        let signature_space = 64 + 1 + 1 + 8 + 8 + 4 + message_len;
+
+        created_space - NEGATIVE_SIGNATURES_SIZE + n_signatures * signature_space
+    }
+
     pub fn validate_owner(
         &self,
         expected_owner: &Pubkey
@@ -54,26 +274,31 @@ impl UserBankAccount {
         self.authority == *expected_owner
     }
 
+    /// pubkey in the seeds of bank-account's PDA, the PDA doesn't move when the authority is transferred
+    pub fn seed_owner(&self) -> Pubkey {
+        self.original_owner.unwrap_or(self.authority)
+    }
+
+    /// `seed_index` as it appears in the seeds of bank-account's PDA, empty for the authority's default bank-account
+    pub fn seed_index_bytes(&self) -> Vec<u8> {
+        self.seed_index
+            .map(|seed_index| seed_index.to_le_bytes().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// records a signature, a signature that's already stored is rejected even if its attempt failed (`is_ok == false`)
+    /// -> an underfunded authorization can't be resubmitted over and over, the owner signs a new message to retry
     pub fn add_signature(
         &mut self,
         signature_info: &VerifiedSignature
     ) -> ProgramResult  {
-        let mut is_exist: bool = false;
-        for sig_info in self.signatures.iter() {
-            if sig_info.signature == signature_info.signature && sig_info.is_ok == true {
-                is_exist = true;
-                break;
-            };
-        };
-
-        if is_exist == true {
+        if self.find_signature(&signature_info.signature).is_some() || self.get_partial_authorization(&signature_info.signature).is_some() {
             return Err(
-                ProgramError::Custom(
-                    BankError::SignatureAlreadyUsed as u32
-                )
+                BankError::SignatureAlreadyUsed.into()
             );
         };
 
+        self.make_room_for_signature(signature_info.time)?;
         self.signatures.push(
             signature_info.clone()
         );
@@ -81,6 +306,276 @@ impl UserBankAccount {
         Ok(())
     }
 
+    /// stored entry of the signature (redeemed or failed attempt)
+    pub fn find_signature(
+        &self,
+        signature: &[u8; 64]
+    ) -> Option<&VerifiedSignature> {
+        self.signatures
+            .iter()
+            .find(|sig_info| sig_info.signature == *signature)
+    }
+
+    /// the signature is stored and its withdrawal went through (`is_ok == true`)
+    pub fn is_signature_redeemed(
+        &self,
+        signature: &[u8; 64]
+    ) -> bool {
+        self.find_signature(signature)
+            .is_some_and(|sig_info| sig_info.is_ok)
+    }
+
+    /// (successful, failed) counts of the stored signatures
+    pub fn signature_stats(&self) -> (usize, usize) {
+        let successful = self.signatures
+            .iter()
+            .filter(|sig_info| sig_info.is_ok)
+            .count();
+
+        (successful, self.signatures.len() - successful)
+    }
+
+    /// evicts the oldest (by `time`) entries until a new signature fits under `MAX_STORED_SIGNATURES`,
+    /// only entries of expired messages are evicted -> a stored signature can't be replayed whether its attempt failed or not
+    fn make_room_for_signature(
+        &mut self,
+        now: i64
+    ) -> ProgramResult {
+        while self.signatures.len() >= MAX_STORED_SIGNATURES {
+            let oldest_evictable = self.signatures
+                .iter()
+                .enumerate()
+                .filter(|(_, sig_info)| sig_info.is_expired(now))
+                .min_by_key(|(_, sig_info)| sig_info.time)
+                .map(|(index, _)| index);
+
+            match oldest_evictable {
+                Some(index) => {
+                    self.signatures.remove(index);
+                },
+                None => return Err(
+                    BankError::SignatureStoreFull.into()
+                )
+            };
+        };
+
+        Ok(())
+    }
+
+    /// withdrawals are blocked while the bank-account is frozen
+    pub fn check_not_frozen(&self) -> ProgramResult {
+        if self.is_frozen {
+            return Err(
+                BankError::AccountFrozen.into()
+            );
+        };
+
+        Ok(())
+    }
+
+    /// withdrawals are blocked until `withdrawal_grace_secs` elapsed since bank-account's creation
+    pub fn check_withdrawal_grace_period(
+        &self,
+        now: i64
+    ) -> ProgramResult {
+        if let Some(withdrawal_grace_secs) = self.config.withdrawal_grace_secs {
+            if now < self.account_created_at.saturating_add(withdrawal_grace_secs as i64) {
+                return Err(
+                    BankError::WithdrawalGracePeriodActive.into()
+                );
+            };
+        };
+
+        Ok(())
+    }
+
+    pub fn check_memo_requirement(
+        &self,
+        memo: Option<&str>
+    ) -> ProgramResult {
+        if self.config.require_memo && memo.unwrap_or_default().is_empty() {
+            return Err(
+                BankError::MemoRequired.into()
+            );
+        };
+
+        Ok(())
+    }
+
+    pub fn check_ed25519_enabled(&self) -> ProgramResult {
+        if !self.config.ed25519_enabled {
+            return Err(
+                BankError::Ed25519WithdrawalsDisabled.into()
+            );
+        };
+
+        Ok(())
+    }
+
+    pub fn check_recipient_not_blocked(
+        &self,
+        recepient: &Pubkey
+    ) -> ProgramResult {
+        if self.config.blocked_recipients.contains(recepient) {
+            return Err(
+                BankError::RecipientBlocked.into()
+            );
+        };
+
+        Ok(())
+    }
+
+    /// records `recepient` as a distinct recipient, no-op when `max_distinct_recipients` isn't configured
+    pub fn track_recipient(
+        &mut self,
+        recepient: &Pubkey
+    ) -> ProgramResult {
+        let max_distinct_recipients = match self.config.max_distinct_recipients {
+            Some(max_distinct_recipients) => max_distinct_recipients,
+            None => return Ok(())
+        };
+
+        if self.recipients.contains(recepient) {
+            return Ok(());
+        };
+
+        if self.recipients.len() >= max_distinct_recipients as usize {
+            return Err(
+                BankError::TooManyRecipients.into()
+            );
+        };
+
+        self.recipients.push(*recepient);
+
+        Ok(())
+    }
+
+    /// ed25519 lamports withdrawals below the minimum are rejected before any signature is stored
+    pub fn check_min_withdrawal(
+        &self,
+        lamports: u64
+    ) -> ProgramResult {
+        let min_withdrawal = if self.min_withdrawal == 0 {
+            MIN_ED25519_WITHDRAW_LAMPORTS
+        } else {
+            self.min_withdrawal
+        };
+
+        if lamports < min_withdrawal {
+            return Err(
+                BankError::WithdrawalBelowMinimum.into()
+            );
+        };
+
+        Ok(())
+    }
+
+    /// records an ed25519 lamports withdrawal to `recipient` against its `daily_limit`, entries older than the window are dropped,
+    /// no-op when `daily_limit` isn't set
+    pub fn track_daily_withdrawal(
+        &mut self,
+        now: i64,
+        recipient: &Pubkey,
+        lamports: u64
+    ) -> ProgramResult {
+        if self.daily_limit == 0 {
+            return Ok(());
+        };
+
+        self.daily_withdrawals.retain(|(_, time, _)| now - *time < DAILY_LIMIT_WINDOW_SECS);
+
+        let withdrawn_in_window = self.daily_withdrawals
+            .iter()
+            .filter(|(withdrawal_recipient, _, _)| withdrawal_recipient == recipient)
+            .try_fold(lamports, |total, (_, _, withdrawn)| total.checked_add(*withdrawn))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if withdrawn_in_window > self.daily_limit {
+            return Err(
+                BankError::DailyLimitExceeded.into()
+            );
+        };
+
+        self.daily_withdrawals.push((*recipient, now, lamports));
+
+        Ok(())
+    }
+
+    /// lamports `recipient` can still receive within the current window of `daily_limit` (a new recipient -> the whole limit),
+    /// `None` when `daily_limit` isn't set
+    pub fn daily_limit_remaining(
+        &self,
+        now: i64,
+        recipient: Option<&Pubkey>
+    ) -> Option<u64> {
+        if self.daily_limit == 0 {
+            return None;
+        };
+
+        let withdrawn_in_window = self.daily_withdrawals
+            .iter()
+            .filter(|(withdrawal_recipient, time, _)| Some(withdrawal_recipient) == recipient && now - *time < DAILY_LIMIT_WINDOW_SECS)
+            .fold(0u64, |total, (_, _, withdrawn)| total.saturating_add(*withdrawn));
+
+        Some(
+            self.daily_limit.saturating_sub(withdrawn_in_window)
+        )
+    }
+
+    pub fn get_partial_authorization(
+        &self,
+        signature: &[u8; 64]
+    ) -> Option<&PartialAuthorization> {
+        self.partial_authorizations
+            .iter()
+            .find(|authorization| authorization.signature == *signature)
+    }
+
+    /// redeems `amount` of the authorization (`total_amount` is the authorized amount of the signed message),
+    /// once fully consumed the authorization is recorded as a used signature, any stored signature (even a failed one) is rejected
+    pub fn redeem_partial_authorization(
+        &mut self,
+        signature_info: &VerifiedSignature,
+        total_amount: u64,
+        amount: u64
+    ) -> ProgramResult {
+        if let Some(sig_info) = self.find_signature(&signature_info.signature) {
+            return Err(
+                if sig_info.is_ok {
+                    BankError::AuthorizationFullyConsumed.into()
+                } else {
+                    BankError::SignatureAlreadyUsed.into()
+                }
+            );
+        };
+
+        let remaining_amount = self.get_partial_authorization(&signature_info.signature)
+            .map(|authorization| authorization.remaining_amount)
+            .unwrap_or(total_amount);
+        if amount == 0 || amount > remaining_amount {
+            return Err(
+                BankError::InvalidLamports.into()
+            );
+        };
+
+        self.partial_authorizations.retain(|authorization| authorization.signature != signature_info.signature);
+
+        if remaining_amount - amount == 0 {
+            self.make_room_for_signature(signature_info.time)?;
+            self.signatures.push(
+                signature_info.clone()
+            );
+        } else {
+            self.partial_authorizations.push(
+                PartialAuthorization {
+                    signature: signature_info.signature,
+                    remaining_amount: remaining_amount - amount
+                }
+            );
+        };
+
+        Ok(())
+    }
+
     pub fn get_user_bank_account_using_fpa(
         user: &Pubkey,
         program_id: &Pubkey
@@ -98,7 +593,7 @@ impl UserBankAccount {
         user: &Pubkey,
         bump: &u8,
         program_id: &Pubkey
-    ) -> PdaAddress {
+    ) -> Result<PdaAddress, ProgramError> {
         Pubkey::create_program_address(
             &[
                 b"user_bank_account",
@@ -106,9 +601,62 @@ impl UserBankAccount {
                 &[*bump]
             ],
             program_id
+        ).map_err(|_| ProgramError::InvalidSeeds)
+    }
+
+    pub fn get_user_bank_account_with_index(
+        user: &Pubkey,
+        index: u16,
+        program_id: &Pubkey
+    ) -> (PdaAddress, Bump) {
+        Pubkey::try_find_program_address(
+            &[
+                b"user_bank_account",
+                user.to_bytes().as_slice(),
+                index.to_le_bytes().as_slice()
+            ],
+            program_id
         ).unwrap()
     }
+
+    /// same as `get_user_bank_account_using_cpa` when `index` is None (an empty seed doesn't change the address)
+    pub fn get_user_bank_account_with_index_using_cpa(
+        user: &Pubkey,
+        index: &Option<u16>,
+        bump: &u8,
+        program_id: &Pubkey
+    ) -> Result<PdaAddress, ProgramError> {
+        let index_bytes = index
+            .map(|index| index.to_le_bytes().to_vec())
+            .unwrap_or_default();
+
+        Pubkey::create_program_address(
+            &[
+                b"user_bank_account",
+                user.to_bytes().as_slice(),
+                index_bytes.as_slice(),
+                &[*bump]
+            ],
+            program_id
+        ).map_err(|_| ProgramError::InvalidSeeds)
+    }
     
+    /// canonical associated-token-account of the bank-account for `mint`
+    pub fn associated_token_account(
+        bank_pda: &Pubkey,
+        token_program: &Pubkey,
+        mint: &Pubkey
+    ) -> (PdaAddress, Bump) {
+        Pubkey::try_find_program_address(
+            &[
+                bank_pda.to_bytes().as_slice(),
+                token_program.to_bytes().as_slice(),
+                mint.to_bytes().as_slice()
+            ],
+            &spl_associated_token_account::id()
+        ).unwrap()
+    }
+
     pub fn get_bank_account_discriminator() -> [u8; 8] {
         hash(b"account:bank_account")
             .as_ref()
@@ -117,4 +665,811 @@ impl UserBankAccount {
             .map(|dis: [u8; 8]| dis)
             .unwrap()
     }
+
+    /// fixed-size prefix of the account (everything before the variable-size fields)
+    pub fn header(&self) -> UserBankAccountHeader {
+        UserBankAccountHeader {
+            discriminator: self.discriminator,
+            authority: self.authority,
+            bump: self.bump,
+            account_created_at: self.account_created_at
+        }
+    }
+}
+
+/// stable fixed-layout prefix of `UserBankAccount` -> readable through `Pack` without borsh,
+/// unpack it from `&data[..UserBankAccountHeader::LEN]` of the bank-account
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct UserBankAccountHeader {
+    pub discriminator: [u8; 8],
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub account_created_at: i64
+}
+
+impl Sealed for UserBankAccountHeader {}
+
+impl IsInitialized for UserBankAccountHeader {
+    fn is_initialized(&self) -> bool {
+        self.discriminator == UserBankAccount::get_bank_account_discriminator()
+    }
+}
+
+impl Pack for UserBankAccountHeader {
+    const LEN: usize = DISCRIMINATOR_SIZE + AUTHORITY_SIZE + BUMP_SIZE + CREATION_TIME_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let (discriminator, rest) = dst.split_at_mut(DISCRIMINATOR_SIZE);
+        let (authority, rest) = rest.split_at_mut(AUTHORITY_SIZE);
+        let (bump, account_created_at) = rest.split_at_mut(BUMP_SIZE);
+
+        discriminator.copy_from_slice(&self.discriminator);
+        authority.copy_from_slice(self.authority.as_ref());
+        bump[0] = self.bump;
+        account_created_at.copy_from_slice(&self.account_created_at.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (discriminator, rest) = src.split_at(DISCRIMINATOR_SIZE);
+        let (authority, rest) = rest.split_at(AUTHORITY_SIZE);
+        let (bump, account_created_at) = rest.split_at(BUMP_SIZE);
+
+        Ok(
+            UserBankAccountHeader {
+                discriminator: discriminator
+                    .try_into()
+                    .map_err(|_| ProgramError::InvalidAccountData)?,
+                authority: Pubkey::try_from(authority)
+                    .map_err(|_| ProgramError::InvalidAccountData)?,
+                bump: bump[0],
+                account_created_at: i64::from_le_bytes(
+                    account_created_at
+                        .try_into()
+                        .map_err(|_| ProgramError::InvalidAccountData)?
+                )
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize)]
+pub struct RentExemptStatus {
+    /// bank-account's current lamports balance
+    pub lamports: u64,
+    /// rent-exempt minimum balance for bank-account's current data size
+    pub minimum_balance: u64,
+    /// lamports above (positive) or below (negative) the rent-exempt minimum
+    pub surplus: i64
+}
+
+/// policy that limits `EffectiveWithdrawable::amount`
+#[derive(Debug, PartialEq, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub enum WithdrawableLimit {
+    /// lamports above the rent-exempt minimum of bank-account's current data size
+    RentFloor,
+    /// balance of bank-account's token-account
+    TokenBalance,
+    /// per-transaction cap of the mint (`BankAccountConfig::token_max_per_tx`)
+    TokenMaxPerTx,
+    /// withdrawals are blocked during the grace period after bank-account's creation
+    WithdrawalGracePeriod,
+    /// lamports left of the recipient's `UserBankAccount::daily_limit` within the current window
+    DailyLimit,
+    /// withdrawals are blocked while bank-account is frozen
+    AccountFrozen
+}
+
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize)]
+pub struct EffectiveWithdrawable {
+    /// amount withdrawable right now (lamports, or tokens when queried with a token-account)
+    pub amount: u64,
+    /// policy that limits the amount
+    pub limit: WithdrawableLimit
+}
+
+/// summary of bank-account reported by `GetAccountInfo`
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize)]
+pub struct BankAccountSummary {
+    /// authority of the bank-account
+    pub authority: Pubkey,
+    /// time of account creation
+    pub account_created_at: i64,
+    /// number of stored signatures
+    pub signatures_count: u32
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, BorshDeserialize, BorshSerialize)]
+pub enum WithdrawKind {
+    Lamports,
+    SplTokens,
+    LamportsUsingEd25519,
+    SplTokensUsingEd25519,
+    LamportsWithSavings,
+    EmergencyLamports,
+    CloseBankAccountFull,
+    SweepSplTokens,
+    LamportsBatch,
+    PartialLamportsUsingEd25519,
+    RedeemPaymentEscrow
+}
+
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize)]
+pub struct WithdrawEvent {
+    /// which withdraw instruction completed
+    pub kind: WithdrawKind,
+    /// amount received by the recipient (lamports or token base units)
+    pub amount: u64,
+    /// recipient (wallet or token account)
+    pub to: Pubkey,
+    /// time of the withdrawal
+    pub timestamp: UnixTimestamp
+}
+
+impl WithdrawEvent {
+    /// Publishes the event as the instruction's return data so indexers can read it from the transaction metadata
+    pub fn emit(
+        kind: WithdrawKind,
+        amount: u64,
+        to: &Pubkey
+    ) -> ProgramResult {
+        set_return_data(
+            &Self::new(kind, amount, to)?.try_to_vec()?
+        );
+
+        Ok(())
+    }
+
+    /// Publishes the events of an instruction with several withdrawals (batch, sweep) together as a `Vec<WithdrawEvent>`,
+    /// one return data per instruction -> emitting them one by one would keep only the last
+    pub fn emit_all(
+        events: &[WithdrawEvent]
+    ) -> ProgramResult {
+        if events.len() > MAX_WITHDRAW_EVENTS {
+            return Err(
+                BankError::TooManyWithdrawals.into()
+            );
+        };
+
+        set_return_data(
+            &events.try_to_vec()?
+        );
+
+        Ok(())
+    }
+
+    pub fn new(
+        kind: WithdrawKind,
+        amount: u64,
+        to: &Pubkey
+    ) -> Result<Self, ProgramError> {
+        Ok(
+            Self {
+                kind,
+                amount,
+                to: *to,
+                timestamp: Clock::get()?.unix_timestamp
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize, Default)]
+pub struct WithdrawalReceipt {
+    /// discriminator
+    pub discriminator: [u8; 8],
+    /// bank-account which this receipt belongs to
+    pub bank_account: Pubkey,
+    /// bump of the receipt's PDA
+    pub bump: u8,
+    /// number of withdrawals chained into the hash
+    pub withdrawals_count: u64,
+    /// rolling hash of all withdrawals -> H = hash(H_prev || withdrawal_details)
+    pub hash: [u8; 32]
+}
+
+impl WithdrawalReceipt {
+    pub const LEN: usize = 8 + 32 + 1 + 8 + 32;
+
+    /// withdrawal_details -> recepient || mint (default pubkey for lamports) || amount (little-endian)
+    pub fn record_withdrawal(
+        &mut self,
+        recepient: &Pubkey,
+        mint: &Pubkey,
+        amount: u64
+    ) {
+        self.hash = hashv(
+            &[
+                self.hash.as_slice(),
+                recepient.as_ref(),
+                mint.as_ref(),
+                amount.to_le_bytes().as_slice()
+            ]
+        ).to_bytes();
+        self.withdrawals_count += 1;
+    }
+
+    pub fn get_receipt_account_using_fpa(
+        bank_account: &Pubkey,
+        program_id: &Pubkey
+    ) -> (PdaAddress, Bump) {
+        Pubkey::try_find_program_address(
+            &[
+                b"receipt",
+                bank_account.to_bytes().as_slice()
+            ],
+            program_id
+        ).unwrap()
+    }
+
+    pub fn get_receipt_account_using_cpa(
+        bank_account: &Pubkey,
+        bump: &u8,
+        program_id: &Pubkey
+    ) -> Result<PdaAddress, ProgramError> {
+        Pubkey::create_program_address(
+            &[
+                b"receipt",
+                bank_account.to_bytes().as_slice(),
+                &[*bump]
+            ],
+            program_id
+        ).map_err(|_| ProgramError::InvalidSeeds)
+    }
+
+    pub fn get_receipt_discriminator() -> [u8; 8] {
+        hash(b"account:withdrawal_receipt")
+            .as_ref()
+            .get(..8)
+            .and_then(|slice| slice.try_into().ok())
+            .map(|dis: [u8; 8]| dis)
+            .unwrap()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize, Default)]
+pub struct PaymentEscrow {
+    /// discriminator
+    pub discriminator: [u8; 8],
+    /// bank-account which funded this escrow (receives the escrow's rent back on redeem)
+    pub bank_account: Pubkey,
+    /// nonce of the escrow's PDA
+    pub nonce: u64,
+    /// bump of the escrow's PDA
+    pub bump: u8,
+    /// ed25519 key whose signature redeems the escrow
+    pub claim_signer: Pubkey,
+    /// escrowed lamports
+    pub amount: u64
+}
+
+impl PaymentEscrow {
+    pub const LEN: usize = 8 + 32 + 8 + 1 + 32 + 8;
+
+    pub fn get_payment_escrow_using_fpa(
+        bank_account: &Pubkey,
+        nonce: u64,
+        program_id: &Pubkey
+    ) -> (PdaAddress, Bump) {
+        Pubkey::try_find_program_address(
+            &[
+                b"payment_escrow",
+                bank_account.to_bytes().as_slice(),
+                nonce.to_le_bytes().as_slice()
+            ],
+            program_id
+        ).unwrap()
+    }
+
+    pub fn get_payment_escrow_using_cpa(
+        bank_account: &Pubkey,
+        nonce: u64,
+        bump: &u8,
+        program_id: &Pubkey
+    ) -> Result<PdaAddress, ProgramError> {
+        Pubkey::create_program_address(
+            &[
+                b"payment_escrow",
+                bank_account.to_bytes().as_slice(),
+                nonce.to_le_bytes().as_slice(),
+                &[*bump]
+            ],
+            program_id
+        ).map_err(|_| ProgramError::InvalidSeeds)
+    }
+
+    pub fn get_payment_escrow_discriminator() -> [u8; 8] {
+        hash(b"account:payment_escrow")
+            .as_ref()
+            .get(..8)
+            .and_then(|slice| slice.try_into().ok())
+            .map(|dis: [u8; 8]| dis)
+            .unwrap()
+    }
+}
+
+/// program-wide configurations (single PDA -> seeds ["program_config"]), bank-account creations are only
+/// rate limited once it's initialized
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize, Default)]
+pub struct ProgramConfig {
+    /// discriminator
+    pub discriminator: [u8; 8],
+    /// bump of the program-config's PDA
+    pub bump: u8,
+    /// account allowed to update the program-config (the one that initialized it)
+    pub admin: Pubkey,
+    /// cap on bank-accounts created by a single funder within `creation_window_secs`
+    pub max_creations_per_funder: u16,
+    /// length of the window that a funder's creations are counted in
+    pub creation_window_secs: u32,
+    /// funders whose window is still open
+    pub funder_creations: Vec<FunderCreations>
+}
+
+#[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize)]
+pub struct FunderCreations {
+    /// funding account of the bank-account creations
+    pub funder: Pubkey,
+    /// time of the first creation of the window
+    pub window_started_at: i64,
+    /// bank-accounts created within the window
+    pub count: u16
+}
+
+impl ProgramConfig {
+    pub const LEN: usize = 8 + 1 + 32 + 2 + 4 + 4;
+
+    /// counts a bank-account creation of `funder`, funders whose window closed are dropped first
+    pub fn record_creation(
+        &mut self,
+        funder: &Pubkey,
+        now: i64
+    ) -> ProgramResult {
+        let creation_window_secs = self.creation_window_secs as i64;
+        self.funder_creations.retain(|funder_creations| {
+            now.saturating_sub(funder_creations.window_started_at) < creation_window_secs
+        });
+
+        let max_creations_per_funder = self.max_creations_per_funder;
+        match self.funder_creations.iter_mut().find(|funder_creations| funder_creations.funder == *funder) {
+            Some(funder_creations) => {
+                if funder_creations.count >= max_creations_per_funder {
+                    return Err(
+                        BankError::CreationRateExceeded.into()
+                    );
+                };
+
+                funder_creations.count += 1;
+            },
+            None => {
+                if max_creations_per_funder == 0 {
+                    return Err(
+                        BankError::CreationRateExceeded.into()
+                    );
+                };
+
+                self.funder_creations.push(
+                    FunderCreations {
+                        funder: *funder,
+                        window_started_at: now,
+                        count: 1
+                    }
+                );
+            }
+        };
+
+        Ok(())
+    }
+
+    pub fn get_program_config_using_fpa(
+        program_id: &Pubkey
+    ) -> (PdaAddress, Bump) {
+        Pubkey::try_find_program_address(
+            &[
+                b"program_config"
+            ],
+            program_id
+        ).unwrap()
+    }
+
+    pub fn get_program_config_discriminator() -> [u8; 8] {
+        hash(b"account:program_config")
+            .as_ref()
+            .get(..8)
+            .and_then(|slice| slice.try_into().ok())
+            .map(|dis: [u8; 8]| dis)
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test_state {
+    use super::*;
+
+    #[test]
+    fn space_for_matches_create_space() {
+        assert_eq!(
+            UserBankAccount::space_for(0, 0),
+            DISCRIMINATOR_SIZE +
+            AUTHORITY_SIZE +
+            NEGATIVE_SIGNATURES_SIZE +
+            BUMP_SIZE +
+            CREATION_TIME_SIZE +
+            CONFIG_SIZE +
+            SIGNATURES_SIZE +
+            PARTIAL_AUTHORIZATIONS_SIZE +
+            RECIPIENTS_SIZE +
+            REVOCATION_EPOCH_SIZE +
+            TOTAL_DEPOSITED_SIZE +
+            ORIGINAL_OWNER_SIZE +
+            IS_FROZEN_SIZE +
+            DAILY_LIMIT_SIZE +
+            DAILY_WITHDRAWALS_SIZE +
+            LABEL_SIZE +
+            MIN_WITHDRAWAL_SIZE +
+            SEED_INDEX_SIZE,
+            "Create space mismatch."
+        );
+        // the spare bytes aren't part of the serialized state
+        assert_eq!(
+            UserBankAccount::space_for(0, 0) - NEGATIVE_SIGNATURES_SIZE,
+            UserBankAccount::default().try_to_vec().unwrap().len(),
+            "Serialized size mismatch."
+        );
+    }
+
+    #[test]
+    fn space_for_matches_serialized_signatures() {
+        let message = "7BeGyfAGgehC6fVP7QPHhWgGjwSpaJivN16EQHW6oYTt,1000,,4102444800".as_bytes().to_vec();
+
+        for n_signatures in [1, 2, MAX_STORED_SIGNATURES] {
+            let bank_account = UserBankAccount {
+                signatures: vec![
+                    VerifiedSignature {
+                        signature: [1; 64],
+                        is_ok: true,
+                        fail_reason: SignatureFailReason::None,
+                        time: 1,
+                        slot: 1,
+                        message: message.clone()
+                    };
+                    n_signatures
+                ],
+                ..UserBankAccount::default()
+            };
+
+            assert_eq!(
+                UserBankAccount::space_for(n_signatures, message.len()),
+                bank_account.try_to_vec().unwrap().len(),
+                "Space mismatch for {} signatures.",
+                n_signatures
+            );
+        };
+    }
+
+    #[test]
+    fn find_signature_and_is_signature_redeemed() {
+        let new_signature = |signature: [u8; 64], is_ok: bool| {
+            VerifiedSignature {
+                signature,
+                is_ok,
+                fail_reason: if is_ok == true { SignatureFailReason::None } else { SignatureFailReason::InsufficientLamports },
+                time: 1,
+                slot: 1,
+                message: vec![]
+            }
+        };
+        let bank_account = UserBankAccount {
+            signatures: vec![
+                new_signature([1; 64], true),
+                new_signature([2; 64], false)
+            ],
+            ..UserBankAccount::default()
+        };
+
+        // present and ok
+        assert_eq!(bank_account.find_signature(&[1; 64]), Some(&new_signature([1; 64], true)));
+        assert!(bank_account.is_signature_redeemed(&[1; 64]));
+
+        // present but failed
+        assert_eq!(bank_account.find_signature(&[2; 64]), Some(&new_signature([2; 64], false)));
+        assert!(bank_account.is_signature_redeemed(&[2; 64]) == false);
+
+        // absent
+        assert_eq!(bank_account.find_signature(&[3; 64]), None);
+        assert!(bank_account.is_signature_redeemed(&[3; 64]) == false);
+    }
+
+    #[test]
+    fn verified_signature_without_expires_at_never_expires() {
+        let new_signature = |message: &str| {
+            VerifiedSignature {
+                signature: [1; 64],
+                is_ok: true,
+                fail_reason: SignatureFailReason::None,
+                time: 1,
+                slot: 1,
+                message: message.as_bytes().to_vec()
+            }
+        };
+
+        assert!(new_signature("7BeGyfAGgehC6fVP7QPHhWgGjwSpaJivN16EQHW6oYTt,1000,,100").is_expired(101));
+        assert!(!new_signature("7BeGyfAGgehC6fVP7QPHhWgGjwSpaJivN16EQHW6oYTt,1000,,100").is_expired(100));
+        assert!(!new_signature("7BeGyfAGgehC6fVP7QPHhWgGjwSpaJivN16EQHW6oYTt,1000,").is_expired(i64::MAX));
+        assert!(!new_signature("").is_expired(i64::MAX));
+    }
+
+    #[test]
+    fn redeem_partial_authorization_fail_stored_failed_signature() {
+        let signature_info = VerifiedSignature {
+            signature: [1; 64],
+            is_ok: false,
+            fail_reason: SignatureFailReason::InsufficientLamports,
+            time: 1,
+            slot: 1,
+            message: vec![]
+        };
+        let mut bank_account = UserBankAccount {
+            signatures: vec![signature_info.clone()],
+            ..UserBankAccount::default()
+        };
+
+        assert_eq!(
+            bank_account.redeem_partial_authorization(&signature_info, 1000, 100).unwrap_err(),
+            ProgramError::Custom(
+                BankError::SignatureAlreadyUsed as u32
+            )
+        );
+        assert_eq!(bank_account.signatures.len(), 1, "Signatures count mismatch.");
+        assert!(bank_account.partial_authorizations.is_empty(), "Partial authorizations must stay empty.");
+    }
+
+    #[test]
+    fn make_room_for_signature_keeps_unexpired_failed_signatures() {
+        let new_signature = |index: usize, is_ok: bool, expires_at: i64| {
+            let mut signature = [0u8; 64];
+            signature[..8].copy_from_slice(index.to_le_bytes().as_slice());
+
+            VerifiedSignature {
+                signature,
+                is_ok,
+                fail_reason: if is_ok == true { SignatureFailReason::None } else { SignatureFailReason::InsufficientLamports },
+                time: index as i64,
+                slot: index as u64,
+                message: format!("{},1000,,{}", Pubkey::default(), expires_at).into_bytes()
+            }
+        };
+
+        let mut bank_account = UserBankAccount {
+            signatures: (0..MAX_STORED_SIGNATURES)
+                .map(|index| new_signature(index, false, 100))
+                .collect(),
+            ..UserBankAccount::default()
+        };
+        assert_eq!(
+            bank_account.add_signature(&new_signature(MAX_STORED_SIGNATURES, true, 100)).unwrap_err(),
+            ProgramError::Custom(
+                BankError::SignatureStoreFull as u32
+            )
+        );
+
+        // the failed entries are evicted once their messages expired
+        bank_account.add_signature(&new_signature(MAX_STORED_SIGNATURES + 101, true, 1_000)).unwrap();
+        assert_eq!(bank_account.signatures.len(), MAX_STORED_SIGNATURES, "Signatures count mismatch.");
+        assert!(bank_account.find_signature(&new_signature(0, false, 100).signature).is_none(), "Oldest entry must be evicted.");
+    }
+
+    #[test]
+    fn signature_stats_counts_ok_and_failed() {
+        let new_signature = |signature: [u8; 64], is_ok: bool| {
+            VerifiedSignature {
+                signature,
+                is_ok,
+                fail_reason: if is_ok == true { SignatureFailReason::None } else { SignatureFailReason::InsufficientLamports },
+                time: 1,
+                slot: 1,
+                message: vec![]
+            }
+        };
+
+        assert_eq!(UserBankAccount::default().signature_stats(), (0, 0), "Signature stats mismatch.");
+
+        let bank_account = UserBankAccount {
+            signatures: vec![
+                new_signature([1; 64], true),
+                new_signature([2; 64], false),
+                new_signature([3; 64], true),
+                new_signature([4; 64], false),
+                new_signature([5; 64], true)
+            ],
+            ..UserBankAccount::default()
+        };
+        assert_eq!(bank_account.signature_stats(), (3, 2), "Signature stats mismatch.");
+    }
+
+    #[test]
+    fn user_bank_account_header_pack_matches_borsh() {
+        let bank_account = UserBankAccount {
+            discriminator: UserBankAccount::get_bank_account_discriminator(),
+            authority: Pubkey::new_unique(),
+            bump: 254,
+            account_created_at: 1_700_000_000,
+            label: [7; 32],
+            ..UserBankAccount::default()
+        };
+        let borsh_bytes = bank_account.try_to_vec().unwrap();
+
+        let mut packed = vec![0u8; UserBankAccountHeader::LEN];
+        UserBankAccountHeader::pack(bank_account.header(), &mut packed).unwrap();
+        assert_eq!(
+            packed.as_slice(),
+            &borsh_bytes[..UserBankAccountHeader::LEN],
+            "Packed header mismatch."
+        );
+
+        let header = UserBankAccountHeader::unpack(&borsh_bytes[..UserBankAccountHeader::LEN]).unwrap();
+        assert_eq!(header, bank_account.header(), "Unpacked header mismatch.");
+    }
+
+    #[test]
+    fn user_bank_account_header_unpack_fail() {
+        // not a bank-account
+        let error = UserBankAccountHeader::unpack(&[0u8; UserBankAccountHeader::LEN]).unwrap_err();
+        assert_eq!(error, ProgramError::UninitializedAccount, "Mismatch error types!");
+
+        // whole account data instead of the header
+        let borsh_bytes = UserBankAccount {
+            discriminator: UserBankAccount::get_bank_account_discriminator(),
+            ..UserBankAccount::default()
+        }.try_to_vec().unwrap();
+        let error = UserBankAccountHeader::unpack(&borsh_bytes).unwrap_err();
+        assert_eq!(error, ProgramError::InvalidAccountData, "Mismatch error types!");
+    }
+
+    #[test]
+    fn check_min_withdrawal_threshold() {
+        let mut bank_account = UserBankAccount::default();
+
+        // default minimum
+        bank_account.check_min_withdrawal(MIN_ED25519_WITHDRAW_LAMPORTS).unwrap();
+        assert_eq!(
+            bank_account.check_min_withdrawal(MIN_ED25519_WITHDRAW_LAMPORTS - 1).unwrap_err(),
+            ProgramError::Custom(
+                BankError::WithdrawalBelowMinimum as u32
+            ),
+            "Mismatch error types!"
+        );
+
+        // bank-account's own minimum
+        bank_account.min_withdrawal = 1;
+        bank_account.check_min_withdrawal(1).unwrap();
+        assert_eq!(
+            bank_account.check_min_withdrawal(0).unwrap_err(),
+            ProgramError::Custom(
+                BankError::WithdrawalBelowMinimum as u32
+            ),
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn track_daily_withdrawal_boundary() {
+        let mut bank_account = UserBankAccount {
+            daily_limit: 1000,
+            ..UserBankAccount::default()
+        };
+
+        let recipient = Pubkey::new_unique();
+
+        bank_account.track_daily_withdrawal(10, &recipient, 600).unwrap();
+        // exactly the limit
+        bank_account.track_daily_withdrawal(20, &recipient, 400).unwrap();
+
+        let error = bank_account.track_daily_withdrawal(30, &recipient, 1).unwrap_err();
+        assert_eq!(
+            error,
+            ProgramError::Custom(
+                BankError::DailyLimitExceeded as u32
+            ),
+            "Mismatch error types!"
+        );
+        assert_eq!(bank_account.daily_withdrawals.len(), 2, "Daily withdrawals count mismatch.");
+
+        // the first withdrawal left the window
+        bank_account.track_daily_withdrawal(10 + DAILY_LIMIT_WINDOW_SECS, &recipient, 600).unwrap();
+        assert_eq!(
+            bank_account.daily_withdrawals,
+            vec![(recipient, 20, 400), (recipient, 10 + DAILY_LIMIT_WINDOW_SECS, 600)],
+            "Daily withdrawals mismatch."
+        );
+    }
+
+    #[test]
+    fn track_daily_withdrawal_per_recipient() {
+        let mut bank_account = UserBankAccount {
+            daily_limit: 1000,
+            ..UserBankAccount::default()
+        };
+        let recipient = Pubkey::new_unique();
+        let other_recipient = Pubkey::new_unique();
+
+        bank_account.track_daily_withdrawal(10, &recipient, 1000).unwrap();
+        // the limit of one recipient doesn't count against another
+        bank_account.track_daily_withdrawal(20, &other_recipient, 1000).unwrap();
+
+        let error = bank_account.track_daily_withdrawal(30, &recipient, 1).unwrap_err();
+        assert_eq!(
+            error,
+            ProgramError::Custom(
+                BankError::DailyLimitExceeded as u32
+            ),
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn track_daily_withdrawal_no_limit() {
+        let mut bank_account = UserBankAccount::default();
+
+        bank_account.track_daily_withdrawal(10, &Pubkey::new_unique(), u64::MAX).unwrap();
+        assert!(bank_account.daily_withdrawals.is_empty(), "Daily withdrawals tracked without a limit.");
+    }
+
+    #[test]
+    fn daily_limit_remaining_within_window() {
+        let recipient = Pubkey::new_unique();
+        let mut bank_account = UserBankAccount::default();
+        assert_eq!(bank_account.daily_limit_remaining(10, Some(&recipient)), None, "Remaining daily limit without a limit.");
+
+        bank_account.daily_limit = 1000;
+        bank_account.track_daily_withdrawal(10, &recipient, 600).unwrap();
+        assert_eq!(bank_account.daily_limit_remaining(20, Some(&recipient)), Some(400), "Remaining daily limit mismatch.");
+        // other (or unknown) recipients have the whole limit
+        assert_eq!(bank_account.daily_limit_remaining(20, Some(&Pubkey::new_unique())), Some(1000), "Remaining daily limit mismatch.");
+        assert_eq!(bank_account.daily_limit_remaining(20, None), Some(1000), "Remaining daily limit mismatch.");
+
+        // the withdrawal left the window
+        assert_eq!(
+            bank_account.daily_limit_remaining(10 + DAILY_LIMIT_WINDOW_SECS, Some(&recipient)),
+            Some(1000),
+            "Remaining daily limit mismatch."
+        );
+    }
+
+    #[test]
+    fn associated_token_account_matches_spl() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let bank_account = UserBankAccount::get_user_bank_account_using_fpa(&authority, &program_id).0;
+
+        let expected_associated_token_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &bank_account,
+            &mint,
+            &spl_token::id()
+        );
+
+        assert_eq!(
+            expected_associated_token_account,
+            UserBankAccount::associated_token_account(&bank_account, &spl_token::id(), &mint).0,
+            "Mismatch associated-token-accounts!"
+        );
+    }
+
+    #[test]
+    fn get_fee_bps_tier_boundaries() {
+        let config = BankAccountConfig {
+            fee_tiers: vec![
+                (1_000, 100),
+                (5_000, 50)
+            ],
+            ..BankAccountConfig::default()
+        };
+
+        assert_eq!(config.get_fee_bps(999), 0u16, "Mismatch fee bps!");
+        assert_eq!(config.get_fee_bps(1_000), 100u16, "Mismatch fee bps!");
+        assert_eq!(config.get_fee_bps(4_999), 100u16, "Mismatch fee bps!");
+        assert_eq!(config.get_fee_bps(5_000), 50u16, "Mismatch fee bps!");
+        assert_eq!(config.get_fee_bps(u64::MAX), 50u16, "Mismatch fee bps!");
+        assert_eq!(BankAccountConfig::default().get_fee_bps(u64::MAX), 0u16, "Mismatch fee bps!");
+    }
 }
\ No newline at end of file
@@ -11,26 +11,28 @@ use {
         pubkey::Pubkey,
         entrypoint::ProgramResult,
         account_info::AccountInfo,
-        program_memory::{
-            sol_memcmp,
-            sol_memcpy
-        },
-        clock::Epoch
+        borsh0_10::try_from_slice_unchecked,
+        program_memory::sol_memcmp,
+        clock::UnixTimestamp
     },
     std::str::FromStr
 };
 
-/// Example-For-MessageV1 -> "<pubkey>,<lamports>,<memo>"
+/// Example-For-MessageV1 -> "<pubkey>,<lamports>,<memo>,<expires_at>"
 #[derive(Debug)]
 pub struct MessageV1 {
     pub signer: Pubkey,
     pub signature: Signature,
     pub to: Pubkey,
     pub lamports: u64,
-    pub memo: String
+    pub memo: String,
+    /// unix-timestamp after which the message is no longer valid
+    pub expires_at: UnixTimestamp,
+    /// revocation epoch the message was signed for (optional "e<epoch>" field, 0 when absent)
+    pub revocation_epoch: u64
 }
 
-/// Example-For-MessageV2 -> "<pubkey>,<mint>,<amount>,<memo>"
+/// Example-For-MessageV2 -> "<pubkey>,<mint>,<amount>,[<decimals>,]<memo>,<expires_at>"
 #[derive(Debug)]
 pub struct MessageV2 {
     pub signer: Pubkey,
@@ -38,115 +40,491 @@ pub struct MessageV2 {
     pub to: Pubkey,
     pub mint: Pubkey,
     pub amount: u64,
-    pub memo: String
+    /// decimals the signer computed `amount` with (optional field, checked against the mint's decimals)
+    pub decimals: Option<u8>,
+    pub memo: String,
+    /// unix-timestamp after which the message is no longer valid
+    pub expires_at: UnixTimestamp,
+    /// revocation epoch the message was signed for (optional "e<epoch>" field, 0 when absent)
+    pub revocation_epoch: u64
+}
+
+/// Example-For-MessageV3 -> "v3,<pubkey>,<relayer>,<lamports>,<memo>,<expires_at>"
+///
+/// lamports withdrawal submitted by the named relayer on behalf of "to", the "v3" version field is required
+#[derive(Debug)]
+pub struct MessageV3 {
+    pub signer: Pubkey,
+    pub signature: Signature,
+    pub to: Pubkey,
+    /// only account allowed to submit the withdrawal (signs the transaction and funds the signature entry)
+    pub relayer: Pubkey,
+    pub lamports: u64,
+    pub memo: String,
+    /// unix-timestamp after which the message is no longer valid
+    pub expires_at: UnixTimestamp,
+    /// revocation epoch the message was signed for (optional "e<epoch>" field, 0 when absent)
+    pub revocation_epoch: u64
+}
+
+/// Example-For-MessageV4 -> "v4,<payment_escrow>,<pubkey>,<lamports>,<expires_at>"
+///
+/// claim of a payment-escrow by its claim-signer, the "v4" version field is required so a claim can't be read as a withdrawal
+#[derive(Debug)]
+pub struct MessageV4 {
+    pub signer: Pubkey,
+    pub signature: Signature,
+    /// the only payment-escrow the claim redeems
+    pub payment_escrow: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    /// unix-timestamp after which the message is no longer valid
+    pub expires_at: UnixTimestamp
+}
+
+/// Validated message of any supported version
+#[derive(Debug)]
+pub enum Message {
+    V1(MessageV1),
+    V2(MessageV2),
+    V3(MessageV3),
+    V4(MessageV4)
 }
 
 // constants
-const WITHDRAW_WITH_ED25519_LAMPORTS_ARGS_COUNT: usize = 3;
-const WITHDRAW_WITH_Ed25519__SPL_TOKEN__ARGS_COUNT: usize = 4;
+const WITHDRAW_WITH_ED25519_LAMPORTS_ARGS_COUNT: usize = 4;
+const WITHDRAW_WITH_ED25519_SPL_TOKEN_ARGS_COUNT: usize = 5;
+const WITHDRAW_WITH_ED25519_RELAYED_LAMPORTS_ARGS_COUNT: usize = 5;
+const REDEEM_PAYMENT_ESCROW_ARGS_COUNT: usize = 4;
+pub const SUPPORTED_MESSAGE_VERSIONS: &[u8] = &[1, 2, 3, 4];
+/// largest memo the memo program accepts, bounds the signature entry stored for a message
+pub const MAX_MEMO_LEN: usize = 566;
+// ed25519 instruction header -> signatures count (u8), padding (u8), then 7 u16 offsets per signature
+const ED25519_SIGNATURE_OFFSETS_START: usize = 2;
+const ED25519_SIGNATURE_OFFSETS_SIZE: usize = 14;
+const ED25519_PUBKEY_SIZE: usize = 32;
+const ED25519_SIGNATURE_SIZE: usize = 64;
+// layout the message validators slice -> signer [16..48], signature [48..112], message [112..]
+const ED25519_CANONICAL_PUBKEY_OFFSET: u16 = 16;
+const ED25519_CANONICAL_SIGNATURE_OFFSET: u16 = 48;
+const ED25519_CANONICAL_MESSAGE_OFFSET: u16 = 112;
+const ED25519_CURRENT_INSTRUCTION_INDEX: u16 = u16::MAX;
+
+/// Locates the signer, signature and message of the `signature_index`-th signature of an ed25519 instruction through its offsets
+/// and lays them out canonically (the layout the message validators slice)
+///
+/// the offsets must refer to the ed25519 instruction itself (`u16::MAX` or its own index in the transaction)
+pub(crate) fn parse_ed25519_instruction_data(
+    ed25519_instruction_data: &[u8],
+    ed25519_instruction_index: u16,
+    signature_index: u8
+) -> Result<Vec<u8>, ProgramError> {
+    let invalid_instruction = || {
+        BankError::InvalidEd25519SignatureVerificationInstruction.into()
+    };
+
+    let num_signatures = *ed25519_instruction_data
+        .first()
+        .ok_or_else(invalid_instruction)?;
+    if signature_index >= num_signatures {
+        return Err(invalid_instruction());
+    };
+
+    let offsets_start = ED25519_SIGNATURE_OFFSETS_START + signature_index as usize * ED25519_SIGNATURE_OFFSETS_SIZE;
+    let offsets = ed25519_instruction_data
+        .get(offsets_start..offsets_start + ED25519_SIGNATURE_OFFSETS_SIZE)
+        .ok_or_else(invalid_instruction)?
+        .chunks_exact(2)
+        .map(|offset| u16::from_le_bytes([offset[0], offset[1]]))
+        .collect::<Vec<u16>>();
+    let (
+        signature_offset,
+        signature_instruction_index,
+        pubkey_offset,
+        pubkey_instruction_index,
+        message_offset,
+        message_size,
+        message_instruction_index
+    ) = (offsets[0], offsets[1], offsets[2], offsets[3], offsets[4], offsets[5], offsets[6]);
+
+    for instruction_index in [signature_instruction_index, pubkey_instruction_index, message_instruction_index] {
+        if instruction_index != ED25519_CURRENT_INSTRUCTION_INDEX && instruction_index != ed25519_instruction_index {
+            return Err(invalid_instruction());
+        };
+    };
+
+    let read_field = |offset: u16, size: usize| {
+        ed25519_instruction_data
+            .get(offset as usize..offset as usize + size)
+            .ok_or_else(invalid_instruction)
+    };
+    let pubkey = read_field(pubkey_offset, ED25519_PUBKEY_SIZE)?;
+    let signature = read_field(signature_offset, ED25519_SIGNATURE_SIZE)?;
+    let message = read_field(message_offset, message_size as usize)?;
+
+    let mut canonical_data = Vec::with_capacity(ED25519_CANONICAL_MESSAGE_OFFSET as usize + message.len());
+    canonical_data.extend_from_slice(&[1u8, 0u8]);
+    for offset in [
+        ED25519_CANONICAL_SIGNATURE_OFFSET,
+        ED25519_CURRENT_INSTRUCTION_INDEX,
+        ED25519_CANONICAL_PUBKEY_OFFSET,
+        ED25519_CURRENT_INSTRUCTION_INDEX,
+        ED25519_CANONICAL_MESSAGE_OFFSET,
+        message_size,
+        ED25519_CURRENT_INSTRUCTION_INDEX
+    ] {
+        canonical_data.extend_from_slice(&offset.to_le_bytes());
+    };
+    canonical_data.extend_from_slice(pubkey);
+    canonical_data.extend_from_slice(signature);
+    canonical_data.extend_from_slice(message);
+
+    Ok(canonical_data)
+}
+
+/// The signer a message names (the pubkey its bank-account is derived from) must be the pubkey the ed25519 program verified
+pub(crate) fn validate_signer_pubkey(
+    ed25519_signature_data: &[u8],
+    signer: &Pubkey
+) -> ProgramResult {
+    let verified_pubkey = ed25519_signature_data
+        .get(ED25519_CANONICAL_PUBKEY_OFFSET as usize..ED25519_CANONICAL_PUBKEY_OFFSET as usize + ED25519_PUBKEY_SIZE)
+        .ok_or(
+            ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction)
+        )?;
+
+    if verified_pubkey != signer.as_ref() {
+        return Err(
+            BankError::SignerPubkeyMismatch.into()
+        );
+    };
+
+    Ok(())
+}
+
+/// Splits the optional version field off the message -> "v<version>,<message>"
+///
+/// messages without the version field use `default_version` (the version implied by the instruction)
+pub(crate) fn split_message_version(
+    ed25519_signature_data: &[u8],
+    default_version: u8
+) -> Result<(u8, Vec<u8>), ProgramError> {
+    let message = ed25519_signature_data.get(112..).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+
+    let msg = String::from_utf8(
+        message.to_vec()
+    ).map_err(|_| {
+        ProgramError::from(BankError::NonUtf8Message)
+    })?;
+
+    if let Some((version_field, rest)) = msg.split_once(",") {
+        if let Some(version) = version_field.strip_prefix("v").and_then(|version| version.parse::<u8>().ok()) {
+            let mut message_data = ed25519_signature_data.get(..112).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?.to_vec();
+            message_data.extend_from_slice(rest.as_bytes());
+
+            return Ok((version, message_data));
+        };
+    };
+
+    Ok((default_version, ed25519_signature_data.to_vec()))
+}
+
+/// Splits the optional revocation epoch field off the message -> "e<revocation_epoch>,<message>"
+///
+/// messages without the epoch field were signed for the initial epoch (0)
+pub(crate) fn split_message_revocation_epoch(
+    ed25519_signature_data: &[u8]
+) -> Result<(u64, Vec<u8>), ProgramError> {
+    let message = ed25519_signature_data.get(112..).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+
+    let msg = String::from_utf8(
+        message.to_vec()
+    ).map_err(|_| {
+        ProgramError::from(BankError::NonUtf8Message)
+    })?;
+
+    if let Some((epoch_field, rest)) = msg.split_once(",") {
+        if let Some(revocation_epoch) = epoch_field.strip_prefix("e").and_then(|epoch| epoch.parse::<u64>().ok()) {
+            let mut message_data = ed25519_signature_data.get(..112).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?.to_vec();
+            message_data.extend_from_slice(rest.as_bytes());
+
+            return Ok((revocation_epoch, message_data));
+        };
+    };
+
+    Ok((0, ed25519_signature_data.to_vec()))
+}
+
+/// Dispatches the message to the validator of its version
+pub(crate) fn validate_message(
+    version: u8,
+    ed25519_signature_data: &[u8]
+) -> Result<Message, ProgramError> {
+    if !SUPPORTED_MESSAGE_VERSIONS.contains(&version) {
+        return Err(
+            BankError::UnsupportedMessageVersion.into()
+        );
+    };
+
+    match version {
+        1 => Ok(Message::V1(validate_message_v1(ed25519_signature_data)?)),
+        2 => Ok(Message::V2(validate_message_v2(ed25519_signature_data)?)),
+        3 => Ok(Message::V3(validate_message_v3(ed25519_signature_data)?)),
+        4 => Ok(Message::V4(validate_message_v4(ed25519_signature_data)?)),
+        _ => Err(
+            BankError::UnsupportedMessageVersion.into()
+        )
+    }
+}
 
 /// Message validator for lamports withdraw
-pub(crate) fn validate_message_v1(ed25519_signature_data: &Vec<u8>) -> Result<MessageV1, ProgramError> {
-    let signer = ed25519_signature_data.get(16..48).unwrap();
-    let signature = ed25519_signature_data.get(48..112).unwrap();
-    let message = ed25519_signature_data.get(112..).unwrap();
+pub(crate) fn validate_message_v1(ed25519_signature_data: &[u8]) -> Result<MessageV1, ProgramError> {
+    let signer = ed25519_signature_data.get(16..48).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+    let signature = ed25519_signature_data.get(48..112).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+    let message = ed25519_signature_data.get(112..).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
 
     let msg = String::from_utf8(
-        message
-            .try_into()
-            .unwrap()
-    ).unwrap();
+        message.to_vec()
+    ).map_err(|_| {
+        ProgramError::from(BankError::NonUtf8Message)
+    })?;
     let message_info = msg.split(",").collect::<Vec<_>>();
 
     if message_info.len() != WITHDRAW_WITH_ED25519_LAMPORTS_ARGS_COUNT {
         return Err(
-            ProgramError::Custom(
-                BankError::MessageV1ValidationFailed as u32
-            )
+            BankError::MessageV1ValidationFailed.into()
         );
     };
 
     let to = Pubkey::from_str(message_info[0])
         .map_err(|_| {
-            ProgramError::Custom(
-                BankError::InvalidToPubkey as u32
-            )
-        }).unwrap();
+            ProgramError::from(BankError::InvalidToPubkey)
+        })?;
     
     let lamports = message_info[1]
         .parse::<u64>()
         .map_err(|_| {
-            ProgramError::Custom(
-                BankError::InvalidLamports as u32
-            )
-        }).unwrap();
+            ProgramError::from(BankError::InvalidLamports)
+        })?;
         
     let memo = message_info[2].to_owned();
 
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(
+            BankError::MemoTooLong.into()
+        );
+    };
+
+    let expires_at = message_info[3]
+        .parse::<UnixTimestamp>()
+        .map_err(|_| {
+            ProgramError::from(BankError::MessageV1ValidationFailed)
+        })?;
+
     Ok(
         MessageV1 {
-            signer: Pubkey::try_from(signer).unwrap(),
-            signature: signature.try_into().unwrap(),
+            signer: Pubkey::try_from(signer).map_err(|_| ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?,
+            signature: signature.try_into().map_err(|_| ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?,
             to,
             lamports,
-            memo
+            memo,
+            expires_at,
+            revocation_epoch: 0
         }
     )
 }
 
 /// Message validator for spl-tokens withdraw
-pub(crate) fn validate_message_v2(ed25519_signature_data: &Vec<u8>) -> Result<MessageV2, ProgramError> {
-    let signer = ed25519_signature_data.get(16..48).unwrap();
-    let signature = ed25519_signature_data.get(48..112).unwrap();
-    let message = ed25519_signature_data.get(112..).unwrap();
+pub(crate) fn validate_message_v2(ed25519_signature_data: &[u8]) -> Result<MessageV2, ProgramError> {
+    let signer = ed25519_signature_data.get(16..48).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+    let signature = ed25519_signature_data.get(48..112).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+    let message = ed25519_signature_data.get(112..).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
 
     let msg = String::from_utf8(
-        message
-            .try_into()
-            .unwrap()
-    ).unwrap();
+        message.to_vec()
+    ).map_err(|_| {
+        ProgramError::from(BankError::NonUtf8Message)
+    })?;
     let message_info = msg.split(",").collect::<Vec<_>>();
 
-    if message_info.len() != WITHDRAW_WITH_Ed25519__SPL_TOKEN__ARGS_COUNT {
+    // one more field -> the optional decimals follow the amount
+    let has_decimals = message_info.len() == WITHDRAW_WITH_ED25519_SPL_TOKEN_ARGS_COUNT + 1;
+    if message_info.len() != WITHDRAW_WITH_ED25519_SPL_TOKEN_ARGS_COUNT && !has_decimals {
         return Err(
-            ProgramError::Custom(
-                BankError::MessageV2ValidationFailed as u32
-            )
+            BankError::MessageV2ValidationFailed.into()
         );
     };
 
     let to = Pubkey::from_str(message_info[0])
         .map_err(|_| {
-            ProgramError::Custom(
-                BankError::InvalidToPubkey as u32
-            )
-        }).unwrap();
+            ProgramError::from(BankError::InvalidToPubkey)
+        })?;
 
     let mint = Pubkey::from_str(message_info[1])
         .map_err(|_| {
-            ProgramError::Custom(
-                BankError::InvalidMint as u32
-            )
-        }).unwrap();
+            ProgramError::from(BankError::InvalidMint)
+        })?;
 
     let amount = message_info[2]
         .parse::<u64>()
         .map_err(|_| {
-            ProgramError::Custom(
-                BankError::InvalidTokenAmount as u32
-            )
-        }).unwrap();
+            ProgramError::from(BankError::InvalidTokenAmount)
+        })?;
 
-    let memo = message_info[3].to_owned();
+    let decimals = if has_decimals {
+        Some(
+            message_info[3]
+                .parse::<u8>()
+                .map_err(|_| {
+                    ProgramError::from(BankError::MessageV2ValidationFailed)
+                })?
+        )
+    } else {
+        None
+    };
+    let message_info = &message_info[message_info.len() - 2..];
+
+    let memo = message_info[0].to_owned();
+
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(
+            BankError::MemoTooLong.into()
+        );
+    };
+
+    let expires_at = message_info[1]
+        .parse::<UnixTimestamp>()
+        .map_err(|_| {
+            ProgramError::from(BankError::MessageV2ValidationFailed)
+        })?;
 
     Ok(
         MessageV2 {
-            signer: Pubkey::try_from(signer).unwrap(),
-            signature: signature.try_into().unwrap(),
+            signer: Pubkey::try_from(signer).map_err(|_| ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?,
+            signature: signature.try_into().map_err(|_| ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?,
             to,
             mint,
             amount,
-            memo
+            decimals,
+            memo,
+            expires_at,
+            revocation_epoch: 0
+        }
+    )
+}
+
+/// Message validator for relayed lamports withdraw
+pub(crate) fn validate_message_v3(ed25519_signature_data: &[u8]) -> Result<MessageV3, ProgramError> {
+    let signer = ed25519_signature_data.get(16..48).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+    let signature = ed25519_signature_data.get(48..112).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+    let message = ed25519_signature_data.get(112..).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+
+    let msg = String::from_utf8(
+        message.to_vec()
+    ).map_err(|_| {
+        ProgramError::from(BankError::NonUtf8Message)
+    })?;
+    let message_info = msg.split(",").collect::<Vec<_>>();
+
+    if message_info.len() != WITHDRAW_WITH_ED25519_RELAYED_LAMPORTS_ARGS_COUNT {
+        return Err(
+            BankError::MessageV3ValidationFailed.into()
+        );
+    };
+
+    let to = Pubkey::from_str(message_info[0])
+        .map_err(|_| {
+            ProgramError::from(BankError::InvalidToPubkey)
+        })?;
+
+    let relayer = Pubkey::from_str(message_info[1])
+        .map_err(|_| {
+            ProgramError::from(BankError::InvalidRelayer)
+        })?;
+
+    let lamports = message_info[2]
+        .parse::<u64>()
+        .map_err(|_| {
+            ProgramError::from(BankError::InvalidLamports)
+        })?;
+
+    let memo = message_info[3].to_owned();
+
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(
+            BankError::MemoTooLong.into()
+        );
+    };
+
+    let expires_at = message_info[4]
+        .parse::<UnixTimestamp>()
+        .map_err(|_| {
+            ProgramError::from(BankError::MessageV3ValidationFailed)
+        })?;
+
+    Ok(
+        MessageV3 {
+            signer: Pubkey::try_from(signer).map_err(|_| ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?,
+            signature: signature.try_into().map_err(|_| ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?,
+            to,
+            relayer,
+            lamports,
+            memo,
+            expires_at,
+            revocation_epoch: 0
+        }
+    )
+}
+
+/// Message validator for payment-escrow claims
+pub(crate) fn validate_message_v4(ed25519_signature_data: &[u8]) -> Result<MessageV4, ProgramError> {
+    let signer = ed25519_signature_data.get(16..48).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+    let signature = ed25519_signature_data.get(48..112).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+    let message = ed25519_signature_data.get(112..).ok_or(ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?;
+
+    let msg = String::from_utf8(
+        message.to_vec()
+    ).map_err(|_| {
+        ProgramError::from(BankError::NonUtf8Message)
+    })?;
+    let message_info = msg.split(",").collect::<Vec<_>>();
+
+    if message_info.len() != REDEEM_PAYMENT_ESCROW_ARGS_COUNT {
+        return Err(
+            BankError::MessageV4ValidationFailed.into()
+        );
+    };
+
+    let payment_escrow = Pubkey::from_str(message_info[0])
+        .map_err(|_| {
+            ProgramError::from(BankError::InvalidPaymentEscrow)
+        })?;
+
+    let to = Pubkey::from_str(message_info[1])
+        .map_err(|_| {
+            ProgramError::from(BankError::InvalidToPubkey)
+        })?;
+
+    let lamports = message_info[2]
+        .parse::<u64>()
+        .map_err(|_| {
+            ProgramError::from(BankError::InvalidLamports)
+        })?;
+
+    let expires_at = message_info[3]
+        .parse::<UnixTimestamp>()
+        .map_err(|_| {
+            ProgramError::from(BankError::MessageV4ValidationFailed)
+        })?;
+
+    Ok(
+        MessageV4 {
+            signer: Pubkey::try_from(signer).map_err(|_| ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?,
+            signature: signature.try_into().map_err(|_| ProgramError::from(BankError::InvalidEd25519SignatureVerificationInstruction))?,
+            payment_escrow,
+            to,
+            lamports,
+            expires_at
         }
     )
 }
@@ -162,10 +540,17 @@ pub(crate) fn validate_bank_account(
         );
     };
 
-    let bank_account_data = &bank_account_info.data.try_borrow().unwrap()[..];
+    let bank_account_data = &bank_account_info.try_borrow_data()?[..];
+
+    // discriminator + authority + bump, a shorter program-owned account can't be a bank-account
+    if bank_account_data.len() < 8 + 32 + 1 {
+        return Err(
+            ProgramError::InvalidAccountData
+        );
+    };
 
     let cmp_result = sol_memcmp(
-        bank_account_data.get(..8).unwrap(),
+        bank_account_data.get(..8).ok_or(ProgramError::InvalidAccountData)?,
         UserBankAccount::get_bank_account_discriminator().as_slice(),
         8
     );
@@ -175,17 +560,140 @@ pub(crate) fn validate_bank_account(
         );
     };
 
-    let expected_bank_account_pubkey = UserBankAccount::get_user_bank_account_using_cpa(
+    let (seed_owner, seed_index) = get_bank_account_seeds(
+        program_id,
         authority,
-        bank_account_data.get(40).unwrap(), /// bump_offset
+        bank_account_info
+    )?;
+
+    let expected_bank_account_pubkey = UserBankAccount::get_user_bank_account_with_index_using_cpa(
+        &seed_owner,
+        &seed_index,
+        bank_account_data.get(40).ok_or(ProgramError::InvalidAccountData)?, // bump_offset
         program_id
-    );
+    )?;
     if *bank_account_info.key != expected_bank_account_pubkey {
         return Err(
             ProgramError::InvalidSeeds
         );
     };
 
+    // the PDA still belongs to the original owner after its authority was transferred away
+    if bank_account_data.get(8..40) != Some(authority.as_ref()) { // authority_offset
+        return Err(
+            ProgramError::InvalidSeeds
+        );
+    };
+
+    Ok(())
+}
+
+/// (pubkey, index) in the seeds of bank-account's PDA -> `authority` itself, or the original owner once the authority was transferred,
+/// along with the index of bank-accounts created by `CreateBankAccountWithIndex`
+pub(crate) fn get_bank_account_seeds(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    bank_account_info: &AccountInfo
+) -> Result<(Pubkey, Option<u16>), ProgramError> {
+    let bank_account_data = &bank_account_info.try_borrow_data()?[..];
+
+    let bump = bank_account_data.get(40).ok_or(ProgramError::InvalidAccountData)?; // bump_offset
+    if UserBankAccount::get_user_bank_account_using_cpa(authority, bump, program_id) == Ok(*bank_account_info.key) {
+        return Ok((*authority, None));
+    };
+
+    try_from_slice_unchecked::<UserBankAccount>(bank_account_data)
+        .ok()
+        .filter(|bank_account| bank_account.original_owner.is_some() || bank_account.seed_index.is_some())
+        .map(|bank_account| (bank_account.seed_owner(), bank_account.seed_index))
+        .ok_or(ProgramError::InvalidSeeds)
+}
+
+/// same as `validate_bank_account` but a seeds mismatch is reported as `SignerAccountMismatch`,
+/// the bank-account doesn't belong to the ed25519 message signer
+pub(crate) fn validate_signer_bank_account(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    bank_account_info: &AccountInfo
+) -> ProgramResult {
+    validate_bank_account(
+        program_id,
+        signer,
+        bank_account_info
+    ).map_err(|err| {
+        if err == ProgramError::InvalidSeeds {
+            BankError::SignerAccountMismatch.into()
+        } else {
+            err
+        }
+    })?;
+
+    validate_bank_account_authority(
+        signer,
+        bank_account_info
+    )
+}
+
+/// authority stored in the deserialized bank-account must be the ed25519 message signer,
+/// defense-in-depth over the seeds derivation which trusts the bump read from account data
+pub(crate) fn validate_bank_account_authority(
+    signer: &Pubkey,
+    bank_account_info: &AccountInfo
+) -> ProgramResult {
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &bank_account_info.try_borrow_data()?[..]
+    ).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if bank_account.authority != *signer {
+        return Err(
+            ProgramError::InvalidAccountData
+        );
+    };
+
+    Ok(())
+}
+
+/// rejects recipients owned by this program which aren't bank-accounts (receipts, payment escrows, ...),
+/// crediting them would corrupt the program's internal accounting
+pub(crate) fn validate_recipient(
+    program_id: &Pubkey,
+    recepient_account_info: &AccountInfo
+) -> ProgramResult {
+    if recepient_account_info.owner != program_id {
+        return Ok(());
+    };
+
+    let recepient_account_data = &recepient_account_info.try_borrow_data()?[..];
+
+    let is_bank_account = recepient_account_data
+        .get(..8)
+        .map(|discriminator| {
+            sol_memcmp(
+                discriminator,
+                UserBankAccount::get_bank_account_discriminator().as_slice(),
+                8
+            ) == 0_i32
+        })
+        .unwrap_or(false);
+    if !is_bank_account {
+        return Err(
+            BankError::InvalidRecipient.into()
+        );
+    };
+
+    Ok(())
+}
+
+/// the memo program rejects memos that aren't valid utf-8
+pub(crate) fn validate_memo(
+    memo_message: &[u8]
+) -> ProgramResult {
+    if std::str::from_utf8(memo_message).is_err() {
+        return Err(
+            BankError::MemoNotUtf8.into()
+        );
+    };
+
     Ok(())
 }
 
@@ -195,19 +703,24 @@ mod test_validators {
         cell::RefCell,
         rc::Rc
     };
+    use borsh::BorshSerialize;
+    use solana_program::{
+        program_memory::sol_memcpy,
+        clock::Epoch
+    };
     use super::*;
 
     #[test]
     fn valdiate_message_v1_success() {
         let ed25519: Vec<u8> = vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,70,0,255,255,187,220,42,181,173,60,36,199,230,
-            65,125,124,22,8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,
-            180,255,54,4,159,187,73,74,51,186,168,106,147,16,201,14,106,1,49,196,88,116,177,
-            137,13,198,252,34,171,201,99,51,187,100,183,46,111,86,128,156,103,161,229,61,73,
-            72,133,239,84,27,37,192,242,126,121,29,166,79,235,157,205,28,183,2,55,66,101,71,
-            121,102,65,71,103,101,104,67,54,102,86,80,55,81,80,72,104,87,103,71,106,119,83,112,
-            97,74,105,118,78,49,54,69,81,72,87,54,111,89,84,116,44,49,48,48,48,44,72,101,108,108,
-            111,32,80,111,111,114,105,97,71,71,32,240,159,152,131,33
+            1,0,48,0,255,255,16,0,255,255,112,0,81,0,255,255,187,220,42,181,173,60,36,199,230,
+            65,125,124,22,8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,14,181,
+            72,109,61,24,119,85,44,198,174,107,148,186,230,153,125,23,68,160,221,230,152,237,53,76,202,
+            190,78,181,70,75,28,196,224,228,136,185,175,65,58,161,11,44,120,53,184,119,161,31,31,90,
+            13,117,195,128,107,151,150,158,196,151,216,1,55,66,101,71,121,102,65,71,103,101,104,67,54,
+            102,86,80,55,81,80,72,104,87,103,71,106,119,83,112,97,74,105,118,78,49,54,69,81,72,
+            87,54,111,89,84,116,44,49,48,48,48,44,72,101,108,108,111,32,80,111,111,114,105,97,71,
+            71,32,240,159,152,131,33,44,52,49,48,50,52,52,52,56,48,48
         ];
 
         let expected_pubkey = Pubkey::new_from_array(
@@ -255,6 +768,11 @@ mod test_validators {
             message_v1.memo,
             "expected_memo != message_v1.memo"
         );
+        assert_eq!(
+            4102444800,
+            message_v1.expires_at,
+            "expected_expires_at != message_v1.expires_at"
+        );
     }
 
     #[test]
@@ -280,16 +798,322 @@ mod test_validators {
     }
 
     #[test]
-    fn valdiate_message_v2_success() {
-        let ed25519: Vec<u8> = vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,99,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,
-            22,8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,31,122,253,233,93,129,
-            205,198,171,211,41,194,215,77,113,166,170,185,210,119,98,91,212,146,232,95,223,212,71,94,215,
-            200,241,70,251,227,29,58,198,168,230,220,28,193,78,90,145,2,234,29,82,243,255,245,1,78,103,79,
-            169,6,19,2,2,5,55,66,101,71,121,102,65,71,103,101,104,67,54,102,86,80,55,81,80,72,104,87,103,
-            71,106,119,83,112,97,74,105,118,78,49,54,69,81,72,87,54,111,89,84,116,44,69,80,106,70,87,100,
-            100,53,65,117,102,113,83,83,113,101,77,50,113,78,49,120,122,121,98,97,112,67,56,71,52,119,69,
-            71,71,107,90,119,121,84,68,116,49,118,44,49,48,48,48,44,240,159,152,131
+    fn validate_message_v1_fail_invalid_to_pubkey() {
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice("not-a-pubkey,1000,,4102444800".as_bytes());
+
+        let error = validate_message_v1(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::InvalidToPubkey as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(&[0xff, 0xfe, 44, 44]);
+
+        let error = validate_message_v1(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::NonUtf8Message as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn validate_message_v1_memo_length_limit() {
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},1000,{},4102444800", Pubkey::new_unique(), "m".repeat(MAX_MEMO_LEN)).as_bytes()
+        );
+
+        let message_v1 = validate_message_v1(&ed25519).unwrap();
+        assert_eq!(message_v1.memo.len(), MAX_MEMO_LEN);
+
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},1000,{},4102444800", Pubkey::new_unique(), "m".repeat(MAX_MEMO_LEN + 1)).as_bytes()
+        );
+
+        let error = validate_message_v1(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::MemoTooLong as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn validate_message_v2_memo_length_limit() {
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},{},1000,{},4102444800", Pubkey::new_unique(), Pubkey::new_unique(), "m".repeat(MAX_MEMO_LEN)).as_bytes()
+        );
+
+        let message_v2 = validate_message_v2(&ed25519).unwrap();
+        assert_eq!(message_v2.memo.len(), MAX_MEMO_LEN);
+
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},{},1000,{},4102444800", Pubkey::new_unique(), Pubkey::new_unique(), "m".repeat(MAX_MEMO_LEN + 1)).as_bytes()
+        );
+
+        let error = validate_message_v2(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::MemoTooLong as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn validate_message_v3_success() {
+        let to = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},{},1000,relayed,4102444800", to, relayer).as_bytes()
+        );
+
+        let message_v3 = validate_message_v3(&ed25519).unwrap();
+        assert_eq!(message_v3.to, to, "To mismatch.");
+        assert_eq!(message_v3.relayer, relayer, "Relayer mismatch.");
+        assert_eq!(message_v3.lamports, 1000u64, "Lamports mismatch.");
+        assert_eq!(message_v3.memo, "relayed", "Memo mismatch.");
+        assert_eq!(message_v3.expires_at, 4102444800, "Expiry mismatch.");
+    }
+
+    #[test]
+    fn validate_message_v3_fail_invalid_relayer() {
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},not-a-relayer,1000,,4102444800", Pubkey::new_unique()).as_bytes()
+        );
+
+        let error = validate_message_v3(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::InvalidRelayer as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+
+        // V1 layout (no relayer field)
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},1000,,4102444800", Pubkey::new_unique()).as_bytes()
+        );
+
+        let error = validate_message_v3(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::MessageV3ValidationFailed as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn validate_message_v4_success() {
+        let payment_escrow = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("v4,{},{},1000,4102444800", payment_escrow, to).as_bytes()
+        );
+
+        let (version, message_data) = split_message_version(&ed25519, 0).unwrap();
+        assert_eq!(version, 4u8, "Mismatch message versions!");
+
+        match validate_message(version, &message_data).unwrap() {
+            Message::V4(message_v4) => {
+                assert_eq!(message_v4.payment_escrow, payment_escrow, "Payment-escrow mismatch.");
+                assert_eq!(message_v4.to, to, "To mismatch.");
+                assert_eq!(message_v4.lamports, 1000u64, "Lamports mismatch.");
+                assert_eq!(message_v4.expires_at, 4102444800, "Expiry mismatch.");
+            },
+            _ => panic!("expected MessageV4")
+        };
+    }
+
+    #[test]
+    fn validate_message_v4_fail_v1_layout() {
+        // a V1 message whose memo is the escrow's address isn't a claim
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},1000,{},4102444800", Pubkey::new_unique(), Pubkey::new_unique()).as_bytes()
+        );
+
+        let error = validate_message_v4(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::InvalidToPubkey as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn validate_message_v1_fail_invalid_lamports() {
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},not-lamports,,4102444800", Pubkey::new_unique()).as_bytes()
+        );
+
+        let error = validate_message_v1(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::InvalidLamports as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn validate_message_fail_non_utf8_message() {
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},1000,", Pubkey::new_unique()).as_bytes()
+        );
+        // lone continuation byte and truncated 4-byte sequence
+        ed25519.extend_from_slice(&[0x80, 0xf0, 0x9f, 0x98]);
+        ed25519.extend_from_slice(",4102444800".as_bytes());
+
+        let validators: [fn(&Vec<u8>) -> Result<(), ProgramError>; 3] = [
+            |data| validate_message_v1(data).map(|_| ()),
+            |data| validate_message_v2(data).map(|_| ()),
+            |data| validate_message_v3(data).map(|_| ())
+        ];
+        for validator in validators {
+            assert_eq!(
+                ProgramError::Custom(
+                    BankError::NonUtf8Message as u32
+                ),
+                validator(&ed25519).unwrap_err(),
+                "Mismatch error types!"
+            );
+        };
+
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::NonUtf8Message as u32
+            ),
+            split_message_version(&ed25519, 1).unwrap_err(),
+            "Mismatch error types!"
+        );
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::NonUtf8Message as u32
+            ),
+            split_message_revocation_epoch(&ed25519).unwrap_err(),
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn validate_message_fail_truncated_ed25519_data() {
+        // shorter than the signer + signature section, no message section at all
+        let ed25519: Vec<u8> = vec![0u8; 100];
+
+        let validators: [fn(&Vec<u8>) -> Result<(), ProgramError>; 4] = [
+            |data| validate_message_v1(data).map(|_| ()),
+            |data| validate_message_v2(data).map(|_| ()),
+            |data| validate_message_v3(data).map(|_| ()),
+            |data| split_message_version(data, 1).map(|_| ())
+        ];
+        for validator in validators {
+            assert_eq!(
+                ProgramError::Custom(
+                    BankError::InvalidEd25519SignatureVerificationInstruction as u32
+                ),
+                validator(&ed25519).unwrap_err(),
+                "Mismatch error types!"
+            );
+        };
+    }
+
+    #[test]
+    fn validate_message_v2_fail_invalid_fields() {
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("not-a-pubkey,{},1000,,4102444800", Pubkey::new_unique()).as_bytes()
+        );
+
+        let error = validate_message_v2(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::InvalidToPubkey as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},not-a-mint,1000,,4102444800", Pubkey::new_unique()).as_bytes()
+        );
+
+        let error = validate_message_v2(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::InvalidMint as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},{},-1,,4102444800", Pubkey::new_unique(), Pubkey::new_unique()).as_bytes()
+        );
+
+        let error = validate_message_v2(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::InvalidTokenAmount as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519.extend_from_slice(
+            format!("{},{},1000,256,,4102444800", Pubkey::new_unique(), Pubkey::new_unique()).as_bytes()
+        );
+
+        let error = validate_message_v2(&ed25519).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::MessageV2ValidationFailed as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn valdiate_message_v2_success() {
+        let ed25519: Vec<u8> = vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,110,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,
+            22,8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,199,178,212,168,174,172,68,109,
+            201,55,218,25,124,218,14,198,195,172,244,222,204,20,88,89,145,219,228,21,48,150,24,25,94,248,2,64,
+            4,107,189,47,85,190,96,252,193,75,16,87,93,38,236,253,172,152,191,140,164,249,217,10,200,160,133,12,
+            55,66,101,71,121,102,65,71,103,101,104,67,54,102,86,80,55,81,80,72,104,87,103,71,106,119,83,112,
+            97,74,105,118,78,49,54,69,81,72,87,54,111,89,84,116,44,69,80,106,70,87,100,100,53,65,117,102,
+            113,83,83,113,101,77,50,113,78,49,120,122,121,98,97,112,67,56,71,52,119,69,71,71,107,90,119,121,
+            84,68,116,49,118,44,49,48,48,48,44,240,159,152,131,44,52,49,48,50,52,52,52,56,48,48
         ];
 
         let expected_pubkey = Pubkey::new_from_array(
@@ -345,6 +1169,11 @@ mod test_validators {
             message_v2.memo,
             "expected_memo != message_v2.memo"
         );
+        assert_eq!(
+            4102444800,
+            message_v2.expires_at,
+            "expected_expires_at != message_v2.expires_at"
+        );
     }
 
     #[test]
@@ -377,6 +1206,7 @@ mod test_validators {
         let bank_account = UserBankAccount::get_user_bank_account_using_fpa(&authority, &program_id);
 
         let mut bank_account_data: &mut [u8] = &mut [0; 41];
+        bank_account_data[8..40].copy_from_slice(authority.as_ref());
         bank_account_data[40] = bank_account.1;
 
         let dis = UserBankAccount::get_bank_account_discriminator();
@@ -408,6 +1238,52 @@ mod test_validators {
         validate_bank_account(&program_id, &authority, &bank_account_info).unwrap();
     }
 
+    #[test]
+    fn validate_bank_account_success_full_layout() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let bank_account = UserBankAccount::get_user_bank_account_using_fpa(&authority, &program_id);
+
+        let mut bank_account_data = UserBankAccount {
+            discriminator: UserBankAccount::get_bank_account_discriminator(),
+            authority,
+            bump: bank_account.1,
+            account_created_at: 1,
+            ..UserBankAccount::default()
+        }.try_to_vec().unwrap();
+
+        // offsets read by the validator match the borsh layout of the full account
+        assert_eq!(
+            bank_account_data.get(..8).unwrap(),
+            UserBankAccount::get_bank_account_discriminator().as_slice()
+        );
+        assert_eq!(bank_account_data.get(8..40).unwrap(), authority.as_ref());
+        assert_eq!(*bank_account_data.get(40).unwrap(), bank_account.1);
+
+        let mut balance = solana_program::native_token::sol_to_lamports(0.5);
+
+        let bank_account_info: AccountInfo = AccountInfo {
+            key: &bank_account.0,
+            lamports: Rc::new(
+                RefCell::new(
+                    &mut balance
+                )
+            ),
+            owner: &program_id,
+            rent_epoch: Epoch::default(),
+            data: Rc::new(
+                RefCell::new(
+                    &mut bank_account_data[..]
+                )
+            ),
+            is_signer: false,
+            is_writable: false,
+            executable: false
+        };
+
+        validate_bank_account(&program_id, &authority, &bank_account_info).unwrap();
+    }
+
     #[test]
     fn validate_bank_account_fail_1() {
         let program_id = Pubkey::new_unique();
@@ -535,4 +1411,418 @@ mod test_validators {
            "Mismatch error types!"
        );
     }
+
+    #[test]
+    fn validate_bank_account_fail_data_too_small() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let bank_account = UserBankAccount::get_user_bank_account_using_fpa(&authority, &program_id);
+
+        let bank_account_data: &mut [u8] = &mut [0; 5];
+        bank_account_data.copy_from_slice(
+            &UserBankAccount::get_bank_account_discriminator()[..5]
+        );
+
+        let mut balance = solana_program::native_token::sol_to_lamports(0.5);
+
+        let bank_account_info: AccountInfo = AccountInfo {
+            key: &bank_account.0,
+            lamports: Rc::new(
+                RefCell::new(
+                    &mut balance
+                )
+            ),
+            owner: &program_id,
+            rent_epoch: Epoch::default(),
+            data: Rc::new(
+                RefCell::new(
+                    bank_account_data
+                )
+            ),
+            is_signer: false,
+            is_writable: false,
+            executable: false
+        };
+
+        let error = validate_bank_account(&program_id, &authority, &bank_account_info).unwrap_err();
+
+        assert_eq!(
+            ProgramError::InvalidAccountData,
+            error,
+            "Mismatch error types!"
+        );
+    }
+    #[test]
+    fn validate_signer_bank_account_fail() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let message_signer = Pubkey::new_unique();
+        let bank_account = UserBankAccount::get_user_bank_account_using_fpa(&authority, &program_id);
+
+        let mut bank_account_data: &mut [u8] = &mut [0; 41];
+        bank_account_data[40] = bank_account.1;
+
+        let dis = UserBankAccount::get_bank_account_discriminator();
+        let discriminator = dis.as_slice();
+
+        sol_memcpy(bank_account_data, discriminator, 8);
+
+        let mut balance = solana_program::native_token::sol_to_lamports(0.5);
+
+        let bank_account_info: AccountInfo = AccountInfo {
+            key: &bank_account.0,
+            lamports: Rc::new(
+                RefCell::new(
+                    &mut balance
+                )
+            ) ,
+            owner: &program_id,
+            rent_epoch: Epoch::default(),
+            data: Rc::new(
+                RefCell::new(
+                    bank_account_data
+                )
+            ),
+            is_signer: false,
+            is_writable: false,
+            executable: false
+        };
+
+        let error = validate_signer_bank_account(&program_id, &message_signer, &bank_account_info).unwrap_err();
+
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::SignerAccountMismatch as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn validate_bank_account_authority_fail_tampered_authority() {
+        let program_id = Pubkey::new_unique();
+        let message_signer = Pubkey::new_unique();
+        let bank_account = UserBankAccount::get_user_bank_account_using_fpa(&message_signer, &program_id);
+
+        let mut bank_account_data = UserBankAccount {
+            discriminator: UserBankAccount::get_bank_account_discriminator(),
+            authority: Pubkey::new_unique(),
+            bump: bank_account.1,
+            ..UserBankAccount::default()
+        }.try_to_vec().unwrap();
+
+        let mut balance = solana_program::native_token::sol_to_lamports(0.5);
+
+        let bank_account_info: AccountInfo = AccountInfo {
+            key: &bank_account.0,
+            lamports: Rc::new(
+                RefCell::new(
+                    &mut balance
+                )
+            ),
+            owner: &program_id,
+            rent_epoch: Epoch::default(),
+            data: Rc::new(
+                RefCell::new(
+                    &mut bank_account_data[..]
+                )
+            ),
+            is_signer: false,
+            is_writable: false,
+            executable: false
+        };
+
+        let error = validate_bank_account_authority(&message_signer, &bank_account_info).unwrap_err();
+
+        assert_eq!(
+            ProgramError::InvalidAccountData,
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    fn with_version_field(ed25519: &Vec<u8>, version_field: &str) -> Vec<u8> {
+        let mut data = ed25519.get(..112).unwrap().to_vec();
+        data.extend_from_slice(version_field.as_bytes());
+        data.extend_from_slice(ed25519.get(112..).unwrap());
+
+        data
+    }
+
+    #[test]
+    fn validate_message_dispatch_v1() {
+        let ed25519: Vec<u8> = vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,81,0,255,255,187,220,42,181,173,60,36,199,230,
+            65,125,124,22,8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,14,181,
+            72,109,61,24,119,85,44,198,174,107,148,186,230,153,125,23,68,160,221,230,152,237,53,76,202,
+            190,78,181,70,75,28,196,224,228,136,185,175,65,58,161,11,44,120,53,184,119,161,31,31,90,
+            13,117,195,128,107,151,150,158,196,151,216,1,55,66,101,71,121,102,65,71,103,101,104,67,54,
+            102,86,80,55,81,80,72,104,87,103,71,106,119,83,112,97,74,105,118,78,49,54,69,81,72,
+            87,54,111,89,84,116,44,49,48,48,48,44,72,101,108,108,111,32,80,111,111,114,105,97,71,
+            71,32,240,159,152,131,33,44,52,49,48,50,52,52,52,56,48,48
+        ];
+
+        let (version, message_data) = split_message_version(&ed25519, 1).unwrap();
+        assert_eq!(version, 1u8, "Mismatch message versions!");
+
+        let message = validate_message(version, &message_data).unwrap();
+        match message {
+            Message::V1(message_v1) => assert_eq!(message_v1.lamports, 1000u64),
+            _ => panic!("expected MessageV1")
+        };
+    }
+
+    #[test]
+    fn validate_message_dispatch_v2() {
+        let ed25519: Vec<u8> = vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,110,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,
+            22,8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,199,178,212,168,174,172,68,109,
+            201,55,218,25,124,218,14,198,195,172,244,222,204,20,88,89,145,219,228,21,48,150,24,25,94,248,2,64,
+            4,107,189,47,85,190,96,252,193,75,16,87,93,38,236,253,172,152,191,140,164,249,217,10,200,160,133,12,
+            55,66,101,71,121,102,65,71,103,101,104,67,54,102,86,80,55,81,80,72,104,87,103,71,106,119,83,112,
+            97,74,105,118,78,49,54,69,81,72,87,54,111,89,84,116,44,69,80,106,70,87,100,100,53,65,117,102,
+            113,83,83,113,101,77,50,113,78,49,120,122,121,98,97,112,67,56,71,52,119,69,71,71,107,90,119,121,
+            84,68,116,49,118,44,49,48,48,48,44,240,159,152,131,44,52,49,48,50,52,52,52,56,48,48
+        ];
+        let ed25519 = with_version_field(&ed25519, "v2,");
+
+        let (version, message_data) = split_message_version(&ed25519, 1).unwrap();
+        assert_eq!(version, 2u8, "Mismatch message versions!");
+
+        let message = validate_message(version, &message_data).unwrap();
+        match message {
+            Message::V2(message_v2) => assert_eq!(message_v2.amount, 1000u64),
+            _ => panic!("expected MessageV2")
+        };
+    }
+
+    #[test]
+    fn validate_message_fail_unsupported_version() {
+        let ed25519: Vec<u8> = vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,81,0,255,255,187,220,42,181,173,60,36,199,230,
+            65,125,124,22,8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,14,181,
+            72,109,61,24,119,85,44,198,174,107,148,186,230,153,125,23,68,160,221,230,152,237,53,76,202,
+            190,78,181,70,75,28,196,224,228,136,185,175,65,58,161,11,44,120,53,184,119,161,31,31,90,
+            13,117,195,128,107,151,150,158,196,151,216,1,55,66,101,71,121,102,65,71,103,101,104,67,54,
+            102,86,80,55,81,80,72,104,87,103,71,106,119,83,112,97,74,105,118,78,49,54,69,81,72,
+            87,54,111,89,84,116,44,49,48,48,48,44,72,101,108,108,111,32,80,111,111,114,105,97,71,
+            71,32,240,159,152,131,33,44,52,49,48,50,52,52,52,56,48,48
+        ];
+        let ed25519 = with_version_field(&ed25519, "v9,");
+
+        let (version, message_data) = split_message_version(&ed25519, 1).unwrap();
+        let error = validate_message(version, &message_data).unwrap_err();
+
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::UnsupportedMessageVersion as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn split_message_revocation_epoch_success() {
+        let ed25519: Vec<u8> = vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,81,0,255,255,187,220,42,181,173,60,36,199,230,
+            65,125,124,22,8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,14,181,
+            72,109,61,24,119,85,44,198,174,107,148,186,230,153,125,23,68,160,221,230,152,237,53,76,202,
+            190,78,181,70,75,28,196,224,228,136,185,175,65,58,161,11,44,120,53,184,119,161,31,31,90,
+            13,117,195,128,107,151,150,158,196,151,216,1,55,66,101,71,121,102,65,71,103,101,104,67,54,
+            102,86,80,55,81,80,72,104,87,103,71,106,119,83,112,97,74,105,118,78,49,54,69,81,72,
+            87,54,111,89,84,116,44,49,48,48,48,44,72,101,108,108,111,32,80,111,111,114,105,97,71,
+            71,32,240,159,152,131,33,44,52,49,48,50,52,52,52,56,48,48
+        ];
+
+        let (revocation_epoch, message_data) = split_message_revocation_epoch(&ed25519).unwrap();
+        assert_eq!(revocation_epoch, 0u64, "Mismatch revocation epochs!");
+        assert_eq!(message_data, ed25519, "Mismatch message data!");
+
+        let (revocation_epoch, message_data) = split_message_revocation_epoch(
+            &with_version_field(&ed25519, "e3,")
+        ).unwrap();
+        assert_eq!(revocation_epoch, 3u64, "Mismatch revocation epochs!");
+        assert_eq!(message_data, ed25519, "Mismatch message data!");
+
+        let message_v1 = validate_message_v1(&message_data).unwrap();
+        assert_eq!(message_v1.lamports, 1000u64);
+    }
+
+    #[test]
+    fn validate_memo_success() {
+        validate_memo("Hello PooriaGG 😃!".as_bytes()).unwrap();
+        validate_memo(&[]).unwrap();
+    }
+
+    #[test]
+    fn validate_memo_fail() {
+        let error = validate_memo(&[72, 105, 0xff, 0xfe]).unwrap_err();
+
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::MemoNotUtf8 as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    /// single-signature ed25519 instruction data -> offsets = (signature, pubkey, message, instruction index)
+    fn ed25519_instruction_data(
+        offsets: (u16, u16, u16, u16),
+        fields: &[(usize, &[u8])],
+        len: usize
+    ) -> Vec<u8> {
+        let (signature_offset, pubkey_offset, message_offset, instruction_index) = offsets;
+
+        let mut ed25519 = vec![0u8; len];
+        ed25519[0] = 1;
+        for (index, offset) in [
+            signature_offset,
+            instruction_index,
+            pubkey_offset,
+            instruction_index,
+            message_offset,
+            5,
+            instruction_index
+        ].iter().enumerate() {
+            ed25519[2 + index * 2..4 + index * 2].copy_from_slice(&offset.to_le_bytes());
+        };
+        for (offset, field) in fields {
+            ed25519[*offset..*offset + field.len()].copy_from_slice(field);
+        };
+
+        ed25519
+    }
+
+    #[test]
+    fn parse_ed25519_instruction_data_follows_offsets() {
+        let pubkey = [7u8; 32];
+        let signature = [9u8; 64];
+        let message = b"hello";
+
+        // message first, then the signature and the pubkey
+        let ed25519 = ed25519_instruction_data(
+            (21, 85, 16, u16::MAX),
+            &[(16, message), (21, &signature), (85, &pubkey)],
+            117
+        );
+
+        let canonical = parse_ed25519_instruction_data(&ed25519, 0, 0).unwrap();
+        assert_eq!(canonical.get(16..48).unwrap(), pubkey.as_slice(), "Pubkey mismatch.");
+        assert_eq!(canonical.get(48..112).unwrap(), signature.as_slice(), "Signature mismatch.");
+        assert_eq!(canonical.get(112..).unwrap(), message.as_slice(), "Message mismatch.");
+
+        // canonical layout parses to itself
+        assert_eq!(parse_ed25519_instruction_data(&canonical, 0, 0).unwrap(), canonical);
+
+        // offsets may refer to the ed25519 instruction by its own index
+        let ed25519 = ed25519_instruction_data(
+            (48, 16, 112, 2),
+            &[(16, &pubkey), (48, &signature), (112, message)],
+            117
+        );
+        assert_eq!(parse_ed25519_instruction_data(&ed25519, 2, 0).unwrap(), canonical);
+    }
+
+    #[test]
+    fn validate_signer_pubkey_fail_mismatch() {
+        let mut ed25519: Vec<u8> = vec![0u8; 112];
+        ed25519[16..48].copy_from_slice(&[7u8; 32]);
+        ed25519.extend_from_slice(
+            format!("{},1000,,4102444800", Pubkey::new_unique()).as_bytes()
+        );
+
+        let message_v1 = validate_message_v1(&ed25519).unwrap();
+        validate_signer_pubkey(&ed25519, &message_v1.signer).unwrap();
+
+        // message validated against a different pubkey than the verified one
+        let error = validate_signer_pubkey(&ed25519, &Pubkey::new_from_array([8u8; 32])).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::SignerPubkeyMismatch as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+
+        // truncated ed25519 data
+        let error = validate_signer_pubkey(&ed25519[..40].to_vec(), &message_v1.signer).unwrap_err();
+        assert_eq!(
+            ProgramError::Custom(
+                BankError::InvalidEd25519SignatureVerificationInstruction as u32
+            ),
+            error,
+            "Mismatch error types!"
+        );
+    }
+
+    #[test]
+    fn parse_ed25519_instruction_data_selects_signature_index() {
+        let first = ([7u8; 32], [9u8; 64], b"hello");
+        let second = ([8u8; 32], [10u8; 64], b"world");
+
+        // 2 offsets headers (2..30) followed by pubkey, signature and message of each signature
+        let mut ed25519 = vec![2u8, 0u8];
+        for (index, base) in [30u16, 131u16].iter().enumerate() {
+            for offset in [base + 32, u16::MAX, *base, u16::MAX, base + 96, 5, u16::MAX] {
+                ed25519.extend_from_slice(&offset.to_le_bytes());
+            };
+            assert_eq!(ed25519.len(), 2 + (index + 1) * 14);
+        };
+        for (pubkey, signature, message) in [first, second] {
+            ed25519.extend_from_slice(&pubkey);
+            ed25519.extend_from_slice(&signature);
+            ed25519.extend_from_slice(message);
+        };
+
+        for (signature_index, (pubkey, signature, message)) in [first, second].iter().enumerate() {
+            let canonical = parse_ed25519_instruction_data(&ed25519, 0, signature_index as u8).unwrap();
+            assert_eq!(canonical.get(16..48).unwrap(), pubkey.as_slice(), "Pubkey mismatch.");
+            assert_eq!(canonical.get(48..112).unwrap(), signature.as_slice(), "Signature mismatch.");
+            assert_eq!(canonical.get(112..).unwrap(), message.as_slice(), "Message mismatch.");
+        };
+    }
+
+    #[test]
+    fn parse_ed25519_instruction_data_fail_unexpected_layout() {
+        let pubkey = [7u8; 32];
+        let signature = [9u8; 64];
+        let message = b"hello";
+        let canonical_fields: &[(usize, &[u8])] = &[(16, &pubkey), (48, &signature), (112, message)];
+
+        let mut two_signatures = ed25519_instruction_data((48, 16, 112, u16::MAX), canonical_fields, 117);
+        two_signatures[0] = 2;
+
+        let tampered_instructions = vec![
+            // zero signatures
+            (vec![0u8, 0u8], 0),
+            // empty instruction data
+            (vec![], 0),
+            // signature index past the signatures count
+            (ed25519_instruction_data((48, 16, 112, u16::MAX), canonical_fields, 117), 1),
+            // second signature's offsets header is made of the first signature's fields
+            (two_signatures, 1),
+            // message lives in another instruction
+            (ed25519_instruction_data((48, 16, 112, 1), canonical_fields, 117), 0),
+            // message runs past the instruction data
+            (ed25519_instruction_data((48, 16, 114, u16::MAX), canonical_fields, 117), 0),
+            // truncated offsets header
+            (ed25519_instruction_data((48, 16, 112, u16::MAX), canonical_fields, 117)[..10].to_vec(), 0)
+        ];
+
+        for (ed25519, signature_index) in tampered_instructions {
+            let error = parse_ed25519_instruction_data(&ed25519, 0, signature_index).unwrap_err();
+
+            assert_eq!(
+                ProgramError::Custom(
+                    BankError::InvalidEd25519SignatureVerificationInstruction as u32
+                ),
+                error,
+                "Mismatch error types!"
+            );
+        };
+    }
 }
@@ -10,11 +10,31 @@ use {
         instruction::*,
         processor::{
             Processor,
-            MEMO_PROGRAM_ID
+            MEMO_PROGRAM_ID,
+            MAX_BLOCKED_RECIPIENTS,
+            INTEGRITY_INVALID_DISCRIMINATOR,
+            INTEGRITY_INVALID_BUMP,
+            INTEGRITY_ZERO_AUTHORITY,
+            INTEGRITY_INCONSISTENT_SIGNATURES,
+            INTEGRITY_BELOW_RENT_MINIMUM
         },
         state::{
             UserBankAccount,
-            VerifiedSignature
+            BankAccountConfig,
+            RentExemptStatus,
+            EffectiveWithdrawable,
+            WithdrawableLimit,
+            BankAccountSummary,
+            WithdrawEvent,
+            WithdrawKind,
+            VerifiedSignature,
+            SignatureFailReason,
+            MAX_STORED_SIGNATURES,
+            MAX_WITHDRAW_EVENTS,
+            MIN_ED25519_WITHDRAW_LAMPORTS,
+            WithdrawalReceipt,
+            PaymentEscrow,
+            ProgramConfig
         }
     },
     solana_program_test::{
@@ -26,21 +46,31 @@ use {
     },
     solana_sdk::{
         account::Account as SolanaAccount,
+        account_info::AccountInfo,
         borsh0_10::try_from_slice_unchecked,
         clock::{
             Epoch,
             Clock
         },
-        hash::Hash,
+        compute_budget::ComputeBudgetInstruction,
+        ed25519_instruction::new_ed25519_instruction,
+        ed25519_program::ID as ED25519_PROGRAM_ID,
+        entrypoint::ProgramResult,
+        hash::{
+            Hash,
+            hashv
+        },
         instruction::{
             Instruction,
-            InstructionError
+            InstructionError,
+            AccountMeta
         },
         native_token::{
             sol_to_lamports, LAMPORTS_PER_SOL
         },
         program_error,
         program_option::COption,
+        program::invoke,
         program_pack::Pack,
         pubkey::Pubkey,
         rent::Rent,
@@ -70,7 +100,8 @@ use {
             Mint
         },
         ID as TOKEN_STANDARD_PROGRAM
-    }
+    },
+    spl_token_2022::ID as TOKEN_2022_PROGRAM
 };
 
 fn setup(program_id: &Pubkey) -> ProgramTest {
@@ -83,6 +114,31 @@ fn setup(program_id: &Pubkey) -> ProgramTest {
     program_test
 }
 
+/// forwards the instruction-data to the program of the first account with the remaining accounts (bank instruction runs as a CPI)
+fn cpi_forwarder_processor(
+    _program_id: &Pubkey,
+    accounts_info: &[AccountInfo],
+    instruction_data: &[u8]
+) -> ProgramResult {
+    let (callee_program_account_info, forwarded_accounts_info) = accounts_info.split_first().unwrap();
+
+    invoke(
+        &Instruction {
+            program_id: *callee_program_account_info.key,
+            accounts: forwarded_accounts_info
+                .iter()
+                .map(|account_info| AccountMeta {
+                    pubkey: *account_info.key,
+                    is_signer: account_info.is_signer,
+                    is_writable: account_info.is_writable
+                })
+                .collect(),
+            data: instruction_data.to_vec()
+        },
+        accounts_info
+    )
+}
+
 fn setup_new_mint_account(
     pt: &mut ProgramTest,
     token_program_id: &Pubkey,
@@ -233,37 +289,142 @@ async fn setup_new_bank_account(
         .unwrap();
 }
 
-#[tokio::test]
-async fn test_create_bank_account_success() {
-    let program_id = Pubkey::new_from_array([5; 32]);
-    let pt = setup(&program_id);
-    let (
-        mut banks_client,
-        payer,
+async fn setup_new_bank_account_with_index(
+    banks_client: &mut BanksClient,
+    bank_account_owner: &Keypair,
+    index: u16,
+    program_id: &Pubkey,
+    recent_blockhash: Hash
+) -> Pubkey {
+    let bank_account_pda = UserBankAccount::get_user_bank_account_with_index(
+        &bank_account_owner.pubkey(),
+        index,
+        program_id
+    ).0;
+
+    let ix = create_create_bank_account_with_index_instruction(
+        &bank_account_owner.pubkey(),
+        &bank_account_owner.pubkey(),
+        &bank_account_pda,
+        &SYSTEM_PROGRAM_ID,
+        &get_program_config_pda(program_id),
+        index,
+        program_id
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&bank_account_owner.pubkey()),
+        &[bank_account_owner],
         recent_blockhash
-    ) = pt.start().await;
+    );
 
-    let bank_account_owner = payer;
-    let bank_account_pda = Pubkey::try_find_program_address(
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    bank_account_pda
+}
+
+fn new_ed25519_signature_verification_instruction(
+    message_signer: &Keypair,
+    message: &str
+) -> Instruction {
+    let dalek_keypair = ed25519_dalek::Keypair::from_bytes(
+        &message_signer.to_bytes()
+    ).unwrap();
+
+    new_ed25519_instruction(
+        &dalek_keypair,
+        message.as_bytes()
+    )
+}
+
+fn get_bank_account_pda(
+    bank_account_owner: &Pubkey,
+    program_id: &Pubkey
+) -> Pubkey {
+    Pubkey::try_find_program_address(
         &[
             b"user_bank_account",
-            bank_account_owner.pubkey().as_ref()
+            bank_account_owner.as_ref()
         ],
-        &program_id
-    ).unwrap().0;
+        program_id
+    ).unwrap().0
+}
 
-    let ix = create_create_initialize_bank_account_instruction(
+fn get_program_config_pda(
+    program_id: &Pubkey
+) -> Pubkey {
+    Pubkey::try_find_program_address(
+        &[
+            b"program_config"
+        ],
+        program_id
+    ).unwrap().0
+}
+
+async fn get_token_balance(
+    banks_client: &mut BanksClient,
+    token_account: &Pubkey
+) -> u64 {
+    let token_account_data = banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+
+    TokenAccount::unpack(&token_account_data).unwrap().amount
+}
+
+async fn fund_bank_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    bank_account: &Pubkey,
+    lamports: u64,
+    recent_blockhash: Hash
+) {
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            transfer_lamports(
+                &payer.pubkey(),
+                bank_account,
+                lamports
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+}
+
+async fn update_bank_account_config(
+    banks_client: &mut BanksClient,
+    bank_account_owner: &Keypair,
+    program_id: &Pubkey,
+    config: &BankAccountConfig,
+    recent_blockhash: Hash
+) {
+    let ix = create_update_config(
         &bank_account_owner.pubkey(),
+        &get_bank_account_pda(&bank_account_owner.pubkey(), program_id),
         &bank_account_owner.pubkey(),
-        &bank_account_pda,
         &SYSTEM_PROGRAM_ID,
-        &program_id
+        program_id,
+        config
     );
 
     let tx = Transaction::new_signed_with_payer(
         &[ix],
         Some(&bank_account_owner.pubkey()),
-        &[&bank_account_owner],
+        &[bank_account_owner],
         recent_blockhash
     );
 
@@ -271,26 +432,227 @@ async fn test_create_bank_account_success() {
         .process_transaction(tx)
         .await
         .unwrap();
+}
 
-    let bank_account_data = banks_client
-        .get_account(bank_account_pda)
+async fn check_rent_exempt(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    bank_account: &Pubkey,
+    program_id: &Pubkey,
+    recent_blockhash: Hash
+) -> RentExemptStatus {
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_check_rent_exempt(
+                bank_account,
+                program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash
+    );
+
+    let return_data = banks_client
+        .simulate_transaction(tx)
         .await
         .unwrap()
+        .simulation_details
         .unwrap()
-        .data;
-    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
-        &bank_account_data
+        .return_data
+        .unwrap();
+
+    RentExemptStatus::try_from_slice(&return_data.data).unwrap()
+}
+
+async fn get_effective_withdrawable(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    bank_account: &Pubkey,
+    token_account: Option<&Pubkey>,
+    program_id: &Pubkey,
+    recent_blockhash: Hash
+) -> EffectiveWithdrawable {
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_get_effective_withdrawable(
+                bank_account,
+                token_account,
+                program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash
+    );
+
+    let return_data = banks_client
+        .simulate_transaction(tx)
+        .await
+        .unwrap()
+        .simulation_details
+        .unwrap()
+        .return_data
+        .unwrap();
+
+    EffectiveWithdrawable::try_from_slice(&return_data.data).unwrap()
+}
+
+async fn verify_integrity(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    bank_account: &Pubkey,
+    program_id: &Pubkey,
+    recent_blockhash: Hash
+) -> u8 {
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_verify_integrity(
+                bank_account,
+                program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash
+    );
+
+    let return_data = banks_client
+        .simulate_transaction(tx)
+        .await
+        .unwrap()
+        .simulation_details
+        .unwrap()
+        .return_data
+        .unwrap();
+
+    return_data.data[0]
+}
+
+async fn dry_run_signature(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    message_signer: &Keypair,
+    message: &str,
+    bank_account: &Pubkey,
+    token_accounts: Option<(&Pubkey, &Pubkey)>,
+    program_id: &Pubkey,
+    recent_blockhash: Hash
+) -> bool {
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(message_signer, message),
+            create_dry_run_signature(
+                bank_account,
+                token_accounts,
+                program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash
+    );
+
+    let return_data = banks_client
+        .simulate_transaction(tx)
+        .await
+        .unwrap()
+        .simulation_details
+        .unwrap()
+        .return_data
+        .unwrap();
+
+    bool::try_from_slice(&return_data.data).unwrap()
+}
+
+/// adds a bank-account (with `config`) holding `bank_token_amount` tokens of `mint_account` and a token-account for `to`
+fn setup_token_bank_account(
+    pt: &mut ProgramTest,
+    program_id: &Pubkey,
+    message_signer: &Pubkey,
+    to: &Pubkey,
+    mint_account: &Pubkey,
+    bank_token_amount: u64,
+    config: BankAccountConfig
+) -> (Pubkey, Pubkey, Pubkey) {
+    let (
+        bank_account,
+        bump
+    ) = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.as_ref()
+        ],
+        program_id
     ).unwrap();
 
-    assert_eq!(
-        bank_account_info.authority,
-        bank_account_owner.pubkey(),
-        "Authority mismatch."
+    pt.add_account(
+        *to,
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            rent_epoch: Epoch::default(),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false
+        }
+    );
+    pt.add_account(
+        bank_account,
+        SolanaAccount {
+            owner: *program_id,
+            lamports: sol_to_lamports(1.0),
+            rent_epoch: Epoch::default(),
+            data: UserBankAccount {
+                discriminator: UserBankAccount::get_bank_account_discriminator(),
+                bump,
+                account_created_at: Clock::default().unix_timestamp,
+                authority: *message_signer,
+                config,
+                ..UserBankAccount::default()
+            }.try_to_vec().unwrap(),
+            executable: false
+        }
+    );
+
+    setup_new_mint_account(
+        pt,
+        &TOKEN_STANDARD_PROGRAM,
+        mint_account,
+        &Pubkey::new_unique(),
+        bank_token_amount
     );
+
+    let to_token_account = Pubkey::new_unique();
+    setup_new_token_account(
+        pt,
+        &TOKEN_STANDARD_PROGRAM,
+        mint_account,
+        &to_token_account,
+        to,
+        0u64
+    );
+    setup_new_associated_token_account(
+        pt,
+        &TOKEN_STANDARD_PROGRAM,
+        mint_account,
+        &bank_account,
+        bank_token_amount
+    );
+
+    let bank_account_token_account = Pubkey::try_find_program_address(
+        &[
+            bank_account.as_ref(),
+            TOKEN_STANDARD_PROGRAM.as_ref(),
+            mint_account.as_ref()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+
+    (bank_account, bank_account_token_account, to_token_account)
 }
 
 #[tokio::test]
-async fn test_create_bank_account_fail_invalid_seeds() {
+async fn test_create_bank_account_success() {
     let program_id = Pubkey::new_from_array([5; 32]);
     let pt = setup(&program_id);
     let (
@@ -302,7 +664,7 @@ async fn test_create_bank_account_fail_invalid_seeds() {
     let bank_account_owner = payer;
     let bank_account_pda = Pubkey::try_find_program_address(
         &[
-            b"User_bank_account",
+            b"user_bank_account",
             bank_account_owner.pubkey().as_ref()
         ],
         &program_id
@@ -323,82 +685,75 @@ async fn test_create_bank_account_fail_invalid_seeds() {
         recent_blockhash
     );
 
-    let error = banks_client
+    banks_client
         .process_transaction(tx)
         .await
-        .unwrap_err()
         .unwrap();
 
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
+        &bank_account_data
+    ).unwrap();
+
     assert_eq!(
-        error,
-        TransactionError::InstructionError(
-            0,
-            InstructionError::InvalidSeeds
-        )
+        bank_account_info.authority,
+        bank_account_owner.pubkey(),
+        "Authority mismatch."
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_lamport_success() {
+async fn test_create_bank_account_initializes_zeroed_data() {
     let program_id = Pubkey::new_from_array([5; 32]);
     let pt = setup(&program_id);
-
     let (
         mut banks_client,
         payer,
         recent_blockhash
     ) = pt.start().await;
 
-    let bank_account_owner = payer;
     setup_new_bank_account(
         &mut banks_client,
-        &bank_account_owner,
+        &payer,
         &program_id,
         recent_blockhash
     ).await;
 
-    let bank_account_pda = Pubkey::try_find_program_address(
-        &[
-            b"user_bank_account",
-            bank_account_owner.pubkey().as_ref()
-        ],
-        &program_id
-    ).unwrap().0;
+    let bank_account_data = banks_client
+        .get_account(get_bank_account_pda(&payer.pubkey(), &program_id))
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
+        &bank_account_data
+    ).unwrap();
+    let initialized_size = bank_account_info
+        .try_to_vec()
+        .unwrap()
+        .len();
 
-    let transfer_lamport_ix = transfer_lamports(
-        &bank_account_owner.pubkey(),
-        &bank_account_pda,
-        sol_to_lamports(1.0)
-    );
-    let withdraw_lamport_ix = create_withdraw_lamports(
-        &bank_account_owner.pubkey(),
-        &bank_account_pda,
-        &bank_account_owner.pubkey(),
-        &program_id,
-        &sol_to_lamports(0.5)
+    assert_eq!(
+        bank_account_info.config,
+        BankAccountConfig::default(),
+        "Config must start zeroed."
     );
-
-    let tx = Transaction::new_signed_with_payer(
-        &[
-            transfer_lamport_ix,
-            withdraw_lamport_ix
-        ],
-        Some(&bank_account_owner.pubkey()),
-        &[&bank_account_owner],
-        recent_blockhash
+    assert!(bank_account_info.signatures.is_empty());
+    assert!(
+        bank_account_data[initialized_size..].iter().all(|byte| *byte == 0),
+        "Unused bytes must stay zeroed."
     );
-
-    banks_client
-        .process_transaction(tx)
-        .await
-        .unwrap();
 }
 
 #[tokio::test]
-async fn test_withdraw_lamport_fail_insufficient_balance() {
+async fn test_create_bank_account_fail_invalid_seeds() {
     let program_id = Pubkey::new_from_array([5; 32]);
     let pt = setup(&program_id);
-
     let (
         mut banks_client,
         payer,
@@ -406,39 +761,24 @@ async fn test_withdraw_lamport_fail_insufficient_balance() {
     ) = pt.start().await;
 
     let bank_account_owner = payer;
-    setup_new_bank_account(
-        &mut banks_client,
-        &bank_account_owner,
-        &program_id,
-        recent_blockhash
-    ).await;
-
     let bank_account_pda = Pubkey::try_find_program_address(
         &[
-            b"user_bank_account",
+            b"User_bank_account",
             bank_account_owner.pubkey().as_ref()
         ],
         &program_id
     ).unwrap().0;
 
-    let transfer_lamport_ix = transfer_lamports(
+    let ix = create_create_initialize_bank_account_instruction(
         &bank_account_owner.pubkey(),
-        &bank_account_pda,
-        sol_to_lamports(1.0)
-    );
-    let withdraw_lamport_ix = create_withdraw_lamports(
         &bank_account_owner.pubkey(),
         &bank_account_pda,
-        &bank_account_owner.pubkey(),
-        &program_id,
-        &sol_to_lamports(1.000001)
+        &SYSTEM_PROGRAM_ID,
+        &program_id
     );
 
     let tx = Transaction::new_signed_with_payer(
-        &[
-            transfer_lamport_ix,
-            withdraw_lamport_ix
-        ],
+        &[ix],
         Some(&bank_account_owner.pubkey()),
         &[&bank_account_owner],
         recent_blockhash
@@ -453,65 +793,223 @@ async fn test_withdraw_lamport_fail_insufficient_balance() {
     assert_eq!(
         error,
         TransactionError::InstructionError(
-            1,
-            InstructionError::Custom(
-                BankError::InsufficientLamportBalance as u32
-            )
+            0,
+            InstructionError::InvalidSeeds
         )
     );
 }
 
+async fn create_bank_account_funded_by(
+    banks_client: &mut BanksClient,
+    funder: &Keypair,
+    authority: &Keypair,
+    program_id: &Pubkey,
+    recent_blockhash: Hash
+) -> Result<(), TransactionError> {
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_create_initialize_bank_account_with_program_config_instruction(
+                &funder.pubkey(),
+                &authority.pubkey(),
+                &get_bank_account_pda(&authority.pubkey(), program_id),
+                &SYSTEM_PROGRAM_ID,
+                &get_program_config_pda(program_id),
+                program_id
+            )
+        ],
+        Some(&funder.pubkey()),
+        &[funder, authority],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
 #[tokio::test]
-async fn test_withdraw_spl_token_success() {
-    let program_id = Pubkey::new_from_array([2; 32]);
+async fn test_create_bank_account_creation_cap_per_funder() {
+    let program_id = Pubkey::new_from_array([5; 32]);
     let mut pt = setup(&program_id);
 
-    let operator = Keypair::new();
+    let other_funder = Keypair::new();
     pt.add_account(
-        operator.pubkey(),
+        other_funder.pubkey(),
         SolanaAccount {
-            lamports: sol_to_lamports(100.0),
+            lamports: sol_to_lamports(10.0),
             owner: Pubkey::default(),
-            data: vec![],
+            rent_epoch: Epoch::default(),
             executable: false,
-            rent_epoch: Epoch::default()
+            data: vec![]
         }
     );
 
-    let mint_account = Pubkey::new_from_array([3;32]);
-    setup_new_mint_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &operator.pubkey(),
-        1000_00u64
+    let mut context = pt.start_with_context().await;
+    let funder = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_init_program_config(
+                &funder.pubkey(),
+                &get_program_config_pda(&program_id),
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                2,
+                3_600
+            )
+        ],
+        Some(&funder.pubkey()),
+        &[&funder],
+        recent_blockhash
     );
+    context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
 
-    let operator_token_account = Pubkey::new_from_array([4; 32]);
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &operator_token_account,
-        &operator.pubkey(),
-        0_00u64
+    for _ in 0..2 {
+        create_bank_account_funded_by(
+            &mut context.banks_client,
+            &funder,
+            &Keypair::new(),
+            &program_id,
+            recent_blockhash
+        ).await.unwrap();
+    };
+
+    let error = create_bank_account_funded_by(
+        &mut context.banks_client,
+        &funder,
+        &Keypair::new(),
+        &program_id,
+        recent_blockhash
+    ).await.unwrap_err();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::CreationRateExceeded as u32
+            )
+        ),
+        "Mismatch error types!"
     );
 
-    let bank_account_pda = Pubkey::try_find_program_address(
+    // the cap is tracked per funder
+    create_bank_account_funded_by(
+        &mut context.banks_client,
+        &other_funder,
+        &Keypair::new(),
+        &program_id,
+        recent_blockhash
+    ).await.unwrap();
+
+    let mut clock = context.banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    clock.unix_timestamp += 3_600;
+    context.set_sysvar(&clock);
+
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    create_bank_account_funded_by(
+        &mut context.banks_client,
+        &funder,
+        &Keypair::new(),
+        &program_id,
+        recent_blockhash
+    ).await.unwrap();
+
+    let program_config_data = context.banks_client
+        .get_account(get_program_config_pda(&program_id))
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let program_config = ProgramConfig::try_from_slice(
+        &program_config_data
+    ).unwrap();
+
+    // other funder's window closed as well -> only the fresh window is tracked
+    assert_eq!(program_config.funder_creations.len(), 1, "Funder creations count mismatch.");
+    assert_eq!(program_config.funder_creations[0].funder, funder.pubkey(), "Funder mismatch.");
+    assert_eq!(program_config.funder_creations[0].count, 1, "Creations count mismatch.");
+}
+
+#[tokio::test]
+async fn test_update_program_config_fail_not_admin() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
         &[
-            b"user_bank_account",
-            operator.pubkey().as_ref()
+            create_init_program_config(
+                &payer.pubkey(),
+                &get_program_config_pda(&program_id),
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                2,
+                3_600
+            )
         ],
-        &program_id
-    ).unwrap().0;
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
 
-    setup_new_associated_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &bank_account_pda,
-        1000_00u64
+    let not_admin = Keypair::new();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_update_program_config(
+                &not_admin.pubkey(),
+                &get_program_config_pda(&program_id),
+                &program_id,
+                u16::MAX,
+                0
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &not_admin],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InvalidProgramConfigAdmin as u32
+            )
+        ),
+        "Mismatch error types!"
     );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamport_success() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
 
     let (
         mut banks_client,
@@ -519,39 +1017,44 @@ async fn test_withdraw_spl_token_success() {
         recent_blockhash
     ) = pt.start().await;
 
+    let bank_account_owner = payer;
     setup_new_bank_account(
         &mut banks_client,
-        &operator,
+        &bank_account_owner,
         &program_id,
         recent_blockhash
     ).await;
 
-    let bank_account_associated_token_account = Pubkey::try_find_program_address(
+    let bank_account_pda = Pubkey::try_find_program_address(
         &[
-            bank_account_pda.as_ref(),
-            TOKEN_STANDARD_PROGRAM.as_ref(),
-            mint_account.as_ref()
+            b"user_bank_account",
+            bank_account_owner.pubkey().as_ref()
         ],
-        &ASSOCIATED_TOKEN_PROGRAM
+        &program_id
     ).unwrap().0;
- 
-    let ix = create_withdraw_spl_tokens(
-        &operator.pubkey(),
+
+    let transfer_lamport_ix = transfer_lamports(
+        &bank_account_owner.pubkey(),
         &bank_account_pda,
-        &bank_account_associated_token_account,
-        &mint_account,
-        &operator_token_account,
+        sol_to_lamports(1.0)
+    );
+    let withdraw_lamport_ix = create_withdraw_lamports(
+        &bank_account_owner.pubkey(),
+        &bank_account_pda,
+        &bank_account_owner.pubkey(),
+        None,
         &program_id,
-        &100_00u64
+        &sol_to_lamports(0.5),
+        None
     );
 
     let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&payer.pubkey()),
         &[
-            &payer,
-            &operator
+            transfer_lamport_ix,
+            withdraw_lamport_ix
         ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
         recent_blockhash
     );
 
@@ -559,84 +1062,12 @@ async fn test_withdraw_spl_token_success() {
         .process_transaction(tx)
         .await
         .unwrap();
+}
 
-    let operator_token_account_data = banks_client
-        .get_account(operator_token_account)
-        .await.unwrap().unwrap().data;
-    let bank_account_associated_token_account_data = banks_client
-        .get_account(bank_account_associated_token_account)
-        .await.unwrap().unwrap().data;
-
-    let operator_token_account_info = TokenAccount::unpack(
-        operator_token_account_data.as_slice()
-    ).unwrap();
-    let bank_account_associated_token_account_info = TokenAccount::unpack(
-        bank_account_associated_token_account_data.as_slice()
-    ).unwrap();
-
-    assert_eq!(
-        operator_token_account_info.amount,
-        100_00u64,
-        "Operator token balance mismatch."
-    );
-    assert_eq!(
-        bank_account_associated_token_account_info.amount,
-        900_00u64,
-        "Bank-account token balance mismatch."
-    );
-}
-
-#[tokio::test]
-async fn test_withdraw_spl_token_fail_invalid_seeds() {
-    let program_id = Pubkey::new_from_array([2; 32]);
-    let mut pt = setup(&program_id);
-
-    let operator = Keypair::new();
-    pt.add_account(
-        operator.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(100.0),
-            owner: Pubkey::default(),
-            data: vec![],
-            executable: false,
-            rent_epoch: Epoch::default()
-        }
-    );
-
-    let mint_account = Pubkey::new_from_array([3;32]);
-    setup_new_mint_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &operator.pubkey(),
-        1000_00u64
-    );
-
-    let operator_token_account = Pubkey::new_from_array([4; 32]);
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &operator_token_account,
-        &operator.pubkey(),
-        0_00u64
-    );
-
-    let bank_account_pda = Pubkey::try_find_program_address(
-        &[
-            b"user_bank_account",
-            operator.pubkey().as_ref()
-        ],
-        &program_id
-    ).unwrap().0;
-
-    setup_new_associated_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &bank_account_pda,
-        1000_00u64
-    );
+#[tokio::test]
+async fn test_withdraw_lamport_exact_max_keeps_account() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
 
     let (
         mut banks_client,
@@ -644,283 +1075,282 @@ async fn test_withdraw_spl_token_fail_invalid_seeds() {
         recent_blockhash
     ) = pt.start().await;
 
+    let bank_account_owner = payer;
     setup_new_bank_account(
         &mut banks_client,
-        &operator,
+        &bank_account_owner,
         &program_id,
         recent_blockhash
     ).await;
 
-    let bank_account_associated_token_account = Pubkey::try_find_program_address(
-        &[
-            Pubkey::default().as_ref(),
-            TOKEN_STANDARD_PROGRAM.as_ref(),
-            mint_account.as_ref()
-        ],
-        &ASSOCIATED_TOKEN_PROGRAM
-    ).unwrap().0;
- 
-    let ix = create_withdraw_spl_tokens(
-        &operator.pubkey(),
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    fund_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
         &bank_account_pda,
-        &bank_account_associated_token_account,
-        &mint_account,
-        &operator_token_account,
-        &program_id,
-        &100_00u64
-    );
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let bank_account_before = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let max_lamports = bank_account_before.lamports - Rent::default().minimum_balance(bank_account_before.data.len());
 
     let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&payer.pubkey()),
         &[
-            &payer,
-            &operator
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &bank_account_owner.pubkey(),
+                None,
+                &program_id,
+                &max_lamports,
+                None
+            )
         ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
         recent_blockhash
     );
 
-    let error = banks_client
+    banks_client
         .process_transaction(tx)
         .await
-        .unwrap_err()
         .unwrap();
 
+    let bank_account_after = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
     assert_eq!(
-        error,
-        TransactionError::InstructionError(
-            0,
-            InstructionError::InvalidSeeds
-        )
+        bank_account_after.lamports,
+        Rent::default().minimum_balance(bank_account_after.data.len()),
+        "Bank-account must keep exactly the rent-exempt minimum."
+    );
+    assert_eq!(
+        bank_account_after.data,
+        bank_account_before.data,
+        "Bank-account data must stay intact."
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_lamports_using_ed25519_success_1() {
-    let program_id = Pubkey::new_from_array([2; 32]);
-    let mut pt = setup(&program_id);
-
-    let message_signer = Keypair::from_bytes(
-        &[
-            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
-            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
-            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
-        ]
-    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
-    pt.add_account(
-        message_signer.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(100.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
-    );
-
-    let to = Keypair::from_bytes(
-        &[
-            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
-            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
-            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
-            117,177,107,131,121,11,228,101,173,11,51,156
-        ]
-    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-    pt.add_account(
-        to.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(1.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
-    );
+async fn test_withdraw_lamport_fail_below_raised_rent() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
 
-    let (
-        mut banks_client,
-        payer,
-        recent_blockhash
-    ) = pt.start().await;
+    let mut context = pt.start_with_context().await;
+    let bank_account_owner = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
 
-    let bank_account_pda = Pubkey::try_find_program_address(
-        &[
-            b"user_bank_account",
-            message_signer.pubkey().as_ref()
-        ],
-        &program_id
-    ).unwrap().0;
     setup_new_bank_account(
-        &mut banks_client,
-        &message_signer,
+        &mut context.banks_client,
+        &bank_account_owner,
         &program_id,
         recent_blockhash
     ).await;
 
-    let send_lamport_to_bank_account_ix = transfer_lamports(
-        &message_signer.pubkey(),
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    fund_bank_account(
+        &mut context.banks_client,
+        &bank_account_owner,
         &bank_account_pda,
-        30 * LAMPORTS_PER_SOL
-    );
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let raised_rent = Rent {
+        lamports_per_byte_year: Rent::default().lamports_per_byte_year * 2,
+        ..Rent::default()
+    };
+    context.set_sysvar(&raised_rent);
 
+    let bank_account_before = context.banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let max_lamports = bank_account_before.lamports - raised_rent.minimum_balance(bank_account_before.data.len());
+
+    // the old maximum would drop the bank-account below the raised rent-exempt minimum
     let tx = Transaction::new_signed_with_payer(
-        &[send_lamport_to_bank_account_ix],
-        Some(&payer.pubkey()),
         &[
-            &payer,
-            &message_signer
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &bank_account_owner.pubkey(),
+                None,
+                &program_id,
+                &(max_lamports + 1),
+                None
+            )
         ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
         recent_blockhash
     );
 
-    banks_client
+    let error = context.banks_client
         .process_transaction(tx)
         .await
+        .unwrap_err()
         .unwrap();
 
-
-    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,1500000000,PooriaGG 😃";
-
-    let ed25519_signature_verification_instruction = Instruction {
-        program_id: solana_sdk::ed25519_program::ID,
-        accounts: vec![],
-        data: vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,64,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,157,169,
-            169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,200,251,137,102,255,74,244,207,8,44,211,53,225,188,191,241,
-            98,236,68,152,169,155,198,174,188,31,92,100,146,18,57,0,15,224,180,15,84,19,119,172,117,108,201,10,78,70,198,54,
-            215,109,7,149,99,100,189,213,112,197,131,197,244,56,141,8,52,102,118,118,89,113,107,99,71,53,105,122,113,86,78,117,
-            77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,80,90,44,49,53,48,48,48,48,48,
-            48,48,48,44,80,111,111,114,105,97,71,71,32,240,159,152,131
-        ]
-    };
-
-    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
-        &bank_account_pda,
-        &to.pubkey(),
-        &to.pubkey(),
-        &to.pubkey(),
-        &SYSTEM_PROGRAM_ID,
-        Some(&MEMO_PROGRAM_ID),
-        &program_id
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InsufficientLamportBalance as u32
+            )
+        )
     );
 
-    let ed = ed25519_signature_verification_instruction.clone();
-
     let tx = Transaction::new_signed_with_payer(
         &[
-            ed25519_signature_verification_instruction,
-            withdraw_lamports_using_ed25519_ix
-        ],
-        Some(&payer.pubkey()),
-        &[
-            &payer,
-            &to
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &bank_account_owner.pubkey(),
+                None,
+                &program_id,
+                &max_lamports,
+                None
+            )
         ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
         recent_blockhash
     );
 
-    banks_client
+    context.banks_client
         .process_transaction(tx)
         .await
         .unwrap();
 
-    let bank_account_data = banks_client
+    let bank_account_after = context.banks_client
         .get_account(bank_account_pda)
         .await
         .unwrap()
-        .unwrap()
-        .data;
-    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
-        &bank_account_data
-    ).unwrap();
-
-    let signature_info = bank_account_info
-        .signatures
-        .get(0usize)
         .unwrap();
-
-    let ed25519_data = ed.data;
-
-    let ed25519_msg = ed25519_data.get(112..).unwrap().to_vec();
     assert_eq!(
-        signature_info.message,
-        ed25519_msg,
-        "Message mismatch."
+        bank_account_after.lamports,
+        raised_rent.minimum_balance(bank_account_after.data.len()),
+        "Bank-account must keep exactly the raised rent-exempt minimum."
     );
-
-    let ed25519_signature: [u8; 64] = ed25519_data.get(48..112).unwrap().try_into().unwrap();
     assert_eq!(
-        signature_info.signature,
-        ed25519_signature,
-        "Signature mismatch."
+        bank_account_after.data,
+        bank_account_before.data,
+        "Bank-account data must stay intact."
     );
+}
 
-    assert!(signature_info.is_ok);
+#[tokio::test]
+async fn test_withdraw_lamport_fail_at_rent_minimum() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
 
-    let bank_account_balance = banks_client
-        .get_balance(bank_account_pda)
-        .await
-        .unwrap();
+    let mut context = pt.start_with_context().await;
+    let bank_account_owner = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
 
-    assert_eq!(
-        bank_account_balance,
-        28_502_289_840u64,
-        "Bank-Account balance mismatch."
+    // a fresh bank-account holds exactly the rent-exempt minimum
+    setup_new_bank_account(
+        &mut context.banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &bank_account_owner.pubkey(),
+                None,
+                &program_id,
+                &1u64,
+                None
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
     );
-    
-    let to_account_balance = banks_client
-        .get_balance(to.pubkey())
+
+    let error = context.banks_client
+        .process_transaction(tx)
         .await
+        .unwrap_err()
         .unwrap();
 
     assert_eq!(
-        to_account_balance,
-        2_498_983_840u64,
-        "To-Account balance mismatch."
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InsufficientLamportBalance as u32
+            )
+        )
     );
-}
 
-#[tokio::test]
-async fn test_withdraw_lamports_using_ed25519_success_2() {
-    let program_id = Pubkey::new_from_array([2; 32]);
-    let mut pt = setup(&program_id);
+    // raised rent leaves the bank-account below the rent-exempt minimum
+    let raised_rent = Rent {
+        lamports_per_byte_year: Rent::default().lamports_per_byte_year * 2,
+        ..Rent::default()
+    };
+    context.set_sysvar(&raised_rent);
 
-    let message_signer = Keypair::from_bytes(
+    let recent_blockhash = context.banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
         &[
-            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
-            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
-            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
-        ]
-    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
-    pt.add_account(
-        message_signer.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(100.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &bank_account_owner.pubkey(),
+                None,
+                &program_id,
+                &1u64,
+                None
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
     );
 
-    let to = Keypair::from_bytes(
-        &[
-            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
-            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
-            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
-            117,177,107,131,121,11,228,101,173,11,51,156
-        ]
-    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-    pt.add_account(
-        to.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(1.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
+    let error = context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InsufficientLamportBalance as u32
+            )
+        )
     );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_with_receipt_success() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
 
     let (
         mut banks_client,
@@ -928,78 +1358,69 @@ async fn test_withdraw_lamports_using_ed25519_success_2() {
         recent_blockhash
     ) = pt.start().await;
 
-    let bank_account_pda = Pubkey::try_find_program_address(
-        &[
-            b"user_bank_account",
-            message_signer.pubkey().as_ref()
-        ],
-        &program_id
-    ).unwrap().0;
+    let bank_account_owner = payer;
     setup_new_bank_account(
         &mut banks_client,
-        &message_signer,
+        &bank_account_owner,
         &program_id,
         recent_blockhash
     ).await;
 
-    let send_lamport_to_bank_account_ix = transfer_lamports(
-        &message_signer.pubkey(),
-        &bank_account_pda,
-        30 * LAMPORTS_PER_SOL
-    );
-
-    let tx = Transaction::new_signed_with_payer(
-        &[send_lamport_to_bank_account_ix],
-        Some(&payer.pubkey()),
+    let bank_account_pda = Pubkey::try_find_program_address(
         &[
-            &payer,
-            &message_signer
+            b"user_bank_account",
+            bank_account_owner.pubkey().as_ref()
         ],
-        recent_blockhash
-    );
-
-    banks_client
-        .process_transaction(tx)
-        .await
-        .unwrap();
-
-
-    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,50000000000,PooriaGG 😃";
-
-    let ed25519_signature_verification_instruction = Instruction {
-        program_id: solana_sdk::ed25519_program::ID,
-        accounts: vec![],
-        data: vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,70,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,
-            191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,207,162,88,16,25,188,185,93,161,90,
-            6,62,215,17,139,27,4,111,147,95,173,85,151,17,243,129,217,239,167,44,50,228,144,63,81,99,163,35,107,
-            189,216,217,184,81,239,40,100,240,183,180,199,149,19,91,145,134,52,42,202,19,33,245,165,11,52,102,118,
-            118,89,113,107,99,71,53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,
-            71,49,68,74,87,55,68,106,68,80,90,44,53,48,48,48,48,48,48,48,48,48,48,44,80,111,111,114,105,97,71,71,32,
-            240,159,152,131
-        ]
-    };
+        &program_id
+    ).unwrap().0;
+    let receipt_pda = WithdrawalReceipt::get_receipt_account_using_fpa(
+        &bank_account_pda,
+        &program_id
+    ).0;
 
-    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
+    let init_receipt_ix = create_init_receipt_instruction(
+        &bank_account_owner.pubkey(),
+        &bank_account_owner.pubkey(),
         &bank_account_pda,
-        &to.pubkey(),
-        &to.pubkey(),
-        &to.pubkey(),
+        &receipt_pda,
         &SYSTEM_PROGRAM_ID,
-        Some(&MEMO_PROGRAM_ID),
         &program_id
     );
+    let transfer_lamport_ix = transfer_lamports(
+        &bank_account_owner.pubkey(),
+        &bank_account_pda,
+        sol_to_lamports(1.0)
+    );
+    let recepient_1 = Pubkey::new_unique();
+    let withdraw_lamport_ix_1 = create_withdraw_lamports(
+        &bank_account_owner.pubkey(),
+        &bank_account_pda,
+        &recepient_1,
+        Some(&receipt_pda),
+        &program_id,
+        &sol_to_lamports(0.25),
+        None
+    );
+    let recepient_2 = Pubkey::new_unique();
+    let withdraw_lamport_ix_2 = create_withdraw_lamports(
+        &bank_account_owner.pubkey(),
+        &bank_account_pda,
+        &recepient_2,
+        Some(&receipt_pda),
+        &program_id,
+        &sol_to_lamports(0.5),
+        None
+    );
 
     let tx = Transaction::new_signed_with_payer(
         &[
-            ed25519_signature_verification_instruction,
-            withdraw_lamports_using_ed25519_ix
-        ],
-        Some(&payer.pubkey()),
-        &[
-            &payer,
-            &to
+            init_receipt_ix,
+            transfer_lamport_ix,
+            withdraw_lamport_ix_1,
+            withdraw_lamport_ix_2
         ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
         recent_blockhash
     );
 
@@ -1008,69 +1429,49 @@ async fn test_withdraw_lamports_using_ed25519_success_2() {
         .await
         .unwrap();
 
-    let bank_account_data = banks_client
-        .get_account(bank_account_pda)
+    let receipt_data = banks_client
+        .get_account(receipt_pda)
         .await
         .unwrap()
         .unwrap()
         .data;
-    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
-        &bank_account_data
+    let receipt = WithdrawalReceipt::try_from_slice(
+        &receipt_data
     ).unwrap();
 
-    let signature_info = bank_account_info
-        .signatures
-        .get(0usize)
-        .unwrap();
+    let expected_hash_1 = hashv(
+        &[
+            [0u8; 32].as_slice(),
+            recepient_1.as_ref(),
+            Pubkey::default().as_ref(),
+            sol_to_lamports(0.25).to_le_bytes().as_slice()
+        ]
+    ).to_bytes();
+    let expected_hash_2 = hashv(
+        &[
+            expected_hash_1.as_slice(),
+            recepient_2.as_ref(),
+            Pubkey::default().as_ref(),
+            sol_to_lamports(0.5).to_le_bytes().as_slice()
+        ]
+    ).to_bytes();
 
     assert_eq!(
-        signature_info.is_ok,
-        false,
-        "Flag is invalid."
+        receipt.withdrawals_count,
+        2,
+        "Withdrawals count mismatch."
+    );
+    assert_eq!(
+        receipt.hash,
+        expected_hash_2,
+        "Receipt hash mismatch."
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_lamports_using_ed25519_success_3() {
-    let program_id = Pubkey::new_from_array([2; 32]);
-    let mut pt = setup(&program_id);
-
-    let message_signer = Keypair::from_bytes(
-        &[
-            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
-            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
-            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
-        ]
-    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
-    pt.add_account(
-        message_signer.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(100.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
-    );
-
-    let to = Keypair::from_bytes(
-        &[
-            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
-            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
-            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
-            117,177,107,131,121,11,228,101,173,11,51,156
-        ]
-    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-    pt.add_account(
-        to.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(1.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
-    );
+async fn test_withdraw_lamport_fail_insufficient_balance() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
 
     let (
         mut banks_client,
@@ -1078,195 +1479,157 @@ async fn test_withdraw_lamports_using_ed25519_success_3() {
         recent_blockhash
     ) = pt.start().await;
 
-    let bank_account_pda = Pubkey::try_find_program_address(
-        &[
-            b"user_bank_account",
-            message_signer.pubkey().as_ref()
-        ],
-        &program_id
-    ).unwrap().0;
+    let bank_account_owner = payer;
     setup_new_bank_account(
         &mut banks_client,
-        &message_signer,
+        &bank_account_owner,
         &program_id,
         recent_blockhash
     ).await;
 
-    let send_lamport_to_bank_account_ix = transfer_lamports(
-        &message_signer.pubkey(),
-        &bank_account_pda,
-        30 * LAMPORTS_PER_SOL
-    );
-
-    let tx = Transaction::new_signed_with_payer(
-        &[send_lamport_to_bank_account_ix],
-        Some(&payer.pubkey()),
+    let bank_account_pda = Pubkey::try_find_program_address(
         &[
-            &payer,
-            &message_signer
+            b"user_bank_account",
+            bank_account_owner.pubkey().as_ref()
         ],
-        recent_blockhash
-    );
-
-    banks_client
-        .process_transaction(tx)
-        .await
-        .unwrap();
-
-
-    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,50000000000,PooriaGG 😃";
-
-    let ed25519_signature_verification_instruction = Instruction {
-        program_id: solana_sdk::ed25519_program::ID,
-        accounts: vec![],
-        data: vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,70,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,
-            191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,207,162,88,16,25,188,185,93,161,90,
-            6,62,215,17,139,27,4,111,147,95,173,85,151,17,243,129,217,239,167,44,50,228,144,63,81,99,163,35,107,
-            189,216,217,184,81,239,40,100,240,183,180,199,149,19,91,145,134,52,42,202,19,33,245,165,11,52,102,118,
-            118,89,113,107,99,71,53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,
-            71,49,68,74,87,55,68,106,68,80,90,44,53,48,48,48,48,48,48,48,48,48,48,44,80,111,111,114,105,97,71,71,32,
-            240,159,152,131
-        ]
-    };
+        &program_id
+    ).unwrap().0;
 
-    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
+    let transfer_lamport_ix = transfer_lamports(
+        &bank_account_owner.pubkey(),
         &bank_account_pda,
-        &to.pubkey(),
-        &to.pubkey(),
-        &to.pubkey(),
-        &SYSTEM_PROGRAM_ID,
-        Some(&MEMO_PROGRAM_ID),
-        &program_id
+        sol_to_lamports(1.0)
+    );
+    let withdraw_lamport_ix = create_withdraw_lamports(
+        &bank_account_owner.pubkey(),
+        &bank_account_pda,
+        &bank_account_owner.pubkey(),
+        None,
+        &program_id,
+        &sol_to_lamports(1.000001),
+        None
     );
 
     let tx = Transaction::new_signed_with_payer(
         &[
-            ed25519_signature_verification_instruction.clone(),
-            withdraw_lamports_using_ed25519_ix.clone(),
-            ed25519_signature_verification_instruction,
-            withdraw_lamports_using_ed25519_ix
-        ],
-        Some(&payer.pubkey()),
-        &[
-            &payer,
-            &to
+            transfer_lamport_ix,
+            withdraw_lamport_ix
         ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
         recent_blockhash
     );
 
-    banks_client
+    let error = banks_client
         .process_transaction(tx)
         .await
+        .unwrap_err()
         .unwrap();
 
-    let bank_account_data = banks_client
-        .get_account(bank_account_pda)
-        .await
-        .unwrap()
-        .unwrap()
-        .data;
-    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
-        &bank_account_data
-    ).unwrap();
-
-    assert_eq!(
-        bank_account_info.signatures.len(),
-        2usize
-    );
-
-    let sig_info_1 = bank_account_info.signatures.get(0usize).unwrap();
-    let sig_info_2 = bank_account_info.signatures.get(1usize).unwrap();
-
-    assert_eq!(
-        sig_info_1.is_ok,
-        false
-    );
-    assert_eq!(
-        sig_info_2.is_ok,
-        false
-    );
-
     assert_eq!(
-        sig_info_1.signature,
-        sig_info_2.signature
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::InsufficientLamportBalance as u32
+            )
+        )
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_lamports_using_ed25519_fail_failed_to_get_edd25519_ix() {
+async fn test_withdraw_spl_token_success() {
     let program_id = Pubkey::new_from_array([2; 32]);
     let mut pt = setup(&program_id);
 
-    let message_signer = Keypair::from_bytes(
-        &[
-            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
-            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
-            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
-        ]
-    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+    let operator = Keypair::new();
     pt.add_account(
-        message_signer.pubkey(),
+        operator.pubkey(),
         SolanaAccount {
             lamports: sol_to_lamports(100.0),
             owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
+            data: vec![],
             executable: false,
-            data: vec![]
+            rent_epoch: Epoch::default()
         }
     );
 
-    let to = Keypair::from_bytes(
-        &[
-            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
-            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
-            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
-            117,177,107,131,121,11,228,101,173,11,51,156
-        ]
-    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-    pt.add_account(
-        to.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(1.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
+    let mint_account = Pubkey::new_from_array([3;32]);
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &operator.pubkey(),
+        1000_00u64
     );
 
-    let (
-        mut banks_client,
-        payer,
-        recent_blockhash
-    ) = pt.start().await;
+    let operator_token_account = Pubkey::new_from_array([4; 32]);
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &operator_token_account,
+        &operator.pubkey(),
+        0_00u64
+    );
 
     let bank_account_pda = Pubkey::try_find_program_address(
         &[
             b"user_bank_account",
-            message_signer.pubkey().as_ref()
+            operator.pubkey().as_ref()
         ],
         &program_id
     ).unwrap().0;
+
+    setup_new_associated_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &bank_account_pda,
+        1000_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
     setup_new_bank_account(
         &mut banks_client,
-        &message_signer,
+        &operator,
         &program_id,
         recent_blockhash
     ).await;
 
-    let send_lamport_to_bank_account_ix = transfer_lamports(
-        &message_signer.pubkey(),
+    let bank_account_associated_token_account = Pubkey::try_find_program_address(
+        &[
+            bank_account_pda.as_ref(),
+            TOKEN_STANDARD_PROGRAM.as_ref(),
+            mint_account.as_ref()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+ 
+    let ix = create_withdraw_spl_tokens(
+        &operator.pubkey(),
         &bank_account_pda,
-        30 * LAMPORTS_PER_SOL
+        &bank_account_associated_token_account,
+        &mint_account,
+        &operator_token_account,
+        &TOKEN_STANDARD_PROGRAM,
+        None,
+        &program_id,
+        &100_00u64,
+        None
     );
 
     let tx = Transaction::new_signed_with_payer(
-        &[send_lamport_to_bank_account_ix],
+        &[ix],
         Some(&payer.pubkey()),
         &[
             &payer,
-            &message_signer
+            &operator
         ],
         recent_blockhash
     );
@@ -1276,117 +1639,126 @@ async fn test_withdraw_lamports_using_ed25519_fail_failed_to_get_edd25519_ix() {
         .await
         .unwrap();
 
-    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
-        &bank_account_pda,
-        &to.pubkey(),
-        &to.pubkey(),
-        &to.pubkey(),
-        &SYSTEM_PROGRAM_ID,
-        Some(&MEMO_PROGRAM_ID),
-        &program_id
-    );
-
-    let tx = Transaction::new_signed_with_payer(
-        &[withdraw_lamports_using_ed25519_ix],
-        Some(&payer.pubkey()),
-        &[
-            &payer,
-            &to
-        ],
-        recent_blockhash
-    );
+    let operator_token_account_data = banks_client
+        .get_account(operator_token_account)
+        .await.unwrap().unwrap().data;
+    let bank_account_associated_token_account_data = banks_client
+        .get_account(bank_account_associated_token_account)
+        .await.unwrap().unwrap().data;
 
-    let error = banks_client
-        .process_transaction(tx)
-        .await
-        .unwrap_err()
-        .unwrap();
+    let operator_token_account_info = TokenAccount::unpack(
+        operator_token_account_data.as_slice()
+    ).unwrap();
+    let bank_account_associated_token_account_info = TokenAccount::unpack(
+        bank_account_associated_token_account_data.as_slice()
+    ).unwrap();
 
     assert_eq!(
-        error,
-        TransactionError::InstructionError(
-            0,
-            InstructionError::Custom(
-                BankError::FailedToGetEd25519Instruction as u32
-            )
-        )
+        operator_token_account_info.amount,
+        100_00u64,
+        "Operator token balance mismatch."
+    );
+    assert_eq!(
+        bank_account_associated_token_account_info.amount,
+        900_00u64,
+        "Bank-account token balance mismatch."
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_lamports_using_ed25519_fail_invalid_ed25519_ix() {
+async fn test_withdraw_spl_token_2022_success() {
     let program_id = Pubkey::new_from_array([2; 32]);
     let mut pt = setup(&program_id);
 
-    let message_signer = Keypair::from_bytes(
-        &[
-            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
-            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
-            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
-        ]
-    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+    let operator = Keypair::new();
     pt.add_account(
-        message_signer.pubkey(),
+        operator.pubkey(),
         SolanaAccount {
             lamports: sol_to_lamports(100.0),
             owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
+            data: vec![],
             executable: false,
-            data: vec![]
+            rent_epoch: Epoch::default()
         }
     );
 
-    let to = Keypair::from_bytes(
-        &[
-            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
-            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
-            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
-            117,177,107,131,121,11,228,101,173,11,51,156
-        ]
-    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-    pt.add_account(
-        to.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(1.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
+    // base-layout (extension-less) Token-2022 mint and accounts
+    let mint_account = Pubkey::new_from_array([3;32]);
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_2022_PROGRAM,
+        &mint_account,
+        &operator.pubkey(),
+        1000_00u64
     );
 
-    let (
-        mut banks_client,
-        payer,
-        recent_blockhash
-    ) = pt.start().await;
+    let operator_token_account = Pubkey::new_from_array([4; 32]);
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_2022_PROGRAM,
+        &mint_account,
+        &operator_token_account,
+        &operator.pubkey(),
+        0_00u64
+    );
 
     let bank_account_pda = Pubkey::try_find_program_address(
         &[
             b"user_bank_account",
-            message_signer.pubkey().as_ref()
+            operator.pubkey().as_ref()
         ],
         &program_id
     ).unwrap().0;
+
+    setup_new_associated_token_account(
+        &mut pt,
+        &TOKEN_2022_PROGRAM,
+        &mint_account,
+        &bank_account_pda,
+        1000_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
     setup_new_bank_account(
         &mut banks_client,
-        &message_signer,
+        &operator,
         &program_id,
         recent_blockhash
     ).await;
 
-    let send_lamport_to_bank_account_ix = transfer_lamports(
-        &message_signer.pubkey(),
+    let bank_account_associated_token_account = Pubkey::try_find_program_address(
+        &[
+            bank_account_pda.as_ref(),
+            TOKEN_2022_PROGRAM.as_ref(),
+            mint_account.as_ref()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+ 
+    let ix = create_withdraw_spl_tokens(
+        &operator.pubkey(),
         &bank_account_pda,
-        30 * LAMPORTS_PER_SOL
+        &bank_account_associated_token_account,
+        &mint_account,
+        &operator_token_account,
+        &TOKEN_2022_PROGRAM,
+        None,
+        &program_id,
+        &100_00u64,
+        None
     );
 
     let tx = Transaction::new_signed_with_payer(
-        &[send_lamport_to_bank_account_ix],
+        &[ix],
         Some(&payer.pubkey()),
         &[
             &payer,
-            &message_signer
+            &operator
         ],
         recent_blockhash
     );
@@ -1396,169 +1768,299 @@ async fn test_withdraw_lamports_using_ed25519_fail_invalid_ed25519_ix() {
         .await
         .unwrap();
 
+    let operator_token_account_data = banks_client
+        .get_account(operator_token_account)
+        .await.unwrap().unwrap().data;
+    let bank_account_associated_token_account_data = banks_client
+        .get_account(bank_account_associated_token_account)
+        .await.unwrap().unwrap().data;
 
-    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
-        &bank_account_pda,
-        &to.pubkey(),
-        &to.pubkey(),
-        &to.pubkey(),
-        &SYSTEM_PROGRAM_ID,
-        None,
-        &program_id
-    );
+    let operator_token_account_info = TokenAccount::unpack(
+        operator_token_account_data.as_slice()
+    ).unwrap();
+    let bank_account_associated_token_account_info = TokenAccount::unpack(
+        bank_account_associated_token_account_data.as_slice()
+    ).unwrap();
 
-    let tx = Transaction::new_signed_with_payer(
-        &[
-            transfer_lamports(
-                &payer.pubkey(),
-                &to.pubkey(),
-                1_000_000
-            ),
-            withdraw_lamports_using_ed25519_ix
-        ],
-        Some(&payer.pubkey()),
-        &[
-            &payer,
-            &to
-        ],
-        recent_blockhash
+    assert_eq!(
+        operator_token_account_info.amount,
+        100_00u64,
+        "Operator token balance mismatch."
     );
-
-    let error = banks_client
-        .process_transaction(tx)
-        .await
-        .unwrap_err()
-        .unwrap();
-
     assert_eq!(
-        error,
-        TransactionError::InstructionError(
-            1,
-            InstructionError::Custom(
-                BankError::InvalidEd25519SignatureVerificationInstruction as u32
-            )
-        )
+        bank_account_associated_token_account_info.amount,
+        900_00u64,
+        "Bank-account token balance mismatch."
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_lamports_using_ed25519_fail_invalid_message_v1() {
+async fn test_sweep_and_close_bank_account_full_token_2022_success() {
     let program_id = Pubkey::new_from_array([2; 32]);
     let mut pt = setup(&program_id);
 
-    let message_signer = Keypair::from_bytes(
-        &[
-            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
-            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
-            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
-        ]
-    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+    let operator = Keypair::new();
     pt.add_account(
-        message_signer.pubkey(),
+        operator.pubkey(),
         SolanaAccount {
             lamports: sol_to_lamports(100.0),
             owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
+            data: vec![],
             executable: false,
-            data: vec![]
+            rent_epoch: Epoch::default()
         }
     );
 
-    let to = Keypair::from_bytes(
-        &[
-            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
-            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
-            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
-            117,177,107,131,121,11,228,101,173,11,51,156
-        ]
-    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-    pt.add_account(
-        to.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(1.0),
-            owner: Pubkey::default(),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            data: vec![]
-        }
+    // base-layout (extension-less) Token-2022 mint and accounts
+    let mint_account = Pubkey::new_from_array([3;32]);
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_2022_PROGRAM,
+        &mint_account,
+        &operator.pubkey(),
+        1000_00u64
     );
 
-    let (
-        mut banks_client,
-        payer,
-        recent_blockhash
-    ) = pt.start().await;
+    let operator_token_account = Pubkey::new_from_array([4; 32]);
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_2022_PROGRAM,
+        &mint_account,
+        &operator_token_account,
+        &operator.pubkey(),
+        0_00u64
+    );
 
     let bank_account_pda = Pubkey::try_find_program_address(
         &[
             b"user_bank_account",
-            message_signer.pubkey().as_ref()
+            operator.pubkey().as_ref()
         ],
         &program_id
     ).unwrap().0;
+
+    setup_new_associated_token_account(
+        &mut pt,
+        &TOKEN_2022_PROGRAM,
+        &mint_account,
+        &bank_account_pda,
+        1000_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
     setup_new_bank_account(
         &mut banks_client,
-        &message_signer,
+        &operator,
         &program_id,
         recent_blockhash
     ).await;
 
-    let send_lamport_to_bank_account_ix = transfer_lamports(
-        &message_signer.pubkey(),
+    let bank_account_associated_token_account = Pubkey::try_find_program_address(
+        &[
+            bank_account_pda.as_ref(),
+            TOKEN_2022_PROGRAM.as_ref(),
+            mint_account.as_ref()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+
+    let effective_withdrawable = get_effective_withdrawable(
+        &mut banks_client,
+        &payer,
         &bank_account_pda,
-        30 * LAMPORTS_PER_SOL
+        Some(&bank_account_associated_token_account),
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        effective_withdrawable,
+        EffectiveWithdrawable {
+            amount: 1000_00u64,
+            limit: WithdrawableLimit::TokenBalance
+        },
+        "Effective withdrawable mismatch."
     );
 
     let tx = Transaction::new_signed_with_payer(
-        &[send_lamport_to_bank_account_ix],
+        &[
+            create_sweep_multiple_atas(
+                &operator.pubkey(),
+                &bank_account_pda,
+                &TOKEN_2022_PROGRAM,
+                &[(mint_account, bank_account_associated_token_account, operator_token_account)],
+                &program_id,
+                None
+            )
+        ],
         Some(&payer.pubkey()),
         &[
             &payer,
-            &message_signer
+            &operator
         ],
         recent_blockhash
     );
 
-    banks_client
-        .process_transaction(tx)
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
         .await
         .unwrap();
+    assert!(result.result.is_ok());
 
-
-    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,1500000000";
-
-    let ed25519_signature_verification_instruction = Instruction {
-        program_id: solana_sdk::ed25519_program::ID,
-        accounts: vec![],
-        data: vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,55,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,157,
-            169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,157,75,27,216,166,121,75,79,42,115,178,79,191,184,
-            133,218,64,70,155,201,156,240,157,224,11,209,106,220,185,37,84,130,33,161,138,101,216,25,23,40,166,16,52,82,
-            165,118,68,255,215,252,57,130,222,79,29,229,26,136,133,250,38,226,202,2,52,102,118,118,89,113,107,99,71,53,
-            105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,
-            80,90,44,49,53,48,48,48,48,48,48,48,48
-        ]
-    };
-
-    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
-        &bank_account_pda,
-        &to.pubkey(),
-        &to.pubkey(),
-        &to.pubkey(),
-        &SYSTEM_PROGRAM_ID,
-        Some(&MEMO_PROGRAM_ID),
-        &program_id
-    );
+    let return_data = result
+        .metadata
+        .unwrap()
+        .return_data
+        .unwrap();
+    let events = Vec::<WithdrawEvent>::try_from_slice(&return_data.data).unwrap();
+    assert_eq!(events.len(), 1, "Events count mismatch.");
+    assert_eq!(events[0].kind, WithdrawKind::SweepSplTokens, "Withdraw kind mismatch.");
+    assert_eq!(events[0].amount, 1000_00u64, "Withdrawn amount mismatch.");
+    assert_eq!(events[0].to, operator_token_account, "Recepient mismatch.");
 
     let tx = Transaction::new_signed_with_payer(
         &[
-            ed25519_signature_verification_instruction,
-            withdraw_lamports_using_ed25519_ix
+            create_close_bank_account_full(
+                &operator.pubkey(),
+                &bank_account_pda,
+                &TOKEN_2022_PROGRAM,
+                &[(bank_account_associated_token_account, None)],
+                &program_id,
+                None
+            )
         ],
         Some(&payer.pubkey()),
         &[
             &payer,
-            &to
+            &operator
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let operator_token_account_data = banks_client
+        .get_account(operator_token_account)
+        .await.unwrap().unwrap().data;
+    let operator_token_account_info = TokenAccount::unpack(
+        operator_token_account_data.as_slice()
+    ).unwrap();
+    assert_eq!(
+        operator_token_account_info.amount,
+        1000_00u64,
+        "Operator token balance mismatch."
+    );
+
+    assert!(
+        banks_client.get_account(bank_account_associated_token_account).await.unwrap().is_none(),
+        "Bank-account token-account must be closed."
+    );
+    assert!(
+        banks_client.get_account(bank_account_pda).await.unwrap().is_none(),
+        "Bank-account must be closed."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_token_fail_invalid_seeds() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let operator = Keypair::new();
+    pt.add_account(
+        operator.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false,
+            rent_epoch: Epoch::default()
+        }
+    );
+
+    let mint_account = Pubkey::new_from_array([3;32]);
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &operator.pubkey(),
+        1000_00u64
+    );
+
+    let operator_token_account = Pubkey::new_from_array([4; 32]);
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &operator_token_account,
+        &operator.pubkey(),
+        0_00u64
+    );
+
+    let bank_account_pda = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            operator.pubkey().as_ref()
+        ],
+        &program_id
+    ).unwrap().0;
+
+    setup_new_associated_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &bank_account_pda,
+        1000_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    setup_new_bank_account(
+        &mut banks_client,
+        &operator,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let bank_account_associated_token_account = Pubkey::try_find_program_address(
+        &[
+            Pubkey::default().as_ref(),
+            TOKEN_STANDARD_PROGRAM.as_ref(),
+            mint_account.as_ref()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+ 
+    let ix = create_withdraw_spl_tokens(
+        &operator.pubkey(),
+        &bank_account_pda,
+        &bank_account_associated_token_account,
+        &mint_account,
+        &operator_token_account,
+        &TOKEN_STANDARD_PROGRAM,
+        None,
+        &program_id,
+        &100_00u64,
+        None
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &operator
         ],
         recent_blockhash
     );
@@ -1572,16 +2074,131 @@ async fn test_withdraw_lamports_using_ed25519_fail_invalid_message_v1() {
     assert_eq!(
         error,
         TransactionError::InstructionError(
-            1,
+            0,
+            InstructionError::InvalidSeeds
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_token_fail_uninitialized_mint() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let operator = Keypair::new();
+    pt.add_account(
+        operator.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false,
+            rent_epoch: Epoch::default()
+        }
+    );
+
+    let mint_account = Pubkey::new_from_array([3;32]);
+    pt.add_account(
+        mint_account,
+        SolanaAccount {
+            lamports: Rent::default().minimum_balance(Mint::LEN),
+            owner: TOKEN_STANDARD_PROGRAM,
+            data: vec![0u8; Mint::LEN],
+            executable: false,
+            rent_epoch: Epoch::default()
+        }
+    );
+
+    let operator_token_account = Pubkey::new_from_array([4; 32]);
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &operator_token_account,
+        &operator.pubkey(),
+        0_00u64
+    );
+
+    let bank_account_pda = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            operator.pubkey().as_ref()
+        ],
+        &program_id
+    ).unwrap().0;
+
+    setup_new_associated_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &bank_account_pda,
+        1000_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    setup_new_bank_account(
+        &mut banks_client,
+        &operator,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let bank_account_associated_token_account = Pubkey::try_find_program_address(
+        &[
+            bank_account_pda.as_ref(),
+            TOKEN_STANDARD_PROGRAM.as_ref(),
+            mint_account.as_ref()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+ 
+    let ix = create_withdraw_spl_tokens(
+        &operator.pubkey(),
+        &bank_account_pda,
+        &bank_account_associated_token_account,
+        &mint_account,
+        &operator_token_account,
+        &TOKEN_STANDARD_PROGRAM,
+        None,
+        &program_id,
+        &100_00u64,
+        None
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &operator
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
             InstructionError::Custom(
-                BankError::MessageV1ValidationFailed as u32
+                BankError::InvalidMintAccount as u32
             )
         )
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_lamports_using_ed25519_fail_invalid_to_account() {
+async fn test_withdraw_lamports_using_ed25519_success_1() {
     let program_id = Pubkey::new_from_array([2; 32]);
     let mut pt = setup(&program_id);
 
@@ -1622,18 +2239,6 @@ async fn test_withdraw_lamports_using_ed25519_fail_invalid_to_account() {
         }
     );
 
-    let fake_to = Keypair::new();
-    pt.add_account(
-        fake_to.pubkey(),
-        SolanaAccount {
-            lamports: Rent::default().minimum_balance(0),
-            rent_epoch: Epoch::default(),
-            executable: false,
-            owner: Pubkey::default(),
-            data: vec![]
-        }
-    );
-
     let (
         mut banks_client,
         payer,
@@ -1676,32 +2281,35 @@ async fn test_withdraw_lamports_using_ed25519_fail_invalid_to_account() {
         .unwrap();
 
 
-    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,50000000000,PooriaGG 😃";
+    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,1500000000,PooriaGG 😃,4102444800";
 
     let ed25519_signature_verification_instruction = Instruction {
         program_id: solana_sdk::ed25519_program::ID,
         accounts: vec![],
         data: vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,70,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,
-            191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,207,162,88,16,25,188,185,93,161,90,
-            6,62,215,17,139,27,4,111,147,95,173,85,151,17,243,129,217,239,167,44,50,228,144,63,81,99,163,35,107,
-            189,216,217,184,81,239,40,100,240,183,180,199,149,19,91,145,134,52,42,202,19,33,245,165,11,52,102,118,
-            118,89,113,107,99,71,53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,
-            71,49,68,74,87,55,68,106,68,80,90,44,53,48,48,48,48,48,48,48,48,48,48,44,80,111,111,114,105,97,71,71,32,
-            240,159,152,131
+            1,0,48,0,255,255,16,0,255,255,112,0,80,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,157,169,
+            169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,225,147,173,153,246,112,90,93,132,1,234,75,27,107,161,20,254,200,
+            1,161,23,202,3,118,129,56,26,112,250,107,60,40,16,221,209,145,244,228,194,134,149,32,97,239,83,19,212,51,27,63,102,
+            202,224,206,132,30,152,189,139,162,195,97,166,6,52,102,118,118,89,113,107,99,71,53,105,122,113,86,78,117,77,75,121,82,
+            52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,80,90,44,49,53,48,48,48,48,48,48,
+            48,48,44,80,111,111,114,105,97,71,71,32,240,159,152,131,44,52,49,48,50,52,52,52,56,48,48
         ]
     };
 
     let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
         &bank_account_pda,
         &to.pubkey(),
-        &fake_to.pubkey(),
+        &to.pubkey(),
         &to.pubkey(),
         &SYSTEM_PROGRAM_ID,
         Some(&MEMO_PROGRAM_ID),
+        None,
+        None,
         &program_id
     );
 
+    let ed = ed25519_signature_verification_instruction.clone();
+
     let tx = Transaction::new_signed_with_payer(
         &[
             ed25519_signature_verification_instruction,
@@ -1710,31 +2318,79 @@ async fn test_withdraw_lamports_using_ed25519_fail_invalid_to_account() {
         Some(&payer.pubkey()),
         &[
             &payer,
-            &to,
-            &fake_to
+            &to
         ],
         recent_blockhash
     );
 
-    let error = banks_client
+    banks_client
         .process_transaction(tx)
         .await
-        .unwrap_err()
         .unwrap();
 
-    assert_eq!(
-        error,
-        TransactionError::InstructionError(
-            1,
-            InstructionError::Custom(
-                BankError::InvalidToPubkey as u32
-            )
-        )
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
+        &bank_account_data
+    ).unwrap();
+
+    let signature_info = bank_account_info
+        .signatures
+        .get(0usize)
+        .unwrap();
+
+    let ed25519_data = ed.data;
+
+    let ed25519_msg = ed25519_data.get(112..).unwrap().to_vec();
+    assert_eq!(
+        signature_info.message,
+        ed25519_msg,
+        "Message mismatch."
+    );
+
+    let ed25519_signature: [u8; 64] = ed25519_data.get(48..112).unwrap().try_into().unwrap();
+    assert_eq!(
+        signature_info.signature,
+        ed25519_signature,
+        "Signature mismatch."
+    );
+
+    assert!(signature_info.is_ok);
+    assert_eq!(
+        signature_info.fail_reason,
+        SignatureFailReason::None,
+        "Fail reason mismatch."
+    );
+
+    let bank_account_balance = banks_client
+        .get_balance(bank_account_pda)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        bank_account_balance,
+        28_503_138_960u64,
+        "Bank-Account balance mismatch."
+    );
+    
+    let to_account_balance = banks_client
+        .get_balance(to.pubkey())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        to_account_balance,
+        2_498_858_560u64,
+        "To-Account balance mismatch."
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_spl_tokens_using_ed25519_success_1() {
+async fn test_withdraw_lamports_using_ed25519_success_2() {
     let program_id = Pubkey::new_from_array([2; 32]);
     let mut pt = setup(&program_id);
 
@@ -1745,6 +2401,16 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_1() {
             8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
         ]
     ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
 
     let to = Keypair::from_bytes(
         &[
@@ -1754,140 +2420,91 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_1() {
             117,177,107,131,121,11,228,101,173,11,51,156
         ]
     ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-
-    let (
-        bank_account,
-        bump
-    ) = Pubkey::try_find_program_address(
-        &[
-            b"user_bank_account",
-            message_signer.pubkey().to_bytes().as_slice()
-        ],
-        &program_id
-    ).unwrap();
-
-    pt.add_account(
-        message_signer.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(1.0),
-            rent_epoch: Epoch::default(),
-            owner: Pubkey::default(),
-            data: vec![],
-            executable: false
-        }
-    );
     pt.add_account(
         to.pubkey(),
         SolanaAccount {
-            lamports: sol_to_lamports(2.0),
-            rent_epoch: Epoch::default(),
-            owner: Pubkey::default(),
-            data: vec![],
-            executable: false
-        }
-    );
-    pt.add_account(
-        bank_account,
-        SolanaAccount {
-            owner: program_id,
             lamports: sol_to_lamports(1.0),
+            owner: Pubkey::default(),
             rent_epoch: Epoch::default(),
-            data: UserBankAccount {
-                discriminator: UserBankAccount::get_bank_account_discriminator(),
-                bump,
-                account_created_at: Clock::default().unix_timestamp,
-                authority: message_signer.pubkey(),
-                signatures: vec![]
-            }.try_to_vec().unwrap(),
-            executable: false
+            executable: false,
+            data: vec![]
         }
     );
 
-    let mint_account = Pubkey::new_from_array([3; 32]);
-    let signer_token_account = Pubkey::new_from_array([4; 32]);
-    let to_token_account = Pubkey::new_from_array([5; 32]);
-    let bank_account_token_account = Pubkey::try_find_program_address(
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = Pubkey::try_find_program_address(
         &[
-            bank_account.to_bytes().as_slice(),
-            TOKEN_STANDARD_PROGRAM.to_bytes().as_slice(),
-            mint_account.to_bytes().as_slice()
+            b"user_bank_account",
+            message_signer.pubkey().as_ref()
         ],
-        &ASSOCIATED_TOKEN_PROGRAM
+        &program_id
     ).unwrap().0;
-    
-    setup_new_mint_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &Keypair::new().pubkey(),
-        1000_00u64
-    );
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &signer_token_account,
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let send_lamport_to_bank_account_ix = transfer_lamports(
         &message_signer.pubkey(),
-        500_00u64
-    );
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &to_token_account,
-        &to.pubkey(),
-        300_00u64
-    );
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &bank_account_token_account,
-        &bank_account,
-        200_00u64
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL
     );
 
-    let (
-        mut banks_client,
-        payer,
+    let tx = Transaction::new_signed_with_payer(
+        &[send_lamport_to_bank_account_ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &message_signer
+        ],
         recent_blockhash
-    ) = pt.start().await;
-
-    let _message: String = String::from(
-        "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,5000,PooriaGG 🤩"
     );
 
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+
+    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,50000000000,PooriaGG 😃,4102444800";
+
     let ed25519_signature_verification_instruction = Instruction {
         program_id: solana_sdk::ed25519_program::ID,
         accounts: vec![],
         data: vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,107,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,
-            157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,67,142,135,154,113,246,121,210,237,35,38,2,
-            28,232,247,238,246,74,106,20,25,45,244,168,65,181,104,172,53,18,23,88,28,42,212,92,183,167,49,168,108,236,
-            141,101,220,4,104,57,183,12,100,159,30,80,62,45,64,129,3,150,168,12,135,5,52,102,118,118,89,113,107,99,71,
-            53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,
-            68,80,90,44,67,107,116,82,117,81,50,109,116,116,103,82,71,107,88,74,116,121,107,115,100,75,72,106,85,100,99,
-            50,67,52,84,103,68,122,121,66,57,56,111,69,122,121,56,44,53,48,48,48,44,80,111,111,114,105,97,71,71,32,240,159,164,169
+            1,0,48,0,255,255,16,0,255,255,112,0,81,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,
+            191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,83,122,28,89,43,243,38,174,126,56,56,89,
+            121,140,71,151,15,255,40,208,227,91,190,213,106,191,136,215,119,72,81,197,97,66,69,103,14,236,133,185,245,177,
+            85,109,232,186,116,59,39,220,106,109,217,10,140,60,87,128,198,76,174,50,34,10,52,102,118,118,89,113,107,99,
+            71,53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,
+            55,68,106,68,80,90,44,53,48,48,48,48,48,48,48,48,48,48,44,80,111,111,114,105,97,71,71,32,240,159,
+            152,131,44,52,49,48,50,52,52,52,56,48,48
         ]
     };
 
-    let withdraw_spl_tokens_using_ed25519 = create_withdraw_spl_tokens_using_ed25519_signature(
-        &mint_account,
-        &bank_account,
-        &bank_account_token_account,
+    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &to.pubkey(),
         &to.pubkey(),
         &to.pubkey(),
-        &to_token_account,
-        &TOKEN_STANDARD_PROGRAM,
         &SYSTEM_PROGRAM_ID,
         Some(&MEMO_PROGRAM_ID),
+        None,
+        None,
         &program_id
     );
 
     let tx = Transaction::new_signed_with_payer(
         &[
-            ed25519_signature_verification_instruction.clone(),
-            withdraw_spl_tokens_using_ed25519
+            ed25519_signature_verification_instruction,
+            withdraw_lamports_using_ed25519_ix
         ],
         Some(&payer.pubkey()),
         &[
@@ -1903,63 +2520,34 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_1() {
         .unwrap();
 
     let bank_account_data = banks_client
-        .get_account(bank_account)
+        .get_account(bank_account_pda)
         .await
         .unwrap()
         .unwrap()
         .data;
-    let bank_account_info = UserBankAccount::try_from_slice(
+    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
         &bank_account_data
     ).unwrap();
 
-    let expected_signature: [u8; 64] = ed25519_signature_verification_instruction
-        .data
-        .get(48..112)
-        .unwrap()
-        .try_into()
+    let signature_info = bank_account_info
+        .signatures
+        .get(0usize)
         .unwrap();
 
     assert_eq!(
-        expected_signature,
-        bank_account_info
-            .signatures
-            .get(0)
-            .unwrap()
-            .signature,
-        "Signature mismatch!"
-    );
-
-    assert!(bank_account_info.signatures[0].is_ok);
-
-    let to_token_account_data = banks_client
-        .get_account(to_token_account)
-        .await
-        .unwrap()
-        .unwrap()
-        .data;
-    let to_token_account_info = TokenAccount::unpack(&to_token_account_data).unwrap();
-
-    let bank_account_tokne_account_data = banks_client
-        .get_account(bank_account_token_account)
-        .await
-        .unwrap()
-        .unwrap()
-        .data;
-    let bank_account_token_account_info = TokenAccount::unpack(&bank_account_tokne_account_data).unwrap();
-
-    assert_eq!(
-        to_token_account_info.amount,
-        350_00u64
+        signature_info.is_ok,
+        false,
+        "Flag is invalid."
     );
-
     assert_eq!(
-        bank_account_token_account_info.amount,
-        150_00u64
+        signature_info.fail_reason,
+        SignatureFailReason::InsufficientLamports,
+        "Fail reason mismatch."
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_spl_tokens_using_ed25519_success_2() {
+async fn test_withdraw_lamports_using_ed25519_fail_failed_signature_replayed() {
     let program_id = Pubkey::new_from_array([2; 32]);
     let mut pt = setup(&program_id);
 
@@ -1970,8 +2558,18 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_2() {
             8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
         ]
     ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
-
-    let to = Keypair::from_bytes(
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let to = Keypair::from_bytes(
         &[
             237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
             164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
@@ -1979,140 +2577,91 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_2() {
             117,177,107,131,121,11,228,101,173,11,51,156
         ]
     ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-
-    let (
-        bank_account,
-        bump
-    ) = Pubkey::try_find_program_address(
-        &[
-            b"user_bank_account",
-            message_signer.pubkey().to_bytes().as_slice()
-        ],
-        &program_id
-    ).unwrap();
-
-    pt.add_account(
-        message_signer.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(1.0),
-            rent_epoch: Epoch::default(),
-            owner: Pubkey::default(),
-            data: vec![],
-            executable: false
-        }
-    );
     pt.add_account(
         to.pubkey(),
         SolanaAccount {
-            lamports: sol_to_lamports(2.0),
-            rent_epoch: Epoch::default(),
-            owner: Pubkey::default(),
-            data: vec![],
-            executable: false
-        }
-    );
-    pt.add_account(
-        bank_account,
-        SolanaAccount {
-            owner: program_id,
             lamports: sol_to_lamports(1.0),
+            owner: Pubkey::default(),
             rent_epoch: Epoch::default(),
-            data: UserBankAccount {
-                discriminator: UserBankAccount::get_bank_account_discriminator(),
-                bump,
-                account_created_at: Clock::default().unix_timestamp,
-                authority: message_signer.pubkey(),
-                signatures: vec![]
-            }.try_to_vec().unwrap(),
-            executable: false
+            executable: false,
+            data: vec![]
         }
     );
 
-    let mint_account = Pubkey::new_from_array([3; 32]);
-    let signer_token_account = Pubkey::new_from_array([4; 32]);
-    let to_token_account = Pubkey::new_from_array([5; 32]);
-    let bank_account_token_account = Pubkey::try_find_program_address(
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = Pubkey::try_find_program_address(
         &[
-            bank_account.to_bytes().as_slice(),
-            TOKEN_STANDARD_PROGRAM.to_bytes().as_slice(),
-            mint_account.to_bytes().as_slice()
+            b"user_bank_account",
+            message_signer.pubkey().as_ref()
         ],
-        &ASSOCIATED_TOKEN_PROGRAM
+        &program_id
     ).unwrap().0;
-    
-    setup_new_mint_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &Keypair::new().pubkey(),
-        1000_00u64
-    );
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &signer_token_account,
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let send_lamport_to_bank_account_ix = transfer_lamports(
         &message_signer.pubkey(),
-        500_00u64
-    );
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &to_token_account,
-        &to.pubkey(),
-        300_00u64
-    );
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &bank_account_token_account,
-        &bank_account,
-        200_00u64
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL
     );
 
-    let (
-        mut banks_client,
-        payer,
+    let tx = Transaction::new_signed_with_payer(
+        &[send_lamport_to_bank_account_ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &message_signer
+        ],
         recent_blockhash
-    ) = pt.start().await;
-
-    let _message: String = String::from(
-        "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,50000,PooriaGG 🤩"
     );
 
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+
+    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,50000000000,PooriaGG 😃,4102444800";
+
     let ed25519_signature_verification_instruction = Instruction {
         program_id: solana_sdk::ed25519_program::ID,
         accounts: vec![],
         data: vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,108,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,157,169,169,
-            85,113,83,0,79,147,213,225,127,24,199,48,221,200,55,78,28,211,84,230,134,173,195,114,9,205,40,231,223,7,61,8,212,64,
-            156,21,81,42,211,136,180,255,112,43,54,224,245,21,81,14,97,192,71,63,231,247,247,138,35,155,250,190,29,223,228,80,
-            108,120,155,234,213,192,204,191,136,173,124,3,52,102,118,118,89,113,107,99,71,53,105,122,113,86,78,117,77,75,121,82,
-            52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,80,90,44,67,107,116,82,117,81,50,109,116,116,
-            103,82,71,107,88,74,116,121,107,115,100,75,72,106,85,100,99,50,67,52,84,103,68,122,121,66,57,56,111,69,122,121,56,44,
-            53,48,48,48,48,44,80,111,111,114,105,97,71,71,32,240,159,164,169
+            1,0,48,0,255,255,16,0,255,255,112,0,81,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,
+            191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,83,122,28,89,43,243,38,174,126,56,56,89,
+            121,140,71,151,15,255,40,208,227,91,190,213,106,191,136,215,119,72,81,197,97,66,69,103,14,236,133,185,245,177,
+            85,109,232,186,116,59,39,220,106,109,217,10,140,60,87,128,198,76,174,50,34,10,52,102,118,118,89,113,107,99,
+            71,53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,
+            55,68,106,68,80,90,44,53,48,48,48,48,48,48,48,48,48,48,44,80,111,111,114,105,97,71,71,32,240,159,
+            152,131,44,52,49,48,50,52,52,52,56,48,48
         ]
     };
 
-    let withdraw_spl_tokens_using_ed25519 = create_withdraw_spl_tokens_using_ed25519_signature(
-        &mint_account,
-        &bank_account,
-        &bank_account_token_account,
+    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &to.pubkey(),
         &to.pubkey(),
         &to.pubkey(),
-        &to_token_account,
-        &TOKEN_STANDARD_PROGRAM,
         &SYSTEM_PROGRAM_ID,
         Some(&MEMO_PROGRAM_ID),
+        None,
+        None,
         &program_id
     );
 
     let tx = Transaction::new_signed_with_payer(
         &[
             ed25519_signature_verification_instruction.clone(),
-            withdraw_spl_tokens_using_ed25519
+            withdraw_lamports_using_ed25519_ix.clone()
         ],
         Some(&payer.pubkey()),
         &[
@@ -2127,54 +2676,63 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_2() {
         .await
         .unwrap();
 
-    let bank_account_data = banks_client
-        .get_account(bank_account)
+    // the failed attempt is stored, submitting the same signature again is a replay
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
         .await
-        .unwrap()
-        .unwrap()
-        .data;
-    let bank_account_info = UserBankAccount::try_from_slice(
-        &bank_account_data
-    ).unwrap();
-
-    let expected_signature: [u8; 64] = ed25519_signature_verification_instruction
-        .data
-        .get(48..112)
-        .unwrap()
-        .try_into()
         .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ed25519_signature_verification_instruction,
+            withdraw_lamports_using_ed25519_ix
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
 
-    assert!(!bank_account_info.signatures[0].is_ok);
-
-    let to_token_account_data = banks_client
-        .get_account(to_token_account)
+    let error = banks_client
+        .process_transaction(tx)
         .await
-        .unwrap()
-        .unwrap()
-        .data;
-    let to_token_account_info = TokenAccount::unpack(&to_token_account_data).unwrap();
+        .unwrap_err()
+        .unwrap();
 
-    let bank_account_tokne_account_data = banks_client
-        .get_account(bank_account_token_account)
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignatureAlreadyUsed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
         .await
         .unwrap()
         .unwrap()
         .data;
-    let bank_account_token_account_info = TokenAccount::unpack(&bank_account_tokne_account_data).unwrap();
+    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
+        &bank_account_data
+    ).unwrap();
 
     assert_eq!(
-        to_token_account_info.amount,
-        300_00u64
+        bank_account_info.signatures.len(),
+        1usize
     );
-
     assert_eq!(
-        bank_account_token_account_info.amount,
-        200_00u64
+        bank_account_info.signatures.get(0usize).unwrap().is_ok,
+        false
     );
 }
 
 #[tokio::test]
-async fn test_withdraw_spl_tokens_using_ed25519_success_3() {
+async fn test_withdraw_lamports_using_ed25519_fail_failed_to_get_edd25519_ix() {
     let program_id = Pubkey::new_from_array([2; 32]);
     let mut pt = setup(&program_id);
 
@@ -2185,6 +2743,16 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_3() {
             8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
         ]
     ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
 
     let to = Keypair::from_bytes(
         &[
@@ -2194,97 +2762,11275 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_3() {
             117,177,107,131,121,11,228,101,173,11,51,156
         ]
     ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
-
-    let (
-        bank_account,
-        bump
-    ) = Pubkey::try_find_program_address(
-        &[
-            b"user_bank_account",
-            message_signer.pubkey().to_bytes().as_slice()
-        ],
-        &program_id
-    ).unwrap();
-
     pt.add_account(
-        message_signer.pubkey(),
+        to.pubkey(),
         SolanaAccount {
             lamports: sol_to_lamports(1.0),
-            rent_epoch: Epoch::default(),
             owner: Pubkey::default(),
-            data: vec![],
-            executable: false
-        }
-    );
-    pt.add_account(
-        to.pubkey(),
-        SolanaAccount {
-            lamports: sol_to_lamports(2.0),
             rent_epoch: Epoch::default(),
-            owner: Pubkey::default(),
-            data: vec![],
-            executable: false
+            executable: false,
+            data: vec![]
         }
     );
-    pt.add_account(
-        bank_account,
-        SolanaAccount {
-            owner: program_id,
-            lamports: sol_to_lamports(1.0),
-            rent_epoch: Epoch::default(),
-            data: UserBankAccount {
-                discriminator: UserBankAccount::get_bank_account_discriminator(),
-                bump,
-                account_created_at: Clock::default().unix_timestamp,
-                authority: message_signer.pubkey(),
-                signatures: vec![]
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.pubkey().as_ref()
+        ],
+        &program_id
+    ).unwrap().0;
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let send_lamport_to_bank_account_ix = transfer_lamports(
+        &message_signer.pubkey(),
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[send_lamport_to_bank_account_ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &message_signer
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &to.pubkey(),
+        &to.pubkey(),
+        &to.pubkey(),
+        &SYSTEM_PROGRAM_ID,
+        Some(&MEMO_PROGRAM_ID),
+        None,
+        None,
+        &program_id
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_lamports_using_ed25519_ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::FailedToGetEd25519Instruction as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_invalid_ed25519_ix() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::from_bytes(
+        &[
+            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
+            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
+            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
+        ]
+    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let to = Keypair::from_bytes(
+        &[
+            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
+            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
+            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
+            117,177,107,131,121,11,228,101,173,11,51,156
+        ]
+    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
+    pt.add_account(
+        to.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.pubkey().as_ref()
+        ],
+        &program_id
+    ).unwrap().0;
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let send_lamport_to_bank_account_ix = transfer_lamports(
+        &message_signer.pubkey(),
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[send_lamport_to_bank_account_ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &message_signer
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+
+    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &to.pubkey(),
+        &to.pubkey(),
+        &to.pubkey(),
+        &SYSTEM_PROGRAM_ID,
+        None,
+        None,
+        None,
+        &program_id
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            transfer_lamports(
+                &payer.pubkey(),
+                &to.pubkey(),
+                1_000_000
+            ),
+            withdraw_lamports_using_ed25519_ix
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // no ed25519 instruction precedes the withdrawal
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::FailedToGetEd25519Instruction as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_invalid_message_v1() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::from_bytes(
+        &[
+            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
+            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
+            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
+        ]
+    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let to = Keypair::from_bytes(
+        &[
+            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
+            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
+            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
+            117,177,107,131,121,11,228,101,173,11,51,156
+        ]
+    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
+    pt.add_account(
+        to.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.pubkey().as_ref()
+        ],
+        &program_id
+    ).unwrap().0;
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let send_lamport_to_bank_account_ix = transfer_lamports(
+        &message_signer.pubkey(),
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[send_lamport_to_bank_account_ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &message_signer
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+
+    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,1500000000";
+
+    let ed25519_signature_verification_instruction = Instruction {
+        program_id: solana_sdk::ed25519_program::ID,
+        accounts: vec![],
+        data: vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,55,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,157,
+            169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,157,75,27,216,166,121,75,79,42,115,178,79,191,184,
+            133,218,64,70,155,201,156,240,157,224,11,209,106,220,185,37,84,130,33,161,138,101,216,25,23,40,166,16,52,82,
+            165,118,68,255,215,252,57,130,222,79,29,229,26,136,133,250,38,226,202,2,52,102,118,118,89,113,107,99,71,53,
+            105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,
+            80,90,44,49,53,48,48,48,48,48,48,48,48
+        ]
+    };
+
+    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &to.pubkey(),
+        &to.pubkey(),
+        &to.pubkey(),
+        &SYSTEM_PROGRAM_ID,
+        Some(&MEMO_PROGRAM_ID),
+        None,
+        None,
+        &program_id
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ed25519_signature_verification_instruction,
+            withdraw_lamports_using_ed25519_ix
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::MessageV1ValidationFailed as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_invalid_to_account() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::from_bytes(
+        &[
+            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
+            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
+            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
+        ]
+    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let to = Keypair::from_bytes(
+        &[
+            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
+            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
+            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
+            117,177,107,131,121,11,228,101,173,11,51,156
+        ]
+    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
+    pt.add_account(
+        to.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let fake_to = Keypair::new();
+    pt.add_account(
+        fake_to.pubkey(),
+        SolanaAccount {
+            lamports: Rent::default().minimum_balance(0),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            owner: Pubkey::default(),
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.pubkey().as_ref()
+        ],
+        &program_id
+    ).unwrap().0;
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let send_lamport_to_bank_account_ix = transfer_lamports(
+        &message_signer.pubkey(),
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[send_lamport_to_bank_account_ix],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &message_signer
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+
+    let _message = "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,50000000000,PooriaGG 😃,4102444800";
+
+    let ed25519_signature_verification_instruction = Instruction {
+        program_id: solana_sdk::ed25519_program::ID,
+        accounts: vec![],
+        data: vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,81,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,
+            191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,83,122,28,89,43,243,38,174,126,56,56,89,
+            121,140,71,151,15,255,40,208,227,91,190,213,106,191,136,215,119,72,81,197,97,66,69,103,14,236,133,185,245,177,
+            85,109,232,186,116,59,39,220,106,109,217,10,140,60,87,128,198,76,174,50,34,10,52,102,118,118,89,113,107,99,
+            71,53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,
+            55,68,106,68,80,90,44,53,48,48,48,48,48,48,48,48,48,48,44,80,111,111,114,105,97,71,71,32,240,159,
+            152,131,44,52,49,48,50,52,52,52,56,48,48
+        ]
+    };
+
+    let withdraw_lamports_using_ed25519_ix = create_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &to.pubkey(),
+        &fake_to.pubkey(),
+        &to.pubkey(),
+        &SYSTEM_PROGRAM_ID,
+        Some(&MEMO_PROGRAM_ID),
+        None,
+        None,
+        &program_id
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ed25519_signature_verification_instruction,
+            withdraw_lamports_using_ed25519_ix
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to,
+            &fake_to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::InvalidToPubkey as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_gasless_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        relayer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            gasless: true,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let to = Pubkey::new_unique();
+    let message = format!("{},1500000000,,{}", to, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_gasless_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &relayer.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to_balance = banks_client
+        .get_balance(to)
+        .await
+        .unwrap();
+    assert_eq!(
+        to_balance,
+        1500000000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_gasless_memo_without_funder_signature() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            gasless: true,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    // neither the funder nor "to" signs -> the bank-account signs the memo
+    let funder = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    let message = format!("{},1500000000,invoice #9,{}", to, MESSAGE_EXPIRES_AT);
+    let mut withdraw_ix = create_gasless_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &funder,
+        &to,
+        &SYSTEM_PROGRAM_ID,
+        Some(&MEMO_PROGRAM_ID),
+        None,
+        None,
+        &program_id
+    );
+    withdraw_ix.accounts[1].is_signer = false;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            withdraw_ix
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+
+    let metadata = result
+        .metadata
+        .unwrap();
+    assert!(
+        metadata.log_messages.contains(&format!("Program {} invoke [2]", MEMO_PROGRAM_ID)),
+        "Memo program must be invoked by the withdrawal."
+    );
+    assert!(
+        metadata.log_messages.iter().any(|log| log.contains("invoice #9")),
+        "Memo must be logged by the memo program."
+    );
+
+    assert_eq!(
+        banks_client.get_balance(to).await.unwrap(),
+        1500000000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_gasless_disabled() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        relayer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Pubkey::new_unique();
+    let message = format!("{},1500000000,,{}", to, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_gasless_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &relayer.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::GaslessDisabled as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_success_1() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::from_bytes(
+        &[
+            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
+            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
+            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
+        ]
+    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+
+    let to = Keypair::from_bytes(
+        &[
+            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
+            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
+            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
+            117,177,107,131,121,11,228,101,173,11,51,156
+        ]
+    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
+
+    let (
+        bank_account,
+        bump
+    ) = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.pubkey().to_bytes().as_slice()
+        ],
+        &program_id
+    ).unwrap();
+
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            rent_epoch: Epoch::default(),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false
+        }
+    );
+    pt.add_account(
+        to.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(2.0),
+            rent_epoch: Epoch::default(),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false
+        }
+    );
+    pt.add_account(
+        bank_account,
+        SolanaAccount {
+            owner: program_id,
+            lamports: sol_to_lamports(1.0),
+            rent_epoch: Epoch::default(),
+            data: UserBankAccount {
+                discriminator: UserBankAccount::get_bank_account_discriminator(),
+                bump,
+                account_created_at: Clock::default().unix_timestamp,
+                authority: message_signer.pubkey(),
+                signatures: vec![],
+                ..UserBankAccount::default()
+            }.try_to_vec().unwrap(),
+            executable: false
+        }
+    );
+
+    let mint_account = Pubkey::new_from_array([3; 32]);
+    let signer_token_account = Pubkey::new_from_array([4; 32]);
+    let to_token_account = Pubkey::new_from_array([5; 32]);
+    let bank_account_token_account = Pubkey::try_find_program_address(
+        &[
+            bank_account.to_bytes().as_slice(),
+            TOKEN_STANDARD_PROGRAM.to_bytes().as_slice(),
+            mint_account.to_bytes().as_slice()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+    
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &Keypair::new().pubkey(),
+        1000_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &signer_token_account,
+        &message_signer.pubkey(),
+        500_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &to_token_account,
+        &to.pubkey(),
+        300_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &bank_account_token_account,
+        &bank_account,
+        200_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let _message: String = String::from(
+        "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,5000,PooriaGG 🤩,4102444800"
+    );
+
+    let ed25519_signature_verification_instruction = Instruction {
+        program_id: solana_sdk::ed25519_program::ID,
+        accounts: vec![],
+        data: vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,118,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,
+            157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200,229,235,227,84,15,67,232,191,58,172,25,121,49,8,
+            234,215,111,189,238,223,97,210,159,30,104,104,94,192,221,134,54,137,116,203,113,120,183,98,176,46,102,244,31,64,141,
+            0,72,220,73,150,89,139,116,93,107,129,103,74,219,160,46,75,235,8,52,102,118,118,89,113,107,99,71,53,105,122,
+            113,86,78,117,77,75,121,82,52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,80,
+            90,44,67,107,116,82,117,81,50,109,116,116,103,82,71,107,88,74,116,121,107,115,100,75,72,106,85,100,99,50,67,
+            52,84,103,68,122,121,66,57,56,111,69,122,121,56,44,53,48,48,48,44,80,111,111,114,105,97,71,71,32,240,159,
+            164,169,44,52,49,48,50,52,52,52,56,48,48
+        ]
+    };
+
+    let withdraw_spl_tokens_using_ed25519 = create_withdraw_spl_tokens_using_ed25519_signature(
+        &mint_account,
+        &bank_account,
+        &bank_account_token_account,
+        &to.pubkey(),
+        &to.pubkey(),
+        &to_token_account,
+        &TOKEN_STANDARD_PROGRAM,
+        &SYSTEM_PROGRAM_ID,
+        Some(&MEMO_PROGRAM_ID),
+        None,
+        &program_id
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ed25519_signature_verification_instruction.clone(),
+            withdraw_spl_tokens_using_ed25519
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_data = banks_client
+        .get_account(bank_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    let expected_signature: [u8; 64] = ed25519_signature_verification_instruction
+        .data
+        .get(48..112)
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    assert_eq!(
+        expected_signature,
+        bank_account_info
+            .signatures
+            .get(0)
+            .unwrap()
+            .signature,
+        "Signature mismatch!"
+    );
+
+    assert!(bank_account_info.signatures[0].is_ok);
+    assert_eq!(
+        bank_account_info.signatures[0].fail_reason,
+        SignatureFailReason::None,
+        "Fail reason mismatch."
+    );
+
+    let to_token_account_data = banks_client
+        .get_account(to_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let to_token_account_info = TokenAccount::unpack(&to_token_account_data).unwrap();
+
+    let bank_account_tokne_account_data = banks_client
+        .get_account(bank_account_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_token_account_info = TokenAccount::unpack(&bank_account_tokne_account_data).unwrap();
+
+    assert_eq!(
+        to_token_account_info.amount,
+        350_00u64
+    );
+
+    assert_eq!(
+        bank_account_token_account_info.amount,
+        150_00u64
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_success_2() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::from_bytes(
+        &[
+            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
+            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
+            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
+        ]
+    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+
+    let to = Keypair::from_bytes(
+        &[
+            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
+            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
+            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
+            117,177,107,131,121,11,228,101,173,11,51,156
+        ]
+    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
+
+    let (
+        bank_account,
+        bump
+    ) = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.pubkey().to_bytes().as_slice()
+        ],
+        &program_id
+    ).unwrap();
+
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            rent_epoch: Epoch::default(),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false
+        }
+    );
+    pt.add_account(
+        to.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(2.0),
+            rent_epoch: Epoch::default(),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false
+        }
+    );
+    pt.add_account(
+        bank_account,
+        SolanaAccount {
+            owner: program_id,
+            lamports: sol_to_lamports(1.0),
+            rent_epoch: Epoch::default(),
+            data: UserBankAccount {
+                discriminator: UserBankAccount::get_bank_account_discriminator(),
+                bump,
+                account_created_at: Clock::default().unix_timestamp,
+                authority: message_signer.pubkey(),
+                signatures: vec![],
+                ..UserBankAccount::default()
+            }.try_to_vec().unwrap(),
+            executable: false
+        }
+    );
+
+    let mint_account = Pubkey::new_from_array([3; 32]);
+    let signer_token_account = Pubkey::new_from_array([4; 32]);
+    let to_token_account = Pubkey::new_from_array([5; 32]);
+    let bank_account_token_account = Pubkey::try_find_program_address(
+        &[
+            bank_account.to_bytes().as_slice(),
+            TOKEN_STANDARD_PROGRAM.to_bytes().as_slice(),
+            mint_account.to_bytes().as_slice()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+    
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &Keypair::new().pubkey(),
+        1000_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &signer_token_account,
+        &message_signer.pubkey(),
+        500_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &to_token_account,
+        &to.pubkey(),
+        300_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &bank_account_token_account,
+        &bank_account,
+        200_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let _message: String = String::from(
+        "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,50000,PooriaGG 🤩,4102444800"
+    );
+
+    let ed25519_signature_verification_instruction = Instruction {
+        program_id: solana_sdk::ed25519_program::ID,
+        accounts: vec![],
+        data: vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,119,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,157,169,169,
+            85,113,83,0,79,147,213,225,127,24,199,48,221,200,46,197,76,81,6,59,38,106,167,225,180,42,16,130,97,108,158,234,131,166,
+            69,42,252,62,93,137,135,41,72,148,151,35,25,92,159,88,53,217,187,246,217,175,220,18,71,130,167,85,201,235,232,184,86,121,
+            183,19,55,213,196,232,8,16,113,5,52,102,118,118,89,113,107,99,71,53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,
+            55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,80,90,44,67,107,116,82,117,81,50,109,116,116,103,82,71,
+            107,88,74,116,121,107,115,100,75,72,106,85,100,99,50,67,52,84,103,68,122,121,66,57,56,111,69,122,121,56,44,53,48,48,
+            48,48,44,80,111,111,114,105,97,71,71,32,240,159,164,169,44,52,49,48,50,52,52,52,56,48,48
+        ]
+    };
+
+    let withdraw_spl_tokens_using_ed25519 = create_withdraw_spl_tokens_using_ed25519_signature(
+        &mint_account,
+        &bank_account,
+        &bank_account_token_account,
+        &to.pubkey(),
+        &to.pubkey(),
+        &to_token_account,
+        &TOKEN_STANDARD_PROGRAM,
+        &SYSTEM_PROGRAM_ID,
+        Some(&MEMO_PROGRAM_ID),
+        None,
+        &program_id
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ed25519_signature_verification_instruction.clone(),
+            withdraw_spl_tokens_using_ed25519
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_data = banks_client
+        .get_account(bank_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    let expected_signature: [u8; 64] = ed25519_signature_verification_instruction
+        .data
+        .get(48..112)
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    assert!(!bank_account_info.signatures[0].is_ok);
+    assert_eq!(
+        bank_account_info.signatures[0].fail_reason,
+        SignatureFailReason::InsufficientTokens,
+        "Fail reason mismatch."
+    );
+
+    let to_token_account_data = banks_client
+        .get_account(to_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let to_token_account_info = TokenAccount::unpack(&to_token_account_data).unwrap();
+
+    let bank_account_tokne_account_data = banks_client
+        .get_account(bank_account_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_token_account_info = TokenAccount::unpack(&bank_account_tokne_account_data).unwrap();
+
+    assert_eq!(
+        to_token_account_info.amount,
+        300_00u64
+    );
+
+    assert_eq!(
+        bank_account_token_account_info.amount,
+        200_00u64
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_failed_signature_replayed() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::from_bytes(
+        &[
+            159,42,51,158,177,31,236,33,199,251,245,169,11,226,48,147,119,9,180,119,251,52,
+            136,183,83,36,3,12,120,40,177,57,187,220,42,181,173,60,36,199,230,65,125,124,22,
+            8,191,157,169,169,85,113,83,0,79,147,213,225,127,24,199,48,221,200
+        ]
+    ).unwrap(); // "DeKxTUZrgjpUzNjibLc8kvbByz9e37BEJ8Ce7xDairhV"
+
+    let to = Keypair::from_bytes(
+        &[
+            237,227,10,102,176,81,227,2,143,72,178,176,123,49,168,231,31,
+            164,112,111,25,25,196,116,155,99,155,16,225,248,60,255,54,140,
+            26,77,149,64,206,192,130,179,65,73,200,27,46,201,49,21,157,36,
+            117,177,107,131,121,11,228,101,173,11,51,156
+        ]
+    ).unwrap(); // "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ"
+
+    let (
+        bank_account,
+        bump
+    ) = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.pubkey().to_bytes().as_slice()
+        ],
+        &program_id
+    ).unwrap();
+
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            rent_epoch: Epoch::default(),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false
+        }
+    );
+    pt.add_account(
+        to.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(2.0),
+            rent_epoch: Epoch::default(),
+            owner: Pubkey::default(),
+            data: vec![],
+            executable: false
+        }
+    );
+    pt.add_account(
+        bank_account,
+        SolanaAccount {
+            owner: program_id,
+            lamports: sol_to_lamports(1.0),
+            rent_epoch: Epoch::default(),
+            data: UserBankAccount {
+                discriminator: UserBankAccount::get_bank_account_discriminator(),
+                bump,
+                account_created_at: Clock::default().unix_timestamp,
+                authority: message_signer.pubkey(),
+                signatures: vec![],
+                ..UserBankAccount::default()
             }.try_to_vec().unwrap(),
             executable: false
         }
     );
 
-    let mint_account = Pubkey::new_from_array([3; 32]);
-    let signer_token_account = Pubkey::new_from_array([4; 32]);
-    let to_token_account = Pubkey::new_from_array([5; 32]);
-    let bank_account_token_account = Pubkey::try_find_program_address(
+    let mint_account = Pubkey::new_from_array([3; 32]);
+    let signer_token_account = Pubkey::new_from_array([4; 32]);
+    let to_token_account = Pubkey::new_from_array([5; 32]);
+    let bank_account_token_account = Pubkey::try_find_program_address(
+        &[
+            bank_account.to_bytes().as_slice(),
+            TOKEN_STANDARD_PROGRAM.to_bytes().as_slice(),
+            mint_account.to_bytes().as_slice()
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM
+    ).unwrap().0;
+    
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &Keypair::new().pubkey(),
+        1000_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &signer_token_account,
+        &message_signer.pubkey(),
+        500_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &to_token_account,
+        &to.pubkey(),
+        300_00u64
+    );
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &bank_account_token_account,
+        &bank_account,
+        200_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let _message: String = String::from(
+        "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,50000,PooriaGG 🤩,4102444800"
+    );
+
+    let ed25519_signature_verification_instruction = Instruction {
+        program_id: solana_sdk::ed25519_program::ID,
+        accounts: vec![],
+        data: vec![
+            1,0,48,0,255,255,16,0,255,255,112,0,119,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,157,169,169,
+            85,113,83,0,79,147,213,225,127,24,199,48,221,200,46,197,76,81,6,59,38,106,167,225,180,42,16,130,97,108,158,234,131,166,
+            69,42,252,62,93,137,135,41,72,148,151,35,25,92,159,88,53,217,187,246,217,175,220,18,71,130,167,85,201,235,232,184,86,121,
+            183,19,55,213,196,232,8,16,113,5,52,102,118,118,89,113,107,99,71,53,105,122,113,86,78,117,77,75,121,82,52,67,104,67,
+            55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,80,90,44,67,107,116,82,117,81,50,109,116,116,103,82,71,
+            107,88,74,116,121,107,115,100,75,72,106,85,100,99,50,67,52,84,103,68,122,121,66,57,56,111,69,122,121,56,44,53,48,48,
+            48,48,44,80,111,111,114,105,97,71,71,32,240,159,164,169,44,52,49,48,50,52,52,52,56,48,48
+        ]
+    };
+
+    let withdraw_spl_tokens_using_ed25519 = create_withdraw_spl_tokens_using_ed25519_signature(
+        &mint_account,
+        &bank_account,
+        &bank_account_token_account,
+        &to.pubkey(),
+        &to.pubkey(),
+        &to_token_account,
+        &TOKEN_STANDARD_PROGRAM,
+        &SYSTEM_PROGRAM_ID,
+        Some(&MEMO_PROGRAM_ID),
+        None,
+        &program_id
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ed25519_signature_verification_instruction.clone(),
+            withdraw_spl_tokens_using_ed25519.clone()
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    // the failed attempt is stored, submitting the same signature again is a replay
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ed25519_signature_verification_instruction.clone(),
+            withdraw_spl_tokens_using_ed25519
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignatureAlreadyUsed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    let bank_account_data = banks_client
+        .get_account(bank_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    let expected_signature: [u8; 64] = ed25519_signature_verification_instruction
+        .data
+        .get(48..112)
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    assert_eq!(bank_account_info.signatures.len(), 1usize);
+    assert!(!bank_account_info.signatures[0].is_ok);
+
+    let to_token_account_data = banks_client
+        .get_account(to_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let to_token_account_info = TokenAccount::unpack(&to_token_account_data).unwrap();
+
+    let bank_account_tokne_account_data = banks_client
+        .get_account(bank_account_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_token_account_info = TokenAccount::unpack(&bank_account_tokne_account_data).unwrap();
+
+    assert_eq!(
+        to_token_account_info.amount,
+        300_00u64
+    );
+
+    assert_eq!(
+        bank_account_token_account_info.amount,
+        200_00u64
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_under_token_max_per_tx_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig {
+            token_max_per_tx: vec![(mint_account, 100_00u64)],
+            ..BankAccountConfig::default()
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let message = format!("{},{},10000,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_spl_tokens_using_ed25519_signature(
+                &mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &to.pubkey(),
+                &to.pubkey(),
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to_token_account_data = banks_client
+        .get_account(to_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(
+        TokenAccount::unpack(&to_token_account_data).unwrap().amount,
+        100_00u64
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_twice_in_one_transaction_exceeding_balance() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    // each withdrawal fits the balance on its own, together they don't
+    let mut instructions = vec![];
+    for amount in [600u64, 500u64] {
+        let message = format!("{},{},{},,{}", to.pubkey(), mint_account, amount, MESSAGE_EXPIRES_AT);
+        instructions.push(
+            new_ed25519_signature_verification_instruction(&message_signer, &message)
+        );
+        instructions.push(
+            create_withdraw_spl_tokens_using_ed25519_signature(
+                &mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &to.pubkey(),
+                &to.pubkey(),
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        );
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, &to_token_account).await,
+        600u64,
+        "Destination token balance mismatch."
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &bank_account_token_account).await,
+        400u64,
+        "Bank-account token balance mismatch."
+    );
+
+    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client.get_account(bank_account).await.unwrap().unwrap().data[..]
+    ).unwrap();
+    assert_eq!(bank_account_info.signatures.len(), 2usize);
+    assert_eq!(bank_account_info.signatures[0].is_ok, true);
+    assert_eq!(bank_account_info.signatures[1].is_ok, false);
+    assert_eq!(bank_account_info.signatures[1].fail_reason, SignatureFailReason::InsufficientTokens);
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_exceeds_token_max_per_tx() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig {
+            token_max_per_tx: vec![(mint_account, 100_00u64)],
+            ..BankAccountConfig::default()
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let message = format!("{},{},10001,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_spl_tokens_using_ed25519_signature(
+                &mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &to.pubkey(),
+                &to.pubkey(),
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::ExceedsTokenMaxPerTx as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_check_rent_exempt_fresh_bank_account() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let status = check_rent_exempt(
+        &mut banks_client,
+        &payer,
+        &get_bank_account_pda(&payer.pubkey(), &program_id),
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    assert_eq!(
+        status.surplus,
+        0,
+        "Fresh bank-account must hold exactly the rent-exempt minimum."
+    );
+    assert_eq!(
+        status.lamports,
+        status.minimum_balance,
+        "Lamports mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_check_rent_exempt_after_ed25519_withdrawal() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let status = check_rent_exempt(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    assert_eq!(
+        status.surplus,
+        (30 * LAMPORTS_PER_SOL - 1500000000) as i64,
+        "Surplus mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_close_bank_account_full_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        authority_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_lamports = banks_client
+        .get_balance(bank_account)
+        .await
+        .unwrap();
+    let bank_account_token_account_lamports = banks_client
+        .get_balance(bank_account_token_account)
+        .await
+        .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_close_bank_account_full(
+                &authority.pubkey(),
+                &bank_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &[
+                    (bank_account_token_account, Some((mint_account, authority_token_account)))
+                ],
+                &program_id,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+
+    let return_data = result
+        .metadata
+        .unwrap()
+        .return_data
+        .unwrap();
+    let event = WithdrawEvent::try_from_slice(&return_data.data).unwrap();
+    assert_eq!(event.kind, WithdrawKind::CloseBankAccountFull, "Withdraw kind mismatch.");
+    assert_eq!(event.amount, bank_account_lamports, "Withdrawn amount mismatch.");
+    assert_eq!(event.to, authority.pubkey(), "Recepient mismatch.");
+
+    let authority_token_account_data = banks_client
+        .get_account(authority_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(
+        TokenAccount::unpack(&authority_token_account_data).unwrap().amount,
+        1000_00u64,
+        "Swept token amount mismatch."
+    );
+
+    assert!(
+        banks_client.get_account(bank_account_token_account).await.unwrap().is_none(),
+        "Bank-account's token-account must be closed."
+    );
+    assert!(
+        banks_client.get_account(bank_account).await.unwrap().is_none(),
+        "Bank-account must be closed."
+    );
+
+    let authority_balance = banks_client
+        .get_balance(authority.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        authority_balance,
+        sol_to_lamports(1.0) + bank_account_lamports + bank_account_token_account_lamports,
+        "Authority balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_close_bank_account_full_fail_unswept_tokens() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        _
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_close_bank_account_full(
+                &authority.pubkey(),
+                &bank_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &[
+                    (bank_account_token_account, None)
+                ],
+                &program_id,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::UnsweptTokens as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_failed_entry_keeps_rent_exempt() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let status = check_rent_exempt(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        status.surplus,
+        0,
+        "Bank-account must be exactly rent-exempt after a failed entry."
+    );
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    assert_eq!(
+        bank_account_info.signatures.len(),
+        1usize
+    );
+    assert_eq!(
+        bank_account_info.signatures.get(0usize).unwrap().is_ok,
+        false
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_signer_account_mismatch() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let bank_account_owner = Keypair::new();
+    pt.add_account(
+        bank_account_owner.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignerAccountMismatch as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_partially_using_ed25519_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},3000000000,payment #1,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    for (lamports, expected_remaining) in [(1000000000u64, Some(2000000000u64)), (2000000000u64, None)] {
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_lamports_partially_using_ed25519_signature(
+                    &bank_account_pda,
+                    &payer.pubkey(),
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    &program_id,
+                    lamports
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &to],
+            recent_blockhash
+        );
+
+        let result = banks_client
+            .process_transaction_with_metadata(tx)
+            .await
+            .unwrap();
+        assert!(result.result.is_ok());
+
+        let return_data = result
+            .metadata
+            .unwrap()
+            .return_data
+            .unwrap();
+        let event = WithdrawEvent::try_from_slice(&return_data.data).unwrap();
+        assert_eq!(event.kind, WithdrawKind::PartialLamportsUsingEd25519, "Withdraw kind mismatch.");
+        assert_eq!(event.amount, lamports, "Withdrawn amount mismatch.");
+        assert_eq!(event.to, to.pubkey(), "Recepient mismatch.");
+
+        let bank_account_data = banks_client
+            .get_account(bank_account_pda)
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+        let bank_account_info = UserBankAccount::try_from_slice(
+            &bank_account_data
+        ).unwrap();
+
+        assert_eq!(
+            bank_account_info
+                .partial_authorizations
+                .get(0usize)
+                .map(|authorization| authorization.remaining_amount),
+            expected_remaining,
+            "Remaining amount mismatch."
+        );
+    };
+
+    let to_balance = banks_client
+        .get_balance(to.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        to_balance,
+        3000000000,
+        "Recepient balance mismatch."
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_partially_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                &program_id,
+                500000000
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::AuthorizationFullyConsumed as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_redeem_payment_escrow_once() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        10 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let claim_signer = Keypair::new();
+    let nonce = 7u64;
+    let payment_escrow_pda = PaymentEscrow::get_payment_escrow_using_fpa(
+        &bank_account_pda,
+        nonce,
+        &program_id
+    ).0;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_init_payment_escrow(
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &bank_account_pda,
+                &payment_escrow_pda,
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                nonce,
+                2 * LAMPORTS_PER_SOL,
+                &claim_signer.pubkey()
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_balance = banks_client
+        .get_balance(bank_account_pda)
+        .await
+        .unwrap();
+    let payment_escrow_balance = banks_client
+        .get_balance(payment_escrow_pda)
+        .await
+        .unwrap();
+
+    let to = Pubkey::new_unique();
+    let message = format!("v4,{},{},2000000000,{}", payment_escrow_pda, to, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&claim_signer, &message),
+            create_redeem_payment_escrow(
+                &payment_escrow_pda,
+                &bank_account_pda,
+                &to,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+
+    let return_data = result
+        .metadata
+        .unwrap()
+        .return_data
+        .unwrap();
+    let event = WithdrawEvent::try_from_slice(&return_data.data).unwrap();
+    assert_eq!(event.kind, WithdrawKind::RedeemPaymentEscrow, "Withdraw kind mismatch.");
+    assert_eq!(event.amount, 2 * LAMPORTS_PER_SOL, "Withdrawn amount mismatch.");
+    assert_eq!(event.to, to, "Recepient mismatch.");
+
+    let to_balance = banks_client
+        .get_balance(to)
+        .await
+        .unwrap();
+    assert_eq!(
+        to_balance,
+        2 * LAMPORTS_PER_SOL,
+        "Recepient balance mismatch."
+    );
+
+    assert!(
+        banks_client.get_account(payment_escrow_pda).await.unwrap().is_none(),
+        "Payment-escrow must be closed."
+    );
+
+    let bank_account_balance_after = banks_client
+        .get_balance(bank_account_pda)
+        .await
+        .unwrap();
+    assert_eq!(
+        bank_account_balance_after,
+        bank_account_balance + payment_escrow_balance - 2 * LAMPORTS_PER_SOL,
+        "Escrow's rent must return to the bank-account."
+    );
+
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&claim_signer, &message),
+            create_redeem_payment_escrow(
+                &payment_escrow_pda,
+                &bank_account_pda,
+                &to,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::InvalidPaymentEscrow as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_redeem_payment_escrow_fail_signature_of_another_escrow() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        10 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    // two escrows of the same claim-signer and amount
+    let claim_signer = Keypair::new();
+    let payment_escrows = [1u64, 2u64].map(|nonce| {
+        PaymentEscrow::get_payment_escrow_using_fpa(
+            &bank_account_pda,
+            nonce,
+            &program_id
+        ).0
+    });
+    let tx = Transaction::new_signed_with_payer(
+        &[1u64, 2u64].map(|nonce| {
+            create_init_payment_escrow(
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &bank_account_pda,
+                &payment_escrows[nonce as usize - 1],
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                nonce,
+                LAMPORTS_PER_SOL,
+                &claim_signer.pubkey()
+            )
+        }),
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to = Pubkey::new_unique();
+    let message = format!("v4,{},{},1000000000,{}", payment_escrows[0], to, MESSAGE_EXPIRES_AT);
+    let redeem = |payment_escrow: &Pubkey| {
+        Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&claim_signer, &message),
+                create_redeem_payment_escrow(
+                    payment_escrow,
+                    &bank_account_pda,
+                    &to,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash
+        )
+    };
+
+    banks_client
+        .process_transaction(redeem(&payment_escrows[0]))
+        .await
+        .unwrap();
+
+    let error = banks_client
+        .process_transaction(redeem(&payment_escrows[1]))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::PaymentEscrowMessageMismatch as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    assert_eq!(
+        banks_client.get_balance(to).await.unwrap(),
+        LAMPORTS_PER_SOL,
+        "Recepient balance mismatch."
+    );
+    assert!(
+        banks_client.get_account(payment_escrows[1]).await.unwrap().is_some(),
+        "Second payment-escrow must stay open."
+    );
+}
+
+#[tokio::test]
+async fn test_redeem_payment_escrow_fail_claim_replayed_as_withdrawal() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    // the claim-signer owns a bank-account of its own
+    let claim_signer = Keypair::new();
+    pt.add_account(
+        claim_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        10 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let claim_signer_bank_account_pda = get_bank_account_pda(&claim_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &claim_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &claim_signer_bank_account_pda,
+        10 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let nonce = 1u64;
+    let payment_escrow_pda = PaymentEscrow::get_payment_escrow_using_fpa(
+        &bank_account_pda,
+        nonce,
+        &program_id
+    ).0;
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_init_payment_escrow(
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &bank_account_pda,
+                &payment_escrow_pda,
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                nonce,
+                LAMPORTS_PER_SOL,
+                &claim_signer.pubkey()
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to = payer.pubkey();
+    let claim_signer_bank_account_balance = banks_client
+        .get_balance(claim_signer_bank_account_pda)
+        .await
+        .unwrap();
+
+    // the escrow claim can't withdraw from the claim-signer's bank-account
+    let claim = format!("v4,{},{},1000000000,{}", payment_escrow_pda, to, MESSAGE_EXPIRES_AT);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&claim_signer, &claim),
+            create_withdraw_lamports_using_ed25519_signature(
+                &claim_signer_bank_account_pda,
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::MessageV1ValidationFailed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    assert_eq!(
+        banks_client.get_balance(claim_signer_bank_account_pda).await.unwrap(),
+        claim_signer_bank_account_balance,
+        "Claim-signer's bank-account must keep its lamports."
+    );
+
+    // nor can a V1 withdrawal message naming the escrow redeem it
+    let withdrawal = format!("{},1000000000,{},{}", to, payment_escrow_pda, MESSAGE_EXPIRES_AT);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&claim_signer, &withdrawal),
+            create_redeem_payment_escrow(
+                &payment_escrow_pda,
+                &bank_account_pda,
+                &to,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::MessageV4ValidationFailed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    assert!(
+        banks_client.get_account(payment_escrow_pda).await.unwrap().is_some(),
+        "Payment-escrow must stay open."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_mint_not_owned_by_token_program() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    // mint-shaped data that isn't owned by the spl-token program
+    let fake_mint_account = Pubkey::new_unique();
+    setup_new_mint_account(
+        &mut pt,
+        &Pubkey::new_unique(),
+        &fake_mint_account,
+        &Pubkey::new_unique(),
+        1000_00u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let message = format!("{},{},10000,,{}", to.pubkey(), fake_mint_account, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_spl_tokens_using_ed25519_signature(
+                &fake_mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &to.pubkey(),
+                &to.pubkey(),
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::InvalidMintAccount as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_with_savings_success() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    let savings_bank_account_pda = setup_new_bank_account_with_index(
+        &mut banks_client,
+        &payer,
+        1u16,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(10.0),
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        &BankAccountConfig {
+            savings_account: Some(savings_bank_account_pda),
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let savings_balance_before = banks_client
+        .get_balance(savings_bank_account_pda)
+        .await
+        .unwrap();
+
+    let recepient = Pubkey::new_unique();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_with_savings(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                &savings_bank_account_pda,
+                &program_id,
+                sol_to_lamports(1.0),
+                2500u16,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let recepient_balance = banks_client
+        .get_balance(recepient)
+        .await
+        .unwrap();
+    assert_eq!(
+        recepient_balance,
+        sol_to_lamports(0.75),
+        "Recepient balance mismatch."
+    );
+
+    let savings_balance_after = banks_client
+        .get_balance(savings_bank_account_pda)
+        .await
+        .unwrap();
+    assert_eq!(
+        savings_balance_after - savings_balance_before,
+        sol_to_lamports(0.25),
+        "Savings balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_with_savings_fail_foreign_savings_account() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let mut pt = setup(&program_id);
+
+    let savings_owner = Keypair::new();
+    pt.add_account(
+        savings_owner.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    let savings_bank_account_pda = get_bank_account_pda(&savings_owner.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    setup_new_bank_account(
+        &mut banks_client,
+        &savings_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(10.0),
+        recent_blockhash
+    ).await;
+    // designating another authority's bank-account as savings
+    update_bank_account_config(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        &BankAccountConfig {
+            savings_account: Some(savings_bank_account_pda),
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_with_savings(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &Pubkey::new_unique(),
+                &savings_bank_account_pda,
+                &program_id,
+                sol_to_lamports(1.0),
+                2500u16,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InvalidSavingsAccount as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_with_savings_fail_invalid_savings_bps() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_with_savings(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                &program_id,
+                sol_to_lamports(1.0),
+                10001u16,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InvalidSavingsBps as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_short_ed25519_ix_data() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    // zero signatures -> passes the ed25519 program but carries no signer/signature/message
+    let short_ed25519_ix = Instruction {
+        program_id: ED25519_PROGRAM_ID,
+        accounts: vec![],
+        data: vec![0u8, 0u8]
+    };
+
+    let to = Keypair::new();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            short_ed25519_ix,
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::InvalidEd25519SignatureVerificationInstruction as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_records_slot() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let mut context = pt.start_with_context().await;
+    context.warp_to_slot(100).unwrap();
+
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let current_slot = context.banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .slot;
+
+    let bank_account_data = context.banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    let sig_info = bank_account_info.signatures.get(0usize).unwrap();
+    assert!(current_slot >= 100);
+    assert_eq!(
+        sig_info.slot,
+        current_slot,
+        "Recorded slot mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamport_max_distinct_recipients() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(10.0),
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        &BankAccountConfig {
+            max_distinct_recipients: Some(2),
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let recepient_1 = Pubkey::new_unique();
+    let recepient_2 = Pubkey::new_unique();
+    let recepient_3 = Pubkey::new_unique();
+
+    // the first recepient is withdrawn to twice, it counts once
+    for (recepient, lamports) in [
+        (recepient_1, sol_to_lamports(1.0)),
+        (recepient_2, sol_to_lamports(1.0)),
+        (recepient_1, sol_to_lamports(0.5))
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                create_withdraw_lamports(
+                    &payer.pubkey(),
+                    &bank_account_pda,
+                    &recepient,
+                    None,
+                    &program_id,
+                    &lamports,
+                    None
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash
+        );
+
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap();
+    };
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+    assert_eq!(
+        bank_account_info.recipients,
+        vec![recepient_1, recepient_2]
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient_3,
+                None,
+                &program_id,
+                &sol_to_lamports(1.0),
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::TooManyRecipients as u32
+            )
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_revoke_all_rejects_stale_signature() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_revoke_all(
+                &message_signer.pubkey(),
+                &bank_account_pda,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &message_signer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to = Keypair::new();
+    let stale_message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &stale_message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::StaleRevocationEpoch as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    let message = format!("e1,{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    assert_eq!(bank_account_info.revocation_epoch, 1u64, "Mismatch revocation epochs!");
+}
+
+#[tokio::test]
+async fn test_withdraw_lamport_fail_recipient_is_receipt_pda() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let receipt_pda = WithdrawalReceipt::get_receipt_account_using_fpa(
+        &bank_account_pda,
+        &program_id
+    ).0;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_init_receipt_instruction(
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &bank_account_pda,
+                &receipt_pda,
+                &SYSTEM_PROGRAM_ID,
+                &program_id
+            ),
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &receipt_pda,
+                None,
+                &program_id,
+                &sol_to_lamports(0.25),
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::InvalidRecipient as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+/// upper bound of compute units for an ed25519 withdrawal from a bank-account holding
+/// `ADD_SIGNATURE_BENCHMARK_SIGNATURES` used signatures (`add_signature` scans all of them),
+/// the builtin `processor!` isn't metered so the budget only bites against the SBF build (`BPF_OUT_DIR`)
+// far-future expiry for messages that shouldn't expire during the tests (2100-01-01)
+const MESSAGE_EXPIRES_AT: i64 = 4_102_444_800;
+
+const ADD_SIGNATURE_CU_BUDGET: u64 = 200_000;
+const ADD_SIGNATURE_BENCHMARK_SIGNATURES: u16 = 500;
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_add_signature_cu_budget() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let (bank_account_pda, bump) = Pubkey::find_program_address(
+        &[
+            b"user_bank_account",
+            message_signer.pubkey().as_ref()
+        ],
+        &program_id
+    );
+
+    let signatures = (0..ADD_SIGNATURE_BENCHMARK_SIGNATURES)
+        .map(|index| {
+            let mut signature = [0u8; 64];
+            signature[..2].copy_from_slice(index.to_le_bytes().as_slice());
+
+            VerifiedSignature {
+                signature,
+                is_ok: true,
+                fail_reason: SignatureFailReason::None,
+                time: 0,
+                slot: 0,
+                message: format!("{},1000,,0", Pubkey::new_unique()).into_bytes()
+            }
+        })
+        .collect::<Vec<VerifiedSignature>>();
+    let bank_account_data = UserBankAccount {
+        discriminator: UserBankAccount::get_bank_account_discriminator(),
+        authority: message_signer.pubkey(),
+        bump,
+        signatures,
+        ..UserBankAccount::default()
+    }.try_to_vec().unwrap();
+
+    pt.add_account(
+        bank_account_pda,
+        SolanaAccount {
+            lamports: Rent::default().minimum_balance(bank_account_data.len()) + 30 * LAMPORTS_PER_SOL,
+            owner: program_id,
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: bank_account_data
+        }
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    result.result.unwrap();
+
+    let compute_units_consumed = result.metadata
+        .unwrap()
+        .compute_units_consumed;
+    assert!(
+        compute_units_consumed <= ADD_SIGNATURE_CU_BUDGET,
+        "ed25519 withdrawal consumed {} compute units (budget: {}).",
+        compute_units_consumed,
+        ADD_SIGNATURE_CU_BUDGET
+    );
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    // the benchmark entries already expired -> evicted down to the cap
+    assert_eq!(
+        bank_account_info.signatures.len(),
+        MAX_STORED_SIGNATURES,
+        "Signatures count mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_signatures_capped() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let mut context = pt.start_with_context().await;
+    let message_signer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let mut clock = context.banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+
+    let new_withdraw_tx = |to: &Keypair, expires_at: i64, recent_blockhash: Hash| {
+        let message = format!("{},1000000,,{}", to.pubkey(), expires_at);
+
+        Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_lamports_using_ed25519_signature(
+                    &bank_account_pda,
+                    &message_signer.pubkey(),
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&message_signer.pubkey()),
+            &[&message_signer, to],
+            recent_blockhash
+        )
+    };
+
+    for _ in 0..MAX_STORED_SIGNATURES {
+        let tx = new_withdraw_tx(&Keypair::new(), clock.unix_timestamp + 60, recent_blockhash);
+
+        context.banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap();
+    };
+
+    // every stored signature still guards against replays
+    let tx = new_withdraw_tx(&Keypair::new(), clock.unix_timestamp + 60, recent_blockhash);
+    let error = context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignatureStoreFull as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    clock.unix_timestamp += 120;
+    context.set_sysvar(&clock);
+
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    let to = Keypair::new();
+    let tx = new_withdraw_tx(&to, clock.unix_timestamp + 60, recent_blockhash);
+
+    context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_data = context.banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    assert_eq!(
+        bank_account_info.signatures.len(),
+        MAX_STORED_SIGNATURES,
+        "Signatures count mismatch."
+    );
+    assert!(
+        bank_account_info.signatures
+            .iter()
+            .any(|signature_info| signature_info.message.starts_with(to.pubkey().to_string().as_bytes())),
+        "Latest signature wasn't stored."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fee_tiers() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let fee_treasury = Pubkey::new_unique();
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            fee_tiers: vec![
+                (sol_to_lamports(1.0), 100),
+                (sol_to_lamports(5.0), 50)
+            ],
+            fee_treasury: Some(fee_treasury),
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let mut expected_treasury_lamports = 0u64;
+    // below the lowest tier, on the first tier's threshold, on the second tier's threshold
+    for (lamports, expected_fee) in [
+        (sol_to_lamports(0.5), 0u64),
+        (sol_to_lamports(1.0), sol_to_lamports(0.01)),
+        (sol_to_lamports(5.0), sol_to_lamports(0.025))
+    ] {
+        let to = Keypair::new();
+        let message = format!("{},{},,{}", to.pubkey(), lamports, MESSAGE_EXPIRES_AT);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_lamports_using_ed25519_signature(
+                    &bank_account_pda,
+                    &payer.pubkey(),
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    Some(&fee_treasury),
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &to],
+            recent_blockhash
+        );
+
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap();
+
+        expected_treasury_lamports += expected_fee;
+
+        let recepient_lamports = banks_client
+            .get_balance(to.pubkey())
+            .await
+            .unwrap();
+        let treasury_lamports = banks_client
+            .get_balance(fee_treasury)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            recepient_lamports,
+            lamports - expected_fee,
+            "Recepient lamports mismatch."
+        );
+        assert_eq!(
+            treasury_lamports,
+            expected_treasury_lamports,
+            "Treasury lamports mismatch."
+        );
+    };
+}
+
+#[tokio::test]
+async fn test_update_config_fail_invalid_fee_tiers() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    for config in [
+        // unsorted thresholds
+        BankAccountConfig {
+            fee_tiers: vec![(sol_to_lamports(5.0), 50), (sol_to_lamports(1.0), 100)],
+            fee_treasury: Some(Pubkey::new_unique()),
+            ..BankAccountConfig::default()
+        },
+        // bps above 10000
+        BankAccountConfig {
+            fee_tiers: vec![(sol_to_lamports(1.0), 10_001)],
+            fee_treasury: Some(Pubkey::new_unique()),
+            ..BankAccountConfig::default()
+        },
+        // no treasury to route the fees to
+        BankAccountConfig {
+            fee_tiers: vec![(sol_to_lamports(1.0), 100)],
+            ..BankAccountConfig::default()
+        }
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                create_update_config(
+                    &payer.pubkey(),
+                    &bank_account_pda,
+                    &payer.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    &program_id,
+                    &config
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash
+        );
+
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    BankError::InvalidFeeTiers as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+}
+
+#[tokio::test]
+async fn test_withdraw_lamport_withdrawal_grace_period() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let mut context = pt.start_with_context().await;
+    let bank_account_owner = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut context.banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut context.banks_client,
+        &bank_account_owner,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut context.banks_client,
+        &bank_account_owner,
+        &program_id,
+        &BankAccountConfig {
+            withdrawal_grace_secs: Some(3_600),
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let recepient = Pubkey::new_unique();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+
+    let error = context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::WithdrawalGracePeriodActive as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    // closing, sweeping and escrowing are withdrawals too
+    let grace_period_instructions = [
+        create_close_bank_account_full(
+            &bank_account_owner.pubkey(),
+            &bank_account_pda,
+            &TOKEN_STANDARD_PROGRAM,
+            &[],
+            &program_id,
+            None
+        ),
+        create_sweep_multiple_atas(
+            &bank_account_owner.pubkey(),
+            &bank_account_pda,
+            &TOKEN_STANDARD_PROGRAM,
+            &[],
+            &program_id,
+            None
+        ),
+        create_init_payment_escrow(
+            &bank_account_owner.pubkey(),
+            &bank_account_owner.pubkey(),
+            &bank_account_pda,
+            &PaymentEscrow::get_payment_escrow_using_fpa(&bank_account_pda, 1, &program_id).0,
+            &SYSTEM_PROGRAM_ID,
+            &program_id,
+            1,
+            sol_to_lamports(0.5),
+            &recepient
+        )
+    ];
+    for ix in grace_period_instructions {
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&bank_account_owner.pubkey()),
+            &[&bank_account_owner],
+            recent_blockhash
+        );
+        let error = context.banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    BankError::WithdrawalGracePeriodActive as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+
+    let mut clock = context.banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    clock.unix_timestamp += 3_600;
+    context.set_sysvar(&clock);
+
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+
+    context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let recepient_lamports = context.banks_client
+        .get_balance(recepient)
+        .await
+        .unwrap();
+    assert_eq!(
+        recepient_lamports,
+        sol_to_lamports(0.5),
+        "Recepient lamports mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_bypasses_withdrawal_grace_period() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let emergency_signer_1 = Keypair::new();
+    let emergency_signer_2 = Keypair::new();
+    let emergency_signer_3 = Keypair::new();
+    update_bank_account_config(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        &BankAccountConfig {
+            withdrawal_grace_secs: Some(3_600),
+            emergency_signers: vec![
+                emergency_signer_1.pubkey(),
+                emergency_signer_2.pubkey(),
+                emergency_signer_3.pubkey()
+            ],
+            emergency_threshold: 2,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let recepient = Pubkey::new_unique();
+
+    // a single emergency signer doesn't meet the threshold
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_emergency_withdraw(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                &[emergency_signer_1.pubkey()],
+                &program_id,
+                sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &emergency_signer_1],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::EmergencyThresholdNotMet as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    // the grace period is still active for regular withdrawals
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::WithdrawalGracePeriodActive as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_emergency_withdraw(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                &[emergency_signer_1.pubkey(), emergency_signer_3.pubkey()],
+                &program_id,
+                sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &emergency_signer_1, &emergency_signer_3],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let recepient_lamports = banks_client
+        .get_balance(recepient)
+        .await
+        .unwrap();
+    assert_eq!(
+        recepient_lamports,
+        sol_to_lamports(0.5),
+        "Recepient lamports mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_fail_freezable_mint_not_allowed() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig {
+            allow_freezable_mints: false,
+            ..BankAccountConfig::default()
+        }
+    );
+
+    // replaces the mint with a freezable one
+    let mut mint = Mint::unpack_unchecked(
+        [0u8; Mint::LEN].as_slice()
+    ).unwrap();
+    mint.decimals = 2u8;
+    mint.freeze_authority = COption::Some(Pubkey::new_unique());
+    mint.mint_authority = COption::Some(Pubkey::new_unique());
+    mint.supply = 1000_00u64;
+    mint.is_initialized = true;
+
+    let mint_data: &mut [u8] = &mut [0u8; Mint::LEN];
+    Mint::pack(mint, mint_data).unwrap();
+
+    pt.add_account(
+        mint_account,
+        SolanaAccount {
+            owner: TOKEN_STANDARD_PROGRAM,
+            lamports: Rent::default().minimum_balance(Mint::LEN),
+            data: mint_data.to_vec(),
+            executable: false,
+            rent_epoch: Epoch::default()
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_spl_tokens(
+                &authority.pubkey(),
+                &bank_account,
+                &bank_account_token_account,
+                &mint_account,
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                None,
+                &program_id,
+                &100_00u64,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::FreezableMintNotAllowed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_malformed_instruction_data_fail_invalid_instruction_data() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    // empty data, unknown versioned variant, truncated versioned variant fields
+    for data in [
+        vec![],
+        vec![INSTRUCTION_VERSION_FLAG | CURRENT_INSTRUCTION_VERSION, 0xff],
+        vec![INSTRUCTION_VERSION_FLAG | CURRENT_INSTRUCTION_VERSION, 1, 0xe8, 0x03]
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                Instruction {
+                    program_id,
+                    data,
+                    accounts: vec![]
+                }
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash
+        );
+
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData
+            ),
+            "Mismatch error types!"
+        );
+    };
+}
+
+#[tokio::test]
+async fn test_require_memo_direct_lamport_paths() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let emergency_signer = Keypair::new();
+    update_bank_account_config(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        &BankAccountConfig {
+            require_memo: true,
+            emergency_signers: vec![emergency_signer.pubkey()],
+            emergency_threshold: 1,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let recepient = Pubkey::new_unique();
+
+    for (ix, signers) in [
+        (
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                None
+            ),
+            vec![&payer]
+        ),
+        (
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                Some("")
+            ),
+            vec![&payer]
+        ),
+        (
+            create_withdraw_with_savings(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                &Pubkey::new_unique(),
+                &program_id,
+                sol_to_lamports(0.5),
+                1_000,
+                None
+            ),
+            vec![&payer]
+        ),
+        (
+            create_emergency_withdraw(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                &[emergency_signer.pubkey()],
+                &program_id,
+                sol_to_lamports(0.5),
+                None
+            ),
+            vec![&payer, &emergency_signer]
+        ),
+        (
+            create_close_bank_account_full(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &TOKEN_STANDARD_PROGRAM,
+                &[],
+                &program_id,
+                None
+            ),
+            vec![&payer]
+        ),
+        (
+            create_sweep_multiple_atas(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &TOKEN_STANDARD_PROGRAM,
+                &[],
+                &program_id,
+                None
+            ),
+            vec![&payer]
+        )
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &signers,
+            recent_blockhash
+        );
+
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    BankError::MemoRequired as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                Some("invoice #1")
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let recepient_lamports = banks_client
+        .get_balance(recepient)
+        .await
+        .unwrap();
+    assert_eq!(
+        recepient_lamports,
+        sol_to_lamports(0.5),
+        "Recepient lamports mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_fail_memo_required() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig {
+            require_memo: true,
+            ..BankAccountConfig::default()
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_spl_tokens(
+                &authority.pubkey(),
+                &bank_account,
+                &bank_account_token_account,
+                &mint_account,
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                None,
+                &program_id,
+                &100_00u64,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::MemoRequired as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_memo_required() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            require_memo: true,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::MemoRequired as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_memo_required() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig {
+            require_memo: true,
+            ..BankAccountConfig::default()
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let message = format!("{},{},100,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_spl_tokens_using_ed25519_signature(
+                &mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &to.pubkey(),
+                &to.pubkey(),
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::MemoRequired as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_compact_account_after_clearing_config() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    pt.add_account(
+        authority.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(10.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&authority.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &authority,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let initial_size = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+        .len();
+
+    update_bank_account_config(
+        &mut banks_client,
+        &authority,
+        &program_id,
+        &BankAccountConfig {
+            token_max_per_tx: (0..50)
+                .map(|_| (Pubkey::new_unique(), 1_000u64))
+                .collect(),
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &authority,
+        &program_id,
+        &BankAccountConfig {
+            gasless: true,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let bank_account_before = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let authority_balance_before = banks_client
+        .get_balance(authority.pubkey())
+        .await
+        .unwrap();
+    assert!(bank_account_before.data.len() > initial_size + 50 * 32);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_compact_account(
+                &authority.pubkey(),
+                &bank_account_pda,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &authority],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_after = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let authority_balance_after = banks_client
+        .get_balance(authority.pubkey())
+        .await
+        .unwrap();
+
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_after.data
+    ).unwrap();
+    assert_eq!(bank_account_info.config.gasless, true, "Config must survive compaction.");
+
+    assert!(bank_account_after.data.len() <= initial_size);
+    assert_eq!(
+        bank_account_after.data.len(),
+        bank_account_info.try_to_vec().unwrap().len(),
+        "Compacted size mismatch."
+    );
+    assert_eq!(
+        bank_account_after.lamports,
+        Rent::default().minimum_balance(bank_account_after.data.len()),
+        "Bank-account must keep exactly its rent-exempt minimum."
+    );
+    assert_eq!(
+        authority_balance_after - authority_balance_before,
+        bank_account_before.lamports - bank_account_after.lamports,
+        "Refund mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_through_cpi_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let forwarder_program_id = Pubkey::new_unique();
+    let mut pt = setup(&program_id);
+    pt.add_program(
+        "cpi_forwarder",
+        forwarder_program_id,
+        processor!(cpi_forwarder_processor)
+    );
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        relayer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            gasless: true,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let to = Pubkey::new_unique();
+    let message = format!("{},1500000000,,{}", to, MESSAGE_EXPIRES_AT);
+
+    let bank_ix = create_gasless_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &relayer.pubkey(),
+        &to,
+        &SYSTEM_PROGRAM_ID,
+        None,
+        None,
+        None,
+        &program_id
+    );
+    let mut forwarded_accounts = vec![
+        AccountMeta::new_readonly(program_id, false)
+    ];
+    forwarded_accounts.extend(bank_ix.accounts);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            Instruction {
+                program_id: forwarder_program_id,
+                accounts: forwarded_accounts,
+                data: bank_ix.data
+            }
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to_balance = banks_client
+        .get_balance(to)
+        .await
+        .unwrap();
+    assert_eq!(
+        to_balance,
+        1500000000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_ed25519_ix_not_immediately_preceding_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        relayer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            gasless: true,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let to = Pubkey::new_unique();
+    let message = format!("{},1500000000,,{}", to, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            transfer_lamports(
+                &relayer.pubkey(),
+                &to,
+                LAMPORTS_PER_SOL
+            ),
+            create_gasless_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &relayer.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to_balance = banks_client
+        .get_balance(to)
+        .await
+        .unwrap();
+    assert_eq!(
+        to_balance,
+        LAMPORTS_PER_SOL + 1500000000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_fail_insufficient_token_balance() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        100_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_spl_tokens(
+                &authority.pubkey(),
+                &bank_account,
+                &bank_account_token_account,
+                &mint_account,
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                None,
+                &program_id,
+                &100_01u64,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InsufficientTokenBalance as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_ed25519_disabled() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            ed25519_enabled: false,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::Ed25519WithdrawalsDisabled as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_ed25519_disabled() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig {
+            ed25519_enabled: false,
+            ..BankAccountConfig::default()
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let message = format!("{},{},100,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_spl_tokens_using_ed25519_signature(
+                &mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &to.pubkey(),
+                &to.pubkey(),
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::Ed25519WithdrawalsDisabled as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_get_effective_withdrawable_lamports() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let bank_account = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let rent = banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(bank_account.data.len());
+
+    let effective_withdrawable = get_effective_withdrawable(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        None,
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        effective_withdrawable,
+        EffectiveWithdrawable {
+            amount: bank_account.lamports - rent,
+            limit: WithdrawableLimit::RentFloor
+        },
+        "Effective withdrawable mismatch."
+    );
+
+    // a fresh bank-account is still inside its grace period
+    update_bank_account_config(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        &BankAccountConfig {
+            withdrawal_grace_secs: Some(3_600),
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let effective_withdrawable = get_effective_withdrawable(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        None,
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        effective_withdrawable,
+        EffectiveWithdrawable {
+            amount: 0,
+            limit: WithdrawableLimit::WithdrawalGracePeriod
+        },
+        "Effective withdrawable mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_get_effective_withdrawable_tokens() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let capped_mint_account = Pubkey::new_unique();
+    let uncapped_mint_account = Pubkey::new_unique();
+    let config = BankAccountConfig {
+        token_max_per_tx: vec![(capped_mint_account, 50_00u64)],
+        ..BankAccountConfig::default()
+    };
+    let (
+        bank_account,
+        capped_token_account,
+        _
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &capped_mint_account,
+        100_00u64,
+        config.clone()
+    );
+    let (
+        _,
+        uncapped_token_account,
+        _
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &uncapped_mint_account,
+        100_00u64,
+        config
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let effective_withdrawable = get_effective_withdrawable(
+        &mut banks_client,
+        &payer,
+        &bank_account,
+        Some(&capped_token_account),
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        effective_withdrawable,
+        EffectiveWithdrawable {
+            amount: 50_00u64,
+            limit: WithdrawableLimit::TokenMaxPerTx
+        },
+        "Effective withdrawable mismatch."
+    );
+
+    let effective_withdrawable = get_effective_withdrawable(
+        &mut banks_client,
+        &payer,
+        &bank_account,
+        Some(&uncapped_token_account),
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        effective_withdrawable,
+        EffectiveWithdrawable {
+            amount: 100_00u64,
+            limit: WithdrawableLimit::TokenBalance
+        },
+        "Effective withdrawable mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_get_effective_withdrawable_daily_limit() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_set_daily_limit(
+                &message_signer.pubkey(),
+                &bank_account_pda,
+                LAMPORTS_PER_SOL,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &message_signer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let effective_withdrawable = get_effective_withdrawable(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        None,
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        effective_withdrawable,
+        EffectiveWithdrawable {
+            amount: LAMPORTS_PER_SOL,
+            limit: WithdrawableLimit::DailyLimit
+        },
+        "Effective withdrawable mismatch."
+    );
+
+    let to = Keypair::new();
+    let message = format!("{},{},,{}", to.pubkey(), 600_000_000u64, MESSAGE_EXPIRES_AT);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &message_signer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &message_signer, &to],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    // the withdrawal is counted against the window of its recipient
+    let effective_withdrawable = get_effective_withdrawable(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        Some(&to.pubkey()),
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        effective_withdrawable,
+        EffectiveWithdrawable {
+            amount: 400_000_000u64,
+            limit: WithdrawableLimit::DailyLimit
+        },
+        "Effective withdrawable mismatch."
+    );
+
+    // not against the one of another recipient
+    let effective_withdrawable = get_effective_withdrawable(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        Some(&Pubkey::new_unique()),
+        &program_id,
+        recent_blockhash
+    ).await;
+    assert_eq!(
+        effective_withdrawable,
+        EffectiveWithdrawable {
+            amount: LAMPORTS_PER_SOL,
+            limit: WithdrawableLimit::DailyLimit
+        },
+        "Effective withdrawable mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_get_effective_withdrawable_frozen() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        _
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        100_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_freeze_account(
+                &authority.pubkey(),
+                &bank_account,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &authority],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    for token_account in [None, Some(&bank_account_token_account)] {
+        let effective_withdrawable = get_effective_withdrawable(
+            &mut banks_client,
+            &payer,
+            &bank_account,
+            token_account,
+            &program_id,
+            recent_blockhash
+        ).await;
+        assert_eq!(
+            effective_withdrawable,
+            EffectiveWithdrawable {
+                amount: 0,
+                limit: WithdrawableLimit::AccountFrozen
+            },
+            "Effective withdrawable mismatch."
+        );
+    };
+}
+
+#[tokio::test]
+async fn test_garbage_byte_instruction_data_fail_invalid_instruction_data() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    // unversioned data -> the byte is read as an unknown variant index
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            Instruction {
+                program_id,
+                data: vec![0x7f],
+                accounts: vec![]
+            }
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_minimal_bank_account() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    pt.add_account(
+        authority.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(10.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        bank_account_pda,
+        bump
+    ) = Pubkey::try_find_program_address(
+        &[
+            b"user_bank_account",
+            authority.pubkey().as_ref()
+        ],
+        &program_id
+    ).unwrap();
+
+    // discriminator + authority + bump, the layout the validator's offsets are written against
+    let mut minimal_data = vec![0u8; 41];
+    minimal_data[..8].copy_from_slice(&UserBankAccount::get_bank_account_discriminator());
+    minimal_data[8..40].copy_from_slice(authority.pubkey().as_ref());
+    minimal_data[40] = bump;
+    pt.add_account(
+        bank_account_pda,
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            owner: program_id,
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: minimal_data
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_migrate_bank_account(
+                &authority.pubkey(),
+                &bank_account_pda,
+                &authority.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                &program_id
+            )
+        ],
+        Some(&authority.pubkey()),
+        &[&authority],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account = UserBankAccount::try_from_slice(&bank_account_data).unwrap();
+
+    assert_eq!(bank_account.discriminator, UserBankAccount::get_bank_account_discriminator(), "Discriminator mismatch.");
+    assert_eq!(bank_account.authority, authority.pubkey(), "Authority mismatch.");
+    assert_eq!(bank_account.bump, bump, "Bump mismatch.");
+    assert_eq!(bank_account.config, BankAccountConfig::default(), "Config mismatch.");
+    assert_eq!(*bank_account_data.get(40).unwrap(), bump, "Bump offset mismatch.");
+
+    // the migrated bank-account is extended and withdrawn from like a created one
+    update_bank_account_config(
+        &mut banks_client,
+        &authority,
+        &program_id,
+        &BankAccountConfig {
+            max_distinct_recipients: Some(2),
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let recepient = Pubkey::new_unique();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &authority.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &authority],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client
+            .get_account(bank_account_pda)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+    ).unwrap();
+    assert_eq!(bank_account.recipients, vec![recepient], "Recipients mismatch.");
+    assert_eq!(
+        banks_client.get_balance(recepient).await.unwrap(),
+        sol_to_lamports(0.5),
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_records_total_deposited() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let balance_before = banks_client
+        .get_balance(bank_account_pda)
+        .await
+        .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_deposit(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                sol_to_lamports(2.0)
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client
+            .get_account(bank_account_pda)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+    ).unwrap();
+    assert_eq!(
+        bank_account.total_deposited,
+        sol_to_lamports(2.0),
+        "Total deposited mismatch."
+    );
+
+    let balance_after = banks_client
+        .get_balance(bank_account_pda)
+        .await
+        .unwrap();
+    assert_eq!(
+        balance_after - balance_before,
+        sol_to_lamports(2.0),
+        "Bank-Account balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_readonly_bank_account() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let message = format!("{},{},100,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+
+    let mut withdraw_ix = create_withdraw_spl_tokens_using_ed25519_signature(
+        &mint_account,
+        &bank_account,
+        &bank_account_token_account,
+        &to.pubkey(),
+        &to.pubkey(),
+        &to_token_account,
+        &TOKEN_STANDARD_PROGRAM,
+        &SYSTEM_PROGRAM_ID,
+        None,
+        None,
+        &program_id
+    );
+    // bank-account passed as readonly
+    withdraw_ix.accounts[1].is_writable = false;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            withdraw_ix
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::BankAccountNotWritable as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamport_blocked_recipients() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(2.0),
+        recent_blockhash
+    ).await;
+
+    let blocked_recepient = Pubkey::new_unique();
+    update_bank_account_config(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        &BankAccountConfig {
+            blocked_recipients: vec![blocked_recepient],
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &blocked_recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::RecipientBlocked as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    let unblocked_recepient = Pubkey::new_unique();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &unblocked_recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(unblocked_recepient).await.unwrap(),
+        sol_to_lamports(0.5),
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_fail_blocked_recipient_owner() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let blocked_owner = Pubkey::new_unique();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        blocked_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &blocked_owner,
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig {
+            blocked_recipients: vec![blocked_owner],
+            ..BankAccountConfig::default()
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    // sweeping and closing send the tokens to the blocked owner just the same
+    let instructions = [
+        create_withdraw_spl_tokens(
+            &authority.pubkey(),
+            &bank_account,
+            &bank_account_token_account,
+            &mint_account,
+            &blocked_token_account,
+            &TOKEN_STANDARD_PROGRAM,
+            None,
+            &program_id,
+            &100_00u64,
+            None
+        ),
+        create_sweep_multiple_atas(
+            &authority.pubkey(),
+            &bank_account,
+            &TOKEN_STANDARD_PROGRAM,
+            &[(mint_account, bank_account_token_account, blocked_token_account)],
+            &program_id,
+            None
+        ),
+        create_close_bank_account_full(
+            &authority.pubkey(),
+            &bank_account,
+            &TOKEN_STANDARD_PROGRAM,
+            &[(bank_account_token_account, Some((mint_account, blocked_token_account)))],
+            &program_id,
+            None
+        )
+    ];
+    for ix in instructions {
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[
+                &payer,
+                &authority
+            ],
+            recent_blockhash
+        );
+
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    BankError::RecipientBlocked as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+}
+
+#[tokio::test]
+async fn test_update_config_fail_too_many_blocked_recipients() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_update_config(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &payer.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                &BankAccountConfig {
+                    blocked_recipients: (0..MAX_BLOCKED_RECIPIENTS + 1).map(|_| Pubkey::new_unique()).collect(),
+                    ..BankAccountConfig::default()
+                }
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::TooManyBlockedRecipients as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_after_compute_budget_ix_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(400_000),
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to_balance = banks_client
+        .get_balance(to.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        to_balance,
+        1500000000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_success_in_date_message() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let current_timestamp = banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), current_timestamp + 60);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to_balance = banks_client
+        .get_balance(to.pubkey())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        to_balance,
+        1500000000,
+        "To-Account balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_expired_message() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let current_timestamp = banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), current_timestamp - 1);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignatureExpired as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_expired_message() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let current_timestamp = banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+
+    let message = format!("{},{},100,,{}", to.pubkey(), mint_account, current_timestamp - 1);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_spl_tokens_using_ed25519_signature(
+                &mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &to.pubkey(),
+                &to.pubkey(),
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignatureExpired as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_failed_attempt_moves_no_funds() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let mut context = pt.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &bank_account_pda,
+        sol_to_lamports(0.5),
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let new_withdraw_tx = |recent_blockhash: Hash, expires_at: i64| {
+        let message = format!("{},1500000000,,{}", to.pubkey(), expires_at);
+
+        Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_lamports_using_ed25519_signature(
+                    &bank_account_pda,
+                    &payer.pubkey(),
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &to],
+            recent_blockhash
+        )
+    };
+
+    let rent = context.banks_client
+        .get_rent()
+        .await
+        .unwrap();
+    let bank_account_before = context.banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let payer_balance_before = context.banks_client
+        .get_balance(payer.pubkey())
+        .await
+        .unwrap();
+
+    let tx = new_withdraw_tx(recent_blockhash, MESSAGE_EXPIRES_AT);
+    let tx_fee = context.banks_client
+        .get_fee_for_message(tx.message.clone())
+        .await
+        .unwrap()
+        .unwrap();
+
+    context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_after = context.banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let signature_entry_rent = rent.minimum_balance(bank_account_after.data.len()) - rent.minimum_balance(bank_account_before.data.len());
+
+    // the only lamports moved by the failed attempt are the funder's rent for the signature entry
+    assert_eq!(
+        bank_account_after.lamports,
+        bank_account_before.lamports + signature_entry_rent,
+        "Bank-account balance mismatch."
+    );
+    assert_eq!(
+        context.banks_client.get_balance(payer.pubkey()).await.unwrap(),
+        payer_balance_before - signature_entry_rent - tx_fee,
+        "Funder balance mismatch."
+    );
+    assert_eq!(
+        context.banks_client.get_balance(to.pubkey()).await.unwrap(),
+        0u64,
+        "To-Account balance mismatch."
+    );
+
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_after.data
+    ).unwrap();
+    assert_eq!(bank_account_info.signatures.len(), 1usize);
+    assert_eq!(bank_account_info.signatures[0].is_ok, false);
+    assert_eq!(bank_account_info.signatures[0].fail_reason, SignatureFailReason::InsufficientLamports);
+
+    fund_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &bank_account_pda,
+        sol_to_lamports(2.0),
+        recent_blockhash
+    ).await;
+
+    // the failed signature is stored -> retrying it is a replay even once funded
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    let error = context.banks_client
+        .process_transaction(new_withdraw_tx(recent_blockhash, MESSAGE_EXPIRES_AT))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignatureAlreadyUsed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    // the owner retries by signing a new message
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    context.banks_client
+        .process_transaction(new_withdraw_tx(recent_blockhash, MESSAGE_EXPIRES_AT - 1))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context.banks_client.get_balance(to.pubkey()).await.unwrap(),
+        1500000000,
+        "To-Account balance mismatch."
+    );
+
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    let error = context.banks_client
+        .process_transaction(new_withdraw_tx(recent_blockhash, MESSAGE_EXPIRES_AT - 1))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignatureAlreadyUsed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_failed_attempt_moves_no_funds() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        100u64,
+        BankAccountConfig::default()
+    );
+
+    let token_funder = Keypair::new();
+    let token_funder_token_account = Pubkey::new_unique();
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &token_funder_token_account,
+        &token_funder.pubkey(),
+        50u64
+    );
+
+    let mut context = pt.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+
+    let new_withdraw_tx = |recent_blockhash: Hash, expires_at: i64| {
+        let message = format!("{},{},150,,{}", to.pubkey(), mint_account, expires_at);
+
+        Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_spl_tokens_using_ed25519_signature(
+                    &mint_account,
+                    &bank_account,
+                    &bank_account_token_account,
+                    &payer.pubkey(),
+                    &to.pubkey(),
+                    &to_token_account,
+                    &TOKEN_STANDARD_PROGRAM,
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &to],
+            recent_blockhash
+        )
+    };
+
+    let rent = context.banks_client
+        .get_rent()
+        .await
+        .unwrap();
+    let bank_account_before = context.banks_client
+        .get_account(bank_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let payer_balance_before = context.banks_client
+        .get_balance(payer.pubkey())
+        .await
+        .unwrap();
+
+    let tx = new_withdraw_tx(recent_blockhash, MESSAGE_EXPIRES_AT);
+    let tx_fee = context.banks_client
+        .get_fee_for_message(tx.message.clone())
+        .await
+        .unwrap()
+        .unwrap();
+
+    context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_after = context.banks_client
+        .get_account(bank_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let signature_entry_rent = rent.minimum_balance(bank_account_after.data.len()) - rent.minimum_balance(bank_account_before.data.len());
+
+    assert_eq!(
+        bank_account_after.lamports,
+        bank_account_before.lamports + signature_entry_rent,
+        "Bank-account balance mismatch."
+    );
+    assert_eq!(
+        context.banks_client.get_balance(payer.pubkey()).await.unwrap(),
+        payer_balance_before - signature_entry_rent - tx_fee,
+        "Funder balance mismatch."
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, &bank_account_token_account).await,
+        100u64,
+        "Bank-account token balance mismatch."
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, &to_token_account).await,
+        0u64,
+        "Destination token balance mismatch."
+    );
+
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_after.data
+    ).unwrap();
+    assert_eq!(bank_account_info.signatures.len(), 1usize);
+    assert_eq!(bank_account_info.signatures[0].is_ok, false);
+    assert_eq!(bank_account_info.signatures[0].fail_reason, SignatureFailReason::InsufficientTokens);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            spl_token::instruction::transfer(
+                &TOKEN_STANDARD_PROGRAM,
+                &token_funder_token_account,
+                &bank_account_token_account,
+                &token_funder.pubkey(),
+                &[],
+                50u64
+            ).unwrap()
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &token_funder],
+        recent_blockhash
+    );
+    context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    // the failed signature is stored -> retrying it is a replay even once funded
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    let error = context.banks_client
+        .process_transaction(new_withdraw_tx(recent_blockhash, MESSAGE_EXPIRES_AT))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SignatureAlreadyUsed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    // the owner retries by signing a new message
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    context.banks_client
+        .process_transaction(new_withdraw_tx(recent_blockhash, MESSAGE_EXPIRES_AT - 1))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, &bank_account_token_account).await,
+        0u64,
+        "Bank-account token balance mismatch."
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, &to_token_account).await,
+        150u64,
+        "Destination token balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_verify_integrity_healthy_bank_account() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let issues = verify_integrity(
+        &mut banks_client,
+        &payer,
+        &get_bank_account_pda(&payer.pubkey(), &program_id),
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    assert_eq!(issues, 0u8, "Healthy bank-account must have no issues.");
+}
+
+#[tokio::test]
+async fn test_verify_integrity_corrupted_bank_accounts() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let rent = Rent::default();
+    let mut fixtures: Vec<(Pubkey, u8)> = vec![];
+    let mut add_fixture = |pt: &mut ProgramTest, bank_account: Pubkey, data: Vec<u8>, lamports: Option<u64>, expected_issues: u8| {
+        pt.add_account(
+            bank_account,
+            SolanaAccount {
+                lamports: lamports.unwrap_or(rent.minimum_balance(data.len())),
+                owner: program_id,
+                rent_epoch: Epoch::default(),
+                executable: false,
+                data
+            }
+        );
+        fixtures.push((bank_account, expected_issues));
+    };
+
+    let new_bank_account = || {
+        let authority = Pubkey::new_unique();
+        let (bank_account, bump) = Pubkey::find_program_address(
+            &[
+                b"user_bank_account",
+                authority.as_ref()
+            ],
+            &program_id
+        );
+
+        (
+            bank_account,
+            UserBankAccount {
+                discriminator: UserBankAccount::get_bank_account_discriminator(),
+                authority,
+                bump,
+                ..UserBankAccount::default()
+            }
+        )
+    };
+
+    // invalid discriminator
+    let (bank_account, mut bank_account_data) = new_bank_account();
+    bank_account_data.discriminator = [0u8; 8];
+    add_fixture(&mut pt, bank_account, bank_account_data.try_to_vec().unwrap(), None, INTEGRITY_INVALID_DISCRIMINATOR);
+
+    // stored bump doesn't derive the bank-account
+    let (bank_account, mut bank_account_data) = new_bank_account();
+    bank_account_data.bump = bank_account_data.bump.wrapping_sub(1);
+    add_fixture(&mut pt, bank_account, bank_account_data.try_to_vec().unwrap(), None, INTEGRITY_INVALID_BUMP);
+
+    // zeroed authority (the bank-account no longer derives from it either)
+    let (bank_account, mut bank_account_data) = new_bank_account();
+    bank_account_data.authority = Pubkey::default();
+    add_fixture(&mut pt, bank_account, bank_account_data.try_to_vec().unwrap(), None, INTEGRITY_ZERO_AUTHORITY | INTEGRITY_INVALID_BUMP);
+
+    // signatures vector claims an entry that doesn't fit in the data
+    let (bank_account, mut bank_account_data) = new_bank_account();
+    bank_account_data.signatures.push(
+        VerifiedSignature {
+            signature: [1u8; 64],
+            is_ok: true,
+            fail_reason: SignatureFailReason::None,
+            time: 0,
+            slot: 0,
+            message: vec![]
+        }
+    );
+    let mut data = bank_account_data.try_to_vec().unwrap();
+    let signatures_offset = 8 + 32 + 1 + 8 + bank_account_data.config.try_to_vec().unwrap().len();
+    data.truncate(signatures_offset + 4 + 16);
+    add_fixture(&mut pt, bank_account, data, None, INTEGRITY_INCONSISTENT_SIGNATURES);
+
+    // lamports below the rent-exempt minimum
+    let (bank_account, bank_account_data) = new_bank_account();
+    add_fixture(&mut pt, bank_account, bank_account_data.try_to_vec().unwrap(), Some(1), INTEGRITY_BELOW_RENT_MINIMUM);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    for (bank_account, expected_issues) in fixtures {
+        let issues = verify_integrity(
+            &mut banks_client,
+            &payer,
+            &bank_account,
+            &program_id,
+            recent_blockhash
+        ).await;
+
+        assert_eq!(issues, expected_issues, "Integrity issues mismatch.");
+    };
+}
+
+#[tokio::test]
+async fn test_sweep_multiple_atas_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account_1 = Pubkey::new_unique();
+    let mint_account_2 = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account_1,
+        authority_token_account_1
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account_1,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+    let (
+        _,
+        bank_account_token_account_2,
+        authority_token_account_2
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account_2,
+        500_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    // the second triple doesn't hold bank-account's associated token-account -> nothing is swept
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_sweep_multiple_atas(
+                &authority.pubkey(),
+                &bank_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &[
+                    (mint_account_1, bank_account_token_account_1, authority_token_account_1),
+                    (mint_account_2, bank_account_token_account_1, authority_token_account_2)
+                ],
+                &program_id,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidSeeds
+        ),
+        "Mismatch error types!"
+    );
+
+    let authority_token_account_data = banks_client
+        .get_account(authority_token_account_1)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(
+        TokenAccount::unpack(&authority_token_account_data).unwrap().amount,
+        0u64,
+        "Failed sweep must not move any tokens."
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_sweep_multiple_atas(
+                &authority.pubkey(),
+                &bank_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &[
+                    (mint_account_1, bank_account_token_account_1, authority_token_account_1),
+                    (mint_account_2, bank_account_token_account_2, authority_token_account_2)
+                ],
+                &program_id,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+
+    // every swept mint is reported, not only the last one
+    let return_data = result
+        .metadata
+        .unwrap()
+        .return_data
+        .unwrap();
+    let events = Vec::<WithdrawEvent>::try_from_slice(&return_data.data).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| (event.kind, event.amount, event.to))
+            .collect::<Vec<_>>(),
+        vec![
+            (WithdrawKind::SweepSplTokens, 1000_00u64, authority_token_account_1),
+            (WithdrawKind::SweepSplTokens, 500_00u64, authority_token_account_2)
+        ],
+        "Withdraw events mismatch."
+    );
+
+    for (token_account, expected_amount) in [
+        (authority_token_account_1, 1000_00u64),
+        (authority_token_account_2, 500_00u64),
+        (bank_account_token_account_1, 0u64),
+        (bank_account_token_account_2, 0u64)
+    ] {
+        let token_account_data = banks_client
+            .get_account(token_account)
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+        assert_eq!(
+            TokenAccount::unpack(&token_account_data).unwrap().amount,
+            expected_amount,
+            "Swept token amount mismatch."
+        );
+    };
+}
+
+#[tokio::test]
+async fn test_transfer_authority_success() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let mut pt = setup(&program_id);
+
+    let new_authority = Keypair::new();
+    pt.add_account(
+        new_authority.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = payer;
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_transfer_authority(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                &new_authority.pubkey()
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    assert_eq!(bank_account_info.authority, new_authority.pubkey(), "Authority mismatch.");
+    assert_eq!(bank_account_info.original_owner, Some(bank_account_owner.pubkey()), "Original owner mismatch.");
+
+    // the PDA keeps its address -> the new authority withdraws from it
+    let recepient = Pubkey::new_unique();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &new_authority.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                None
+            )
+        ],
+        Some(&new_authority.pubkey()),
+        &[&new_authority],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(recepient).await.unwrap(),
+        sol_to_lamports(0.5),
+        "Recepient lamports mismatch."
+    );
+
+    // ... and the previous authority can't anymore
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.1),
+                None
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidSeeds
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_authority_fail_unauthorized() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = Keypair::new();
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            transfer_lamports(
+                &payer.pubkey(),
+                &bank_account_owner.pubkey(),
+                sol_to_lamports(1.0)
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_transfer_authority(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                &payer.pubkey()
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidSeeds
+        ),
+        "Mismatch error types!"
+    );
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
+        &bank_account_data
+    ).unwrap();
+
+    assert_eq!(bank_account_info.authority, bank_account_owner.pubkey(), "Authority mismatch.");
+    assert_eq!(bank_account_info.original_owner, None, "Original owner mismatch.");
+}
+
+#[tokio::test]
+async fn test_get_account_info_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Pubkey::new_unique();
+    let (
+        bank_account,
+        bump
+    ) = Pubkey::find_program_address(
+        &[
+            b"user_bank_account",
+            authority.as_ref()
+        ],
+        &program_id
+    );
+    let signatures = (0..3u8)
+        .map(|index| {
+            VerifiedSignature {
+                signature: [index; 64],
+                is_ok: true,
+                fail_reason: SignatureFailReason::None,
+                time: 0,
+                slot: 0,
+                message: format!("{},1000,,{}", Pubkey::new_unique(), MESSAGE_EXPIRES_AT).into_bytes()
+            }
+        })
+        .collect::<Vec<VerifiedSignature>>();
+    let bank_account_data = UserBankAccount {
+        discriminator: UserBankAccount::get_bank_account_discriminator(),
+        authority,
+        bump,
+        account_created_at: 1_700_000_000,
+        signatures,
+        ..UserBankAccount::default()
+    }.try_to_vec().unwrap();
+    pt.add_account(
+        bank_account,
+        SolanaAccount {
+            lamports: Rent::default().minimum_balance(bank_account_data.len()),
+            owner: program_id,
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: bank_account_data
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_get_account_info(
+                &bank_account,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let return_data = banks_client
+        .simulate_transaction(tx)
+        .await
+        .unwrap()
+        .simulation_details
+        .unwrap()
+        .return_data
+        .unwrap();
+    let summary = BankAccountSummary::try_from_slice(&return_data.data).unwrap();
+
+    assert_eq!(
+        summary,
+        BankAccountSummary {
+            authority,
+            account_created_at: 1_700_000_000,
+            signatures_count: 3
+        },
+        "Bank-account summary mismatch."
+    );
+}
+
+/// raw ed25519 instruction -> offsets (7 u16 per signature, relative to the whole data) followed by `payload`
+fn new_raw_ed25519_instruction(
+    offsets: &[[u16; 7]],
+    payload: &[u8]
+) -> Instruction {
+    let mut data = vec![offsets.len() as u8, 0u8];
+    for signature_offsets in offsets {
+        for offset in signature_offsets {
+            data.extend_from_slice(&offset.to_le_bytes());
+        };
+    };
+    data.extend_from_slice(payload);
+
+    Instruction {
+        program_id: ED25519_PROGRAM_ID,
+        accounts: vec![],
+        data
+    }
+}
+
+fn sign_ed25519_message(
+    message_signer: &Keypair,
+    message: &[u8]
+) -> [u8; 64] {
+    use ed25519_dalek::Signer as _;
+
+    ed25519_dalek::Keypair::from_bytes(
+        &message_signer.to_bytes()
+    ).unwrap().sign(message).to_bytes()
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_non_canonical_offsets_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+    let signature = sign_ed25519_message(&message_signer, message.as_bytes());
+
+    // message first, then the signature and the pubkey
+    let message_offset = 16u16;
+    let signature_offset = message_offset + message.len() as u16;
+    let pubkey_offset = signature_offset + 64;
+    let mut payload = message.as_bytes().to_vec();
+    payload.extend_from_slice(&signature);
+    payload.extend_from_slice(message_signer.pubkey().as_ref());
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_raw_ed25519_instruction(
+                &[[signature_offset, u16::MAX, pubkey_offset, u16::MAX, message_offset, message.len() as u16, u16::MAX]],
+                &payload
+            ),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(to.pubkey()).await.unwrap(),
+        1500000000,
+        "To-Account balance mismatch."
+    );
+
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = UserBankAccount::try_from_slice(
+        &bank_account_data
+    ).unwrap();
+
+    assert_eq!(bank_account_info.signatures[0].signature, signature, "Signature mismatch.");
+    assert_eq!(bank_account_info.signatures[0].message, message.as_bytes(), "Message mismatch.");
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_unexpected_ed25519_layout() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+    let signature = sign_ed25519_message(&message_signer, message.as_bytes());
+    let message_size = message.len() as u16;
+
+    // two valid signatures over the same message -> (pubkey, signature) pairs after the message
+    let message_offset = 2 + 2 * 14;
+    let mut two_signatures_payload = message.as_bytes().to_vec();
+    for _ in 0..2 {
+        two_signatures_payload.extend_from_slice(message_signer.pubkey().as_ref());
+        two_signatures_payload.extend_from_slice(&signature);
+    };
+    let first_pubkey_offset = message_offset + message_size;
+    let second_pubkey_offset = first_pubkey_offset + 96;
+    let two_signatures_ix = new_raw_ed25519_instruction(
+        &[
+            [first_pubkey_offset + 32, u16::MAX, first_pubkey_offset, u16::MAX, message_offset, message_size, u16::MAX],
+            [second_pubkey_offset + 32, u16::MAX, second_pubkey_offset, u16::MAX, message_offset, message_size, u16::MAX]
+        ],
+        &two_signatures_payload
+    );
+
+    // the verified message lives in the first ed25519 instruction
+    let mut foreign_message_payload = message_signer.pubkey().to_bytes().to_vec();
+    foreign_message_payload.extend_from_slice(&signature);
+    let foreign_message_ixs = vec![
+        new_ed25519_signature_verification_instruction(&message_signer, &message),
+        new_raw_ed25519_instruction(
+            &[[48, u16::MAX, 16, u16::MAX, 112, message_size, 0]],
+            &foreign_message_payload
+        )
+    ];
+
+    // the two signatures instruction is read at an index past its signatures count
+    for (ed25519_ixs, signature_index) in [(vec![two_signatures_ix], 2), (foreign_message_ixs, 0)] {
+        let withdraw_ix_index = ed25519_ixs.len() as u8;
+        let mut instructions = ed25519_ixs;
+        instructions.push(
+            create_withdraw_lamports_using_ed25519_signature_at(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                signature_index,
+                &program_id
+            )
+        );
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[&payer, &to],
+            recent_blockhash
+        );
+
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                withdraw_ix_index,
+                InstructionError::Custom(
+                    BankError::InvalidEd25519SignatureVerificationInstruction as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_batch_success() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = payer;
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let recepients = [
+        (Pubkey::new_unique(), sol_to_lamports(0.5)),
+        (Pubkey::new_unique(), sol_to_lamports(0.3)),
+        (Pubkey::new_unique(), sol_to_lamports(0.2))
+    ];
+    let bank_account_balance = banks_client
+        .get_balance(bank_account_pda)
+        .await
+        .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports_batch(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &recepients,
+                &program_id
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+
+    let return_data = result
+        .metadata
+        .unwrap()
+        .return_data
+        .unwrap();
+    let events = Vec::<WithdrawEvent>::try_from_slice(&return_data.data).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| (event.kind, event.to, event.amount))
+            .collect::<Vec<_>>(),
+        recepients
+            .iter()
+            .map(|(recepient, lamports)| (WithdrawKind::LamportsBatch, *recepient, *lamports))
+            .collect::<Vec<_>>(),
+        "Withdraw events mismatch."
+    );
+
+    for (recepient, lamports) in recepients {
+        assert_eq!(
+            banks_client.get_balance(recepient).await.unwrap(),
+            lamports,
+            "Recepient lamports mismatch."
+        );
+    };
+    assert_eq!(
+        banks_client.get_balance(bank_account_pda).await.unwrap(),
+        bank_account_balance - sol_to_lamports(1.0),
+        "Bank-Account balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_batch_fail_insufficient_balance() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = payer;
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    // each amount is covered on its own, their sum isn't
+    let recepients = [
+        (Pubkey::new_unique(), sol_to_lamports(0.6)),
+        (Pubkey::new_unique(), sol_to_lamports(0.6))
+    ];
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports_batch(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &recepients,
+                &program_id
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InsufficientLamportBalance as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    let mut mismatched_ix = create_withdraw_lamports_batch(
+        &bank_account_owner.pubkey(),
+        &bank_account_pda,
+        &recepients,
+        &program_id
+    );
+    mismatched_ix.accounts.pop();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[mismatched_ix],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::BatchRecipientsMismatch as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_batch_fail_too_many_recipients() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = payer;
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    // their events wouldn't fit in the return data
+    let recepients = (0..=MAX_WITHDRAW_EVENTS)
+        .map(|_| (Pubkey::new_unique(), sol_to_lamports(0.01)))
+        .collect::<Vec<_>>();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports_batch(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &recepients,
+                &program_id
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::TooManyWithdrawals as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_emits_withdraw_event() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = payer;
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let recepient = Pubkey::new_unique();
+    let lamports = sol_to_lamports(0.25);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &lamports,
+                None
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+
+    let return_data = result
+        .metadata
+        .unwrap()
+        .return_data
+        .unwrap();
+    assert_eq!(return_data.program_id, program_id);
+
+    let event = WithdrawEvent::try_from_slice(&return_data.data).unwrap();
+
+    assert_eq!(event.kind, WithdrawKind::Lamports, "Withdraw kind mismatch.");
+    assert_eq!(event.amount, lamports, "Withdrawn amount mismatch.");
+    assert_eq!(event.to, recepient, "Recepient mismatch.");
+    assert_eq!(
+        banks_client.get_balance(recepient).await.unwrap(),
+        event.amount,
+        "Recepient lamports mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_create_bank_account_for_distinct_signing_authority_success() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let authority = Keypair::new();
+    create_bank_account_funded_by(
+        &mut banks_client,
+        &payer,
+        &authority,
+        &program_id,
+        recent_blockhash
+    ).await.unwrap();
+
+    let bank_account_data = banks_client
+        .get_account(get_bank_account_pda(&authority.pubkey(), &program_id))
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &bank_account_data
+    ).unwrap();
+
+    assert_eq!(
+        bank_account.authority,
+        authority.pubkey(),
+        "Authority mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_create_bank_account_fail_authority_not_signer() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let authority = Pubkey::new_unique();
+    let bank_account_pda = get_bank_account_pda(&authority, &program_id);
+
+    let mut ix = create_create_initialize_bank_account_instruction(
+        &payer.pubkey(),
+        &authority,
+        &bank_account_pda,
+        &SYSTEM_PROGRAM_ID,
+        &program_id
+    );
+    ix.accounts[1].is_signer = false;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::MissingRequiredSignature
+        ),
+        "Mismatch error types!"
+    );
+    assert!(
+        banks_client.get_account(bank_account_pda).await.unwrap().is_none(),
+        "Bank-account must not be created."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_all_lamports_success() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_deposit(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &SYSTEM_PROGRAM_ID,
+                &program_id,
+                sol_to_lamports(3.0)
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let recepient = Pubkey::new_unique();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_all_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let rent_minimum = banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(bank_account.data.len());
+
+    assert_eq!(
+        bank_account.lamports,
+        rent_minimum,
+        "Bank-Account must end at the rent-exempt minimum."
+    );
+    assert!(
+        banks_client.get_balance(recepient).await.unwrap() >= sol_to_lamports(3.0),
+        "Recepient lamports mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_with_memo_invokes_memo_program() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let recepient = Pubkey::new_unique();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                None,
+                &program_id,
+                &sol_to_lamports(0.5),
+                Some("invoice #2")
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    let result = banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+
+    let log_messages = result
+        .metadata
+        .unwrap()
+        .log_messages;
+    assert!(
+        log_messages.contains(&format!("Program {} invoke [2]", MEMO_PROGRAM_ID)),
+        "Memo program must be invoked by the withdrawal."
+    );
+    assert!(
+        log_messages.iter().any(|log| log.contains("invoice #2")),
+        "Memo must be logged by the memo program."
+    );
+
+    assert_eq!(
+        banks_client.get_balance(recepient).await.unwrap(),
+        sol_to_lamports(0.5),
+        "Recepient lamports mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_with_savings_and_emergency_withdraw_with_memo_invoke_memo_program() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    let savings_bank_account_pda = setup_new_bank_account_with_index(
+        &mut banks_client,
+        &payer,
+        1u16,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &payer,
+        &bank_account_pda,
+        sol_to_lamports(2.0),
+        recent_blockhash
+    ).await;
+
+    let emergency_signer = Keypair::new();
+    update_bank_account_config(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        &BankAccountConfig {
+            savings_account: Some(savings_bank_account_pda),
+            emergency_signers: vec![emergency_signer.pubkey()],
+            emergency_threshold: 1,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let recepient = Pubkey::new_unique();
+    for (ix, signers, memo, kind, amount) in [
+        (
+            create_withdraw_with_savings(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                &savings_bank_account_pda,
+                &program_id,
+                sol_to_lamports(0.5),
+                2500u16,
+                Some("invoice #3")
+            ),
+            vec![&payer],
+            "invoice #3",
+            WithdrawKind::LamportsWithSavings,
+            sol_to_lamports(0.375)
+        ),
+        (
+            create_emergency_withdraw(
+                &payer.pubkey(),
+                &bank_account_pda,
+                &recepient,
+                &[emergency_signer.pubkey()],
+                &program_id,
+                sol_to_lamports(0.5),
+                Some("invoice #4")
+            ),
+            vec![&payer, &emergency_signer],
+            "invoice #4",
+            WithdrawKind::EmergencyLamports,
+            sol_to_lamports(0.5)
+        )
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &signers,
+            recent_blockhash
+        );
+        let result = banks_client
+            .process_transaction_with_metadata(tx)
+            .await
+            .unwrap();
+        assert!(result.result.is_ok());
+
+        let metadata = result
+            .metadata
+            .unwrap();
+        assert!(
+            metadata.log_messages.contains(&format!("Program {} invoke [2]", MEMO_PROGRAM_ID)),
+            "Memo program must be invoked by the withdrawal."
+        );
+        assert!(
+            metadata.log_messages.iter().any(|log| log.contains(memo)),
+            "Memo must be logged by the memo program."
+        );
+
+        let event = WithdrawEvent::try_from_slice(&metadata.return_data.unwrap().data).unwrap();
+        assert_eq!(event.kind, kind, "Withdraw kind mismatch.");
+        assert_eq!(event.amount, amount, "Withdrawn amount mismatch.");
+        assert_eq!(event.to, recepient, "Recepient mismatch.");
+    };
+
+    assert_eq!(
+        banks_client.get_balance(recepient).await.unwrap(),
+        sol_to_lamports(0.875),
+        "Recepient lamports mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_reclaim_signature_rent_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let mut context = pt.start_with_context().await;
+    let message_signer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut context.banks_client,
+        &message_signer,
+        &bank_account_pda,
+        sol_to_lamports(1.0),
+        recent_blockhash
+    ).await;
+
+    let mut clock = context.banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    let first_signature_time = clock.unix_timestamp;
+
+    // three signatures recorded 100 seconds apart, each message expires a minute after it was redeemed
+    for _ in 0..3 {
+        let recent_blockhash = context
+            .get_new_latest_blockhash()
+            .await
+            .unwrap();
+        let to = Keypair::new();
+        let message = format!("{},1000000,,{}", to.pubkey(), clock.unix_timestamp + 60);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_lamports_using_ed25519_signature(
+                    &bank_account_pda,
+                    &message_signer.pubkey(),
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&message_signer.pubkey()),
+            &[&message_signer, &to],
+            recent_blockhash
+        );
+        context.banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap();
+
+        clock.unix_timestamp += 100;
+        context.set_sysvar(&clock);
+    };
+
+    let rent = context.banks_client
+        .get_rent()
+        .await
+        .unwrap();
+    let bank_account_before = context.banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let bank_account_info_before = UserBankAccount::try_from_slice(
+        &bank_account_before.data
+    ).unwrap();
+    assert_eq!(bank_account_info_before.signatures.len(), 3usize);
+
+    let authority_balance_before = context.banks_client
+        .get_balance(message_signer.pubkey())
+        .await
+        .unwrap();
+
+    // the two oldest signatures are before the cutoff, all three messages expired by now
+    let recent_blockhash = context
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_reclaim_signature_rent(
+                &message_signer.pubkey(),
+                &bank_account_pda,
+                &program_id,
+                first_signature_time + 150
+            )
+        ],
+        Some(&message_signer.pubkey()),
+        &[&message_signer],
+        recent_blockhash
+    );
+    let tx_fee = context.banks_client
+        .get_fee_for_message(tx.message.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    context.banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account_after = context.banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let bank_account_info_after = UserBankAccount::try_from_slice(
+        &bank_account_after.data
+    ).unwrap();
+
+    assert_eq!(
+        bank_account_info_after.signatures,
+        bank_account_info_before.signatures[2..].to_vec(),
+        "Remaining signatures mismatch."
+    );
+    assert_eq!(
+        bank_account_after.data.len(),
+        bank_account_info_after.try_to_vec().unwrap().len(),
+        "Bank-account wasn't shrunk."
+    );
+    assert!(bank_account_after.data.len() < bank_account_before.data.len());
+
+    let freed_rent = rent.minimum_balance(bank_account_before.data.len()) - rent.minimum_balance(bank_account_after.data.len());
+    assert_eq!(
+        bank_account_after.lamports,
+        bank_account_before.lamports - freed_rent,
+        "Bank-account balance mismatch."
+    );
+    assert_eq!(
+        context.banks_client.get_balance(message_signer.pubkey()).await.unwrap(),
+        authority_balance_before + freed_rent - tx_fee,
+        "Authority refund mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_fail_destination_mint_mismatch() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        _
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let other_mint_account = Pubkey::new_unique();
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &other_mint_account,
+        &Pubkey::new_unique(),
+        0u64
+    );
+    let other_mint_token_account = Pubkey::new_unique();
+    setup_new_token_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &other_mint_account,
+        &other_mint_token_account,
+        &authority.pubkey(),
+        0u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_spl_tokens(
+                &authority.pubkey(),
+                &bank_account,
+                &bank_account_token_account,
+                &mint_account,
+                &other_mint_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                None,
+                &program_id,
+                &100_00u64,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::DestinationMintMismatch as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &bank_account_token_account).await,
+        1000_00u64,
+        "Bank-account token balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_signed_decimals() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let new_withdraw_tx = |decimals: u8| {
+        let message = format!("{},{},10000,{},,{}", to.pubkey(), mint_account, decimals, MESSAGE_EXPIRES_AT);
+
+        Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_spl_tokens_using_ed25519_signature(
+                    &mint_account,
+                    &bank_account,
+                    &bank_account_token_account,
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &to_token_account,
+                    &TOKEN_STANDARD_PROGRAM,
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[
+                &payer,
+                &to
+            ],
+            recent_blockhash
+        )
+    };
+
+    // the mint has 2 decimals
+    let error = banks_client
+        .process_transaction(new_withdraw_tx(6))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::DecimalsMismatch as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &to_token_account).await,
+        0u64,
+        "Destination token balance mismatch."
+    );
+
+    banks_client
+        .process_transaction(new_withdraw_tx(2))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, &to_token_account).await,
+        100_00u64,
+        "Destination token balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_create_bank_ata_then_withdraw_spl_tokens() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let mint_authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &mint_authority.pubkey(),
+        0u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = payer;
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    let bank_account = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    let bank_account_token_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &bank_account,
+        &mint_account,
+        &TOKEN_STANDARD_PROGRAM
+    );
+
+    let to_token_account = Keypair::new();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_bank_ata(
+                &bank_account_owner.pubkey(),
+                &bank_account,
+                &mint_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &program_id
+            ),
+            spl_token::instruction::mint_to(
+                &TOKEN_STANDARD_PROGRAM,
+                &mint_account,
+                &bank_account_token_account,
+                &mint_authority.pubkey(),
+                &[],
+                1000_00u64
+            ).unwrap(),
+            create_solana_account(
+                &bank_account_owner.pubkey(),
+                &to_token_account.pubkey(),
+                Rent::default().minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &TOKEN_STANDARD_PROGRAM
+            ),
+            initialize_account3(
+                &TOKEN_STANDARD_PROGRAM,
+                &to_token_account.pubkey(),
+                &mint_account,
+                &bank_account_owner.pubkey()
+            ).unwrap(),
+            create_withdraw_spl_tokens(
+                &bank_account_owner.pubkey(),
+                &bank_account,
+                &bank_account_token_account,
+                &mint_account,
+                &to_token_account.pubkey(),
+                &TOKEN_STANDARD_PROGRAM,
+                None,
+                &program_id,
+                &250_00u64,
+                None
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[
+            &bank_account_owner,
+            &mint_authority,
+            &to_token_account
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, &bank_account_token_account).await,
+        750_00u64,
+        "Bank-account token balance mismatch."
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &to_token_account.pubkey()).await,
+        250_00u64,
+        "Destination token balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_create_bank_ata_fail_invalid_associated_token_program() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let mint_account = Pubkey::new_unique();
+    setup_new_mint_account(
+        &mut pt,
+        &TOKEN_STANDARD_PROGRAM,
+        &mint_account,
+        &Pubkey::new_unique(),
+        0u64
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    let bank_account = get_bank_account_pda(&payer.pubkey(), &program_id);
+
+    let mut ix = create_bank_ata(
+        &payer.pubkey(),
+        &bank_account,
+        &mint_account,
+        &TOKEN_STANDARD_PROGRAM,
+        &program_id
+    );
+    // associated-token-account program
+    ix.accounts[6].pubkey = Pubkey::new_unique();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InvalidAssociatedTokenProgram as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_empty_memo_skips_extra_memo_account() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        relayer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            gasless: true,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let to = Pubkey::new_unique();
+    let message = format!("{},1500000000,,{}", to, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_gasless_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &relayer.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                Some(&MEMO_PROGRAM_ID),
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to_balance = banks_client
+        .get_balance(to)
+        .await
+        .unwrap();
+    assert_eq!(
+        to_balance,
+        1500000000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_memo_without_memo_account() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        relayer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+    update_bank_account_config(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        &BankAccountConfig {
+            gasless: true,
+            ..BankAccountConfig::default()
+        },
+        recent_blockhash
+    ).await;
+
+    let to = Pubkey::new_unique();
+    let message = format!("{},1500000000,hello,{}", to, MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_gasless_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &relayer.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::InvalidMemoProgramAccount as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_freeze_account_blocks_withdrawals_until_unfrozen() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let lamports_message = format!("{},100000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+    let tokens_message = format!("{},{},10000,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+    let new_withdraw_txs = |blockhash: Hash| {
+        vec![
+            Transaction::new_signed_with_payer(
+                &[
+                    create_withdraw_lamports(
+                        &authority.pubkey(),
+                        &bank_account,
+                        &to.pubkey(),
+                        None,
+                        &program_id,
+                        &100_000_000u64,
+                        None
+                    )
+                ],
+                Some(&payer.pubkey()),
+                &[&payer, &authority],
+                blockhash
+            ),
+            Transaction::new_signed_with_payer(
+                &[
+                    create_withdraw_spl_tokens(
+                        &authority.pubkey(),
+                        &bank_account,
+                        &bank_account_token_account,
+                        &mint_account,
+                        &to_token_account,
+                        &TOKEN_STANDARD_PROGRAM,
+                        None,
+                        &program_id,
+                        &100_00u64,
+                        None
+                    )
+                ],
+                Some(&payer.pubkey()),
+                &[&payer, &authority],
+                blockhash
+            ),
+            Transaction::new_signed_with_payer(
+                &[
+                    new_ed25519_signature_verification_instruction(&authority, &lamports_message),
+                    create_withdraw_lamports_using_ed25519_signature(
+                        &bank_account,
+                        &payer.pubkey(),
+                        &to.pubkey(),
+                        &to.pubkey(),
+                        &SYSTEM_PROGRAM_ID,
+                        None,
+                        None,
+                        None,
+                        &program_id
+                    )
+                ],
+                Some(&payer.pubkey()),
+                &[&payer, &to],
+                blockhash
+            ),
+            Transaction::new_signed_with_payer(
+                &[
+                    new_ed25519_signature_verification_instruction(&authority, &tokens_message),
+                    create_withdraw_spl_tokens_using_ed25519_signature(
+                        &mint_account,
+                        &bank_account,
+                        &bank_account_token_account,
+                        &payer.pubkey(),
+                        &to.pubkey(),
+                        &to_token_account,
+                        &TOKEN_STANDARD_PROGRAM,
+                        &SYSTEM_PROGRAM_ID,
+                        None,
+                        None,
+                        &program_id
+                    )
+                ],
+                Some(&payer.pubkey()),
+                &[&payer, &to],
+                blockhash
+            )
+        ]
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_freeze_account(
+                &authority.pubkey(),
+                &bank_account,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &authority],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    for (index, tx) in new_withdraw_txs(recent_blockhash).into_iter().enumerate() {
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        // the ed25519 withdrawals are the second instruction of their transaction
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                if index < 2 { 0 } else { 1 },
+                InstructionError::Custom(
+                    BankError::AccountFrozen as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+
+    // the other instructions moving funds out of the bank-account are blocked as well
+    let frozen_only_instructions = [
+        create_close_bank_account_full(
+            &authority.pubkey(),
+            &bank_account,
+            &TOKEN_STANDARD_PROGRAM,
+            &[(bank_account_token_account, Some((mint_account, to_token_account)))],
+            &program_id,
+            None
+        ),
+        create_sweep_multiple_atas(
+            &authority.pubkey(),
+            &bank_account,
+            &TOKEN_STANDARD_PROGRAM,
+            &[(mint_account, bank_account_token_account, to_token_account)],
+            &program_id,
+            None
+        ),
+        create_emergency_withdraw(
+            &authority.pubkey(),
+            &bank_account,
+            &to.pubkey(),
+            &[],
+            &program_id,
+            100_000_000,
+            None
+        ),
+        create_init_payment_escrow(
+            &payer.pubkey(),
+            &authority.pubkey(),
+            &bank_account,
+            &PaymentEscrow::get_payment_escrow_using_fpa(&bank_account, 1, &program_id).0,
+            &SYSTEM_PROGRAM_ID,
+            &program_id,
+            1,
+            100_000_000,
+            &to.pubkey()
+        )
+    ];
+    for ix in frozen_only_instructions {
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, &authority],
+            recent_blockhash
+        );
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    BankError::AccountFrozen as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_unfreeze_account(
+                &authority.pubkey(),
+                &bank_account,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &authority],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    for tx in new_withdraw_txs(recent_blockhash) {
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap();
+    };
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, &to_token_account).await,
+        200_00u64,
+        "Destination token balance mismatch."
+    );
+    assert_eq!(
+        banks_client.get_balance(to.pubkey()).await.unwrap(),
+        sol_to_lamports(1.0) + 200_000_000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_relayed_using_ed25519_fail_unversioned_v2_message() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    // a token authorization (V2 without decimals) whose mint keypair is held by an attacker,
+    // read as V3 the mint would be the relayer and the token amount the lamports
+    let mint = Keypair::new();
+    let to = Pubkey::new_unique();
+    let message = format!("{},{},1500000000,,{}", to, mint.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            transfer_lamports(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                LAMPORTS_PER_SOL
+            ),
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_relayed_using_ed25519_signature(
+                &bank_account_pda,
+                &mint.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &mint],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(
+                BankError::MessageV3ValidationFailed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+    assert_eq!(
+        banks_client.get_balance(to).await.unwrap(),
+        0,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_relayed_using_ed25519_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        relayer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    // the recipient never signs, the relayer named by the message submits the withdrawal
+    let to = Pubkey::new_unique();
+    let message = format!("v3,{},{},1500000000,,{}", to, relayer.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let other_relayer = Keypair::new();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            transfer_lamports(
+                &relayer.pubkey(),
+                &other_relayer.pubkey(),
+                LAMPORTS_PER_SOL
+            ),
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_relayed_using_ed25519_signature(
+                &bank_account_pda,
+                &other_relayer.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer, &other_relayer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(
+                BankError::InvalidRelayer as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_relayed_using_ed25519_signature(
+                &bank_account_pda,
+                &relayer.pubkey(),
+                &to,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to_balance = banks_client
+        .get_balance(to)
+        .await
+        .unwrap();
+    assert_eq!(
+        to_balance,
+        1500000000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_and_close_ata_using_ed25519_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let funder = Keypair::new();
+    pt.add_account(
+        funder.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(1.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_token_account_rent = banks_client.get_balance(bank_account_token_account).await.unwrap();
+    let bank_account_balance_before = banks_client.get_balance(bank_account).await.unwrap();
+    let funder_balance_before = banks_client.get_balance(funder.pubkey()).await.unwrap();
+
+    // the whole token balance is withdrawn
+    let message = format!("{},{},100000,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_spl_tokens_and_close_ata_using_ed25519_signature(
+                &mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &funder.pubkey(),
+                &to.pubkey(),
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &funder,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, &to_token_account).await,
+        1000_00u64,
+        "Destination token balance mismatch."
+    );
+    assert!(
+        banks_client.get_account(bank_account_token_account).await.unwrap().is_none(),
+        "Bank-account's associated-token-account isn't closed."
+    );
+
+    // the funder paid the growth of the bank-account for the signature entry and got the A.T.A's rent back
+    let signature_entry_rent = banks_client.get_balance(bank_account).await.unwrap() - bank_account_balance_before;
+    assert_eq!(
+        banks_client.get_balance(funder.pubkey()).await.unwrap(),
+        funder_balance_before + bank_account_token_account_rent - signature_entry_rent,
+        "Funder balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_cheap_checks() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let message = format!("{},{},10000,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+    let other_withdrawer = Keypair::new();
+    let new_withdraw_tx = |withdrawer: &Keypair, token_program: &Pubkey, system_program: &Pubkey| {
+        Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_spl_tokens_using_ed25519_signature(
+                    &mint_account,
+                    &bank_account,
+                    &bank_account_token_account,
+                    &payer.pubkey(),
+                    &withdrawer.pubkey(),
+                    &to_token_account,
+                    token_program,
+                    system_program,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[
+                &payer,
+                withdrawer
+            ],
+            recent_blockhash
+        )
+    };
+
+    for (tx, expected_error) in [
+        (
+            new_withdraw_tx(&to, &TOKEN_STANDARD_PROGRAM, &Pubkey::new_unique()),
+            BankError::InvalidSystemProgramAccount
+        ),
+        (
+            new_withdraw_tx(&to, &Pubkey::new_unique(), &SYSTEM_PROGRAM_ID),
+            BankError::InvalidSplTokenProgramAccount
+        ),
+        (
+            new_withdraw_tx(&other_withdrawer, &TOKEN_STANDARD_PROGRAM, &SYSTEM_PROGRAM_ID),
+            BankError::InvalidToPubkey
+        )
+    ] {
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(
+                    expected_error as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, &to_token_account).await,
+        0u64,
+        "Destination token balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_without_funder_uses_bank_account_slack() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let bank_account_balance_before = banks_client.get_balance(bank_account_pda).await.unwrap();
+    let bank_account_size_before = banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data.len();
+
+    // no funder signs -> the generously funded bank-account covers its own growth
+    let funder = Pubkey::new_unique();
+    let to = Keypair::new();
+    let message = format!("{},1500000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+    let mut withdraw_ix = create_withdraw_lamports_using_ed25519_signature(
+        &bank_account_pda,
+        &funder,
+        &to.pubkey(),
+        &to.pubkey(),
+        &SYSTEM_PROGRAM_ID,
+        None,
+        None,
+        None,
+        &program_id
+    );
+    withdraw_ix.accounts[1].is_signer = false;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            withdraw_ix
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(to.pubkey()).await.unwrap(),
+        1500000000,
+        "Recepient balance mismatch."
+    );
+    assert_eq!(
+        banks_client.get_balance(bank_account_pda).await.unwrap(),
+        bank_account_balance_before - 1500000000,
+        "Bank-Account balance mismatch."
+    );
+    assert!(
+        banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data.len() > bank_account_size_before,
+        "Bank-Account didn't grow."
+    );
+    assert_eq!(
+        banks_client.get_balance(funder).await.unwrap(),
+        0,
+        "Funder balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_fail_invalid_mint_data() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        _,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    // a plain system account is passed as the mint
+    let not_a_mint_account = payer.pubkey();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_spl_tokens(
+                &authority.pubkey(),
+                &bank_account,
+                &spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &bank_account,
+                    &not_a_mint_account,
+                    &TOKEN_STANDARD_PROGRAM
+                ),
+                &not_a_mint_account,
+                &to_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                None,
+                &program_id,
+                &100_00u64,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InvalidMintData as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_fail_invalid_token_account_data() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        _
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    // a plain system account is passed as the destination token-account
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_spl_tokens(
+                &authority.pubkey(),
+                &bank_account,
+                &bank_account_token_account,
+                &mint_account,
+                &authority.pubkey(),
+                &TOKEN_STANDARD_PROGRAM,
+                None,
+                &program_id,
+                &100_00u64,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &authority
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::InvalidTokenAccountData as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &bank_account_token_account).await,
+        1000_00u64,
+        "Bank-account token balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_memo_too_long() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    // one byte over what the memo program accepts
+    let to = Keypair::new();
+    let message = format!("{},1500000000,{},{}", to.pubkey(), "m".repeat(567), MESSAGE_EXPIRES_AT);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                Some(&MEMO_PROGRAM_ID),
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[
+            &payer,
+            &to
+        ],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::MemoTooLong as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_fail_memo_too_long() {
+    let program_id = Pubkey::new_from_array([5; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = payer;
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    let bank_account_pda = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    fund_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &bank_account_pda,
+        LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    // the direct withdrawal forwards the memo to the memo program as-is
+    let memo = "m".repeat(567);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_withdraw_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_pda,
+                &Pubkey::new_unique(),
+                None,
+                &program_id,
+                &100_000_000u64,
+                Some(&memo)
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::MemoTooLong as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_dry_run_signature_insufficient_balance() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Pubkey::new_unique();
+    let too_much_message = format!("{},{},,{}", to, 2 * LAMPORTS_PER_SOL, MESSAGE_EXPIRES_AT);
+    assert_eq!(
+        dry_run_signature(&mut banks_client, &payer, &message_signer, &too_much_message, &bank_account_pda, None, &program_id, recent_blockhash).await,
+        false,
+        "Dry-run result mismatch."
+    );
+
+    let covered_message = format!("{},{},,{}", to, LAMPORTS_PER_SOL / 2, MESSAGE_EXPIRES_AT);
+    assert_eq!(
+        dry_run_signature(&mut banks_client, &payer, &message_signer, &covered_message, &bank_account_pda, None, &program_id, recent_blockhash).await,
+        true,
+        "Dry-run result mismatch."
+    );
+
+    // nothing was recorded
+    let bank_account_data = banks_client
+        .get_account(bank_account_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let bank_account_info = try_from_slice_unchecked::<UserBankAccount>(
+        &bank_account_data
+    ).unwrap();
+    assert_eq!(bank_account_info.signatures.len(), 0, "Signatures count mismatch.");
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_daily_limit_boundary() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        30 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let set_daily_limit = |daily_limit: u64| {
+        Transaction::new_signed_with_payer(
+            &[
+                create_set_daily_limit(
+                    &message_signer.pubkey(),
+                    &bank_account_pda,
+                    daily_limit,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &message_signer],
+            recent_blockhash
+        )
+    };
+    let to = Keypair::new();
+    let other_to = Keypair::new();
+    let withdraw = |to: &Keypair, lamports: u64| {
+        let message = format!("{},{},,{}", to.pubkey(), lamports, MESSAGE_EXPIRES_AT);
+        Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_lamports_using_ed25519_signature(
+                    &bank_account_pda,
+                    &message_signer.pubkey(),
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &message_signer, to],
+            recent_blockhash
+        )
+    };
+
+    banks_client
+        .process_transaction(set_daily_limit(LAMPORTS_PER_SOL))
+        .await
+        .unwrap();
+
+    banks_client
+        .process_transaction(withdraw(&to, 600_000_000))
+        .await
+        .unwrap();
+    // exactly reaches the limit
+    banks_client
+        .process_transaction(withdraw(&to, 400_000_000))
+        .await
+        .unwrap();
+
+    let error = banks_client
+        .process_transaction(withdraw(&to, MIN_ED25519_WITHDRAW_LAMPORTS))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::DailyLimitExceeded as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data[..]
+    ).unwrap();
+    assert_eq!(bank_account.daily_limit, LAMPORTS_PER_SOL, "Daily limit mismatch.");
+    assert_eq!(bank_account.daily_withdrawals.len(), 2, "Daily withdrawals count mismatch.");
+
+    // the limit is per recipient -> another recipient still gets its own
+    banks_client
+        .process_transaction(withdraw(&other_to, LAMPORTS_PER_SOL))
+        .await
+        .unwrap();
+    assert_eq!(
+        banks_client.get_balance(other_to.pubkey()).await.unwrap(),
+        LAMPORTS_PER_SOL,
+        "Recepient balance mismatch."
+    );
+
+    // lifting the limit lets the withdrawal through
+    banks_client
+        .process_transaction(set_daily_limit(0))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(withdraw(&to, MIN_ED25519_WITHDRAW_LAMPORTS + 1))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(to.pubkey()).await.unwrap(),
+        LAMPORTS_PER_SOL + MIN_ED25519_WITHDRAW_LAMPORTS + 1,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_set_label() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &payer,
+        &program_id,
+        recent_blockhash
+    ).await;
+
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data[..]
+    ).unwrap();
+    assert_eq!(bank_account.label, [0u8; 32], "Label mismatch.");
+
+    let mut label = [0u8; 32];
+    label[..7].copy_from_slice("savings".as_bytes());
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_set_label(
+                &payer.pubkey(),
+                &bank_account_pda,
+                label,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data[..]
+    ).unwrap();
+    assert_eq!(bank_account.label, label, "Label mismatch.");
+}
+
+#[tokio::test]
+async fn test_create_bank_account_with_index() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    // e.g. savings and checking
+    let mut bank_accounts = vec![];
+    for index in [1u16, 2u16] {
+        let bank_account_pda = UserBankAccount::get_user_bank_account_with_index(
+            &message_signer.pubkey(),
+            index,
+            &program_id
+        ).0;
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                create_create_bank_account_with_index_instruction(
+                    &message_signer.pubkey(),
+                    &message_signer.pubkey(),
+                    &bank_account_pda,
+                    &SYSTEM_PROGRAM_ID,
+                    &get_program_config_pda(&program_id),
+                    index,
+                    &program_id
+                )
+            ],
+            Some(&message_signer.pubkey()),
+            &[&message_signer],
+            recent_blockhash
+        );
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap();
+
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+            &banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data[..]
+        ).unwrap();
+        assert_eq!(bank_account.authority, message_signer.pubkey(), "Authority mismatch.");
+        assert_eq!(bank_account.seed_index, Some(index), "Seed index mismatch.");
+
+        bank_accounts.push(bank_account_pda);
+    };
+    assert_ne!(bank_accounts[0], bank_accounts[1], "Bank-accounts must differ.");
+    assert_ne!(
+        bank_accounts[0],
+        get_bank_account_pda(&message_signer.pubkey(), &program_id),
+        "Indexed bank-account must differ from the default one."
+    );
+
+    let savings_pda = bank_accounts[0];
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &savings_pda,
+        10 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    // withdraw validators accept the index-based seeds
+    let to = Keypair::new();
+    let message = format!("{},{},,{}", to.pubkey(), LAMPORTS_PER_SOL, MESSAGE_EXPIRES_AT);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_lamports_using_ed25519_signature(
+                &savings_pda,
+                &message_signer.pubkey(),
+                &to.pubkey(),
+                &to.pubkey(),
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            ),
+            create_withdraw_lamports(
+                &message_signer.pubkey(),
+                &savings_pda,
+                &to.pubkey(),
+                None,
+                &program_id,
+                &LAMPORTS_PER_SOL,
+                None
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &message_signer, &to],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(to.pubkey()).await.unwrap(),
+        2 * LAMPORTS_PER_SOL,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_sequential_signatures_resize_bank_account() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        10 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    let mut previous_size = banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data.len();
+    for (index, lamports) in [1_000_000_000u64, 2_000_000_000u64].into_iter().enumerate() {
+        let message = format!("{},{},,{}", to.pubkey(), lamports, MESSAGE_EXPIRES_AT);
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_lamports_using_ed25519_signature(
+                    &bank_account_pda,
+                    &message_signer.pubkey(),
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &message_signer, &to],
+            recent_blockhash
+        );
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap();
+
+        let bank_account_info = banks_client.get_account(bank_account_pda).await.unwrap().unwrap();
+        let bank_account = try_from_slice_unchecked::<UserBankAccount>(&bank_account_info.data[..]).unwrap();
+
+        assert_eq!(bank_account.signatures.len(), index + 1, "Signatures count mismatch.");
+        assert_eq!(
+            bank_account_info.data.len(),
+            bank_account.try_to_vec().unwrap().len(),
+            "Bank-Account size mismatch."
+        );
+        assert!(bank_account_info.data.len() > previous_size, "Bank-Account didn't grow.");
+        assert!(
+            bank_account_info.lamports >= rent.minimum_balance(bank_account_info.data.len()),
+            "Bank-Account isn't rent-exempt."
+        );
+
+        previous_size = bank_account_info.data.len();
+    };
+
+    assert_eq!(
+        banks_client.get_balance(to.pubkey()).await.unwrap(),
+        3_000_000_000,
+        "Recepient balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_fail_self_withdrawal() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    // the bank-account can't sign -> only reachable as a gasless withdrawal
+    let message = format!("{},1000000,,{}", bank_account_pda, MESSAGE_EXPIRES_AT);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_gasless_withdraw_lamports_using_ed25519_signature(
+                &bank_account_pda,
+                &message_signer.pubkey(),
+                &bank_account_pda,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &message_signer],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SelfWithdrawalNotAllowed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_spl_tokens_using_ed25519_fail_self_withdrawal() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        _
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let message = format!("{},{},10000,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &message),
+            create_withdraw_spl_tokens_using_ed25519_signature(
+                &mint_account,
+                &bank_account,
+                &bank_account_token_account,
+                &payer.pubkey(),
+                &to.pubkey(),
+                &bank_account_token_account,
+                &TOKEN_STANDARD_PROGRAM,
+                &SYSTEM_PROGRAM_ID,
+                None,
+                None,
+                &program_id
+            )
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &to],
+        recent_blockhash
+    );
+
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::SelfWithdrawalNotAllowed as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, &bank_account_token_account).await,
+        1000_00u64,
+        "Bank-Account token balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_create_bank_account_through_cpi_fail_already_exists() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let forwarder_program_id = Pubkey::new_unique();
+    let mut pt = setup(&program_id);
+    pt.add_program(
+        "cpi_forwarder",
+        forwarder_program_id,
+        processor!(cpi_forwarder_processor)
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&payer.pubkey(), &program_id);
+    let new_forwarded_create_tx = |blockhash: Hash| {
+        let bank_ix = create_create_initialize_bank_account_instruction(
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &bank_account_pda,
+            &SYSTEM_PROGRAM_ID,
+            &program_id
+        );
+        let mut forwarded_accounts = vec![
+            AccountMeta::new_readonly(program_id, false)
+        ];
+        forwarded_accounts.extend(bank_ix.accounts);
+
+        Transaction::new_signed_with_payer(
+            &[
+                Instruction {
+                    program_id: forwarder_program_id,
+                    accounts: forwarded_accounts,
+                    data: bank_ix.data
+                }
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            blockhash
+        )
+    };
+
+    banks_client
+        .process_transaction(new_forwarded_create_tx(recent_blockhash))
+        .await
+        .unwrap();
+
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data[..]
+    ).unwrap();
+    assert_eq!(bank_account.authority, payer.pubkey(), "Authority mismatch.");
+
+    // the caller gets the error back instead of a panic
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let error = banks_client
+        .process_transaction(new_forwarded_create_tx(recent_blockhash))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidAccountData
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_and_unwrap_sol_success() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let pt = setup(&program_id);
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_owner = payer;
+    setup_new_bank_account(
+        &mut banks_client,
+        &bank_account_owner,
+        &program_id,
+        recent_blockhash
+    ).await;
+    let bank_account = get_bank_account_pda(&bank_account_owner.pubkey(), &program_id);
+    let bank_account_token_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &bank_account,
+        &spl_token::native_mint::ID,
+        &TOKEN_STANDARD_PROGRAM
+    );
+
+    // wrap 2 SOL into bank-account's wSOL A.T.A
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_bank_ata(
+                &bank_account_owner.pubkey(),
+                &bank_account,
+                &spl_token::native_mint::ID,
+                &TOKEN_STANDARD_PROGRAM,
+                &program_id
+            ),
+            transfer_lamports(
+                &bank_account_owner.pubkey(),
+                &bank_account_token_account,
+                2 * LAMPORTS_PER_SOL
+            ),
+            spl_token::instruction::sync_native(
+                &TOKEN_STANDARD_PROGRAM,
+                &bank_account_token_account
+            ).unwrap()
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    let to = Keypair::new();
+    let to_token_account = spl_associated_token_account::get_associated_token_address(
+        &to.pubkey(),
+        &spl_token::native_mint::ID
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_associated_token_account(
+                &bank_account_owner.pubkey(),
+                &to.pubkey(),
+                &spl_token::native_mint::ID,
+                &TOKEN_STANDARD_PROGRAM
+            ),
+            create_withdraw_and_unwrap_sol(
+                &bank_account_owner.pubkey(),
+                &bank_account,
+                &bank_account_token_account,
+                &to_token_account,
+                &to.pubkey(),
+                LAMPORTS_PER_SOL / 2,
+                &program_id
+            )
+        ],
+        Some(&bank_account_owner.pubkey()),
+        &[&bank_account_owner, &to],
+        recent_blockhash
+    );
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert!(
+        banks_client.get_account(to_token_account).await.unwrap().is_none(),
+        "Recipient's wSOL token-account wasn't closed."
+    );
+    assert_eq!(
+        banks_client.get_balance(to.pubkey()).await.unwrap(),
+        LAMPORTS_PER_SOL / 2 + Rent::default().minimum_balance(TokenAccount::LEN),
+        "Recepient balance mismatch."
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &bank_account_token_account).await,
+        2 * LAMPORTS_PER_SOL - LAMPORTS_PER_SOL / 2,
+        "Bank-Account token balance mismatch."
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_and_unwrap_sol_fail_not_native_mint() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let authority = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
+        &mut pt,
+        &program_id,
+        &authority.pubkey(),
+        &to.pubkey(),
+        &mint_account,
+        1000_00u64,
+        BankAccountConfig::default()
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let mut withdraw_ix = create_withdraw_and_unwrap_sol(
+        &authority.pubkey(),
+        &bank_account,
+        &bank_account_token_account,
+        &to_token_account,
+        &to.pubkey(),
+        100_00u64,
+        &program_id
+    );
+    withdraw_ix.accounts[3].pubkey = mint_account;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &authority, &to],
+        recent_blockhash
+    );
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(
+                BankError::NotNativeMint as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_signature_at_multi_signature_instruction() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        10 * LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let first_to = Keypair::new();
+    let second_to = Keypair::new();
+    let first_message = format!("{},1000000000,,{}", first_to.pubkey(), MESSAGE_EXPIRES_AT);
+    let second_message = format!("{},2000000000,,{}", second_to.pubkey(), MESSAGE_EXPIRES_AT);
+
+    let new_withdraw_ix = |to: &Keypair, signature_index: u8| {
+        create_withdraw_lamports_using_ed25519_signature_at(
+            &bank_account_pda,
+            &message_signer.pubkey(),
+            &to.pubkey(),
+            &to.pubkey(),
+            &SYSTEM_PROGRAM_ID,
+            None,
+            None,
+            None,
+            signature_index,
+            &program_id
+        )
+    };
+
+    // offsets headers first, then (pubkey, signature, message) of each signature
+    let mut offsets = vec![];
+    let mut payload = vec![];
+    let mut field_offset = 2 + 2 * 14;
+    for message in [&first_message, &second_message] {
+        let message_size = message.len() as u16;
+        offsets.push([field_offset + 32, u16::MAX, field_offset, u16::MAX, field_offset + 96, message_size, u16::MAX]);
+
+        payload.extend_from_slice(message_signer.pubkey().as_ref());
+        payload.extend_from_slice(&sign_ed25519_message(&message_signer, message.as_bytes()));
+        payload.extend_from_slice(message.as_bytes());
+        field_offset += 96 + message_size;
+    };
+
+    let tx = Transaction::new_signed_with_payer(
         &[
-            bank_account.to_bytes().as_slice(),
-            TOKEN_STANDARD_PROGRAM.to_bytes().as_slice(),
-            mint_account.to_bytes().as_slice()
+            new_raw_ed25519_instruction(&offsets, &payload),
+            new_withdraw_ix(&first_to, 0),
+            new_withdraw_ix(&second_to, 1)
         ],
-        &ASSOCIATED_TOKEN_PROGRAM
-    ).unwrap().0;
-    
-    setup_new_mint_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &Keypair::new().pubkey(),
-        1000_00u64
+        Some(&payer.pubkey()),
+        &[&payer, &message_signer, &first_to, &second_to],
+        recent_blockhash
     );
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &signer_token_account,
-        &message_signer.pubkey(),
-        500_00u64
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(first_to.pubkey()).await.unwrap(),
+        1_000_000_000,
+        "Recepient balance mismatch."
     );
-    setup_new_token_account(
-        &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
-        &mint_account,
-        &to_token_account,
-        &to.pubkey(),
-        300_00u64
+    assert_eq!(
+        banks_client.get_balance(second_to.pubkey()).await.unwrap(),
+        2_000_000_000,
+        "Recepient balance mismatch."
     );
-    setup_new_token_account(
+
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data[..]
+    ).unwrap();
+    assert_eq!(bank_account.signature_stats(), (2, 0), "Signature stats mismatch.");
+
+    // no third signature in the verification instruction
+    let third_message = format!("{},3000000000,,{}", first_to.pubkey(), MESSAGE_EXPIRES_AT);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            new_ed25519_signature_verification_instruction(&message_signer, &third_message),
+            new_withdraw_ix(&first_to, 1)
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &message_signer, &first_to],
+        recent_blockhash
+    );
+    let error = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(
+                BankError::InvalidEd25519SignatureVerificationInstruction as u32
+            )
+        ),
+        "Mismatch error types!"
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw_using_ed25519_fail_bank_account_as_funder() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    let to = Keypair::new();
+    let mint_account = Pubkey::new_unique();
+    let (
+        bank_account,
+        bank_account_token_account,
+        to_token_account
+    ) = setup_token_bank_account(
         &mut pt,
-        &TOKEN_STANDARD_PROGRAM,
+        &program_id,
+        &message_signer.pubkey(),
+        &to.pubkey(),
         &mint_account,
-        &bank_account_token_account,
-        &bank_account,
-        200_00u64
+        1000_00u64,
+        BankAccountConfig::default()
     );
 
     let (
@@ -2293,100 +14039,190 @@ async fn test_withdraw_spl_tokens_using_ed25519_success_3() {
         recent_blockhash
     ) = pt.start().await;
 
-    let _message: String = String::from(
-        "4fvvYqkcG5izqVNuMKyR4ChC7wAbwee7xG1DJW7DjDPZ,CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,50000,PooriaGG 🤩"
+    let lamports_message = format!("{},100000000,,{}", to.pubkey(), MESSAGE_EXPIRES_AT);
+    let mut lamports_withdraw_ix = create_withdraw_lamports_using_ed25519_signature(
+        &bank_account,
+        &bank_account,
+        &to.pubkey(),
+        &to.pubkey(),
+        &SYSTEM_PROGRAM_ID,
+        None,
+        None,
+        None,
+        &program_id
     );
+    lamports_withdraw_ix.accounts[1].is_signer = false;
 
-    let ed25519_signature_verification_instruction = Instruction {
-        program_id: solana_sdk::ed25519_program::ID,
-        accounts: vec![],
-        data: vec![
-            1,0,48,0,255,255,16,0,255,255,112,0,108,0,255,255,187,220,42,181,173,60,36,199,230,65,125,124,22,8,191,157,169,169,
-            85,113,83,0,79,147,213,225,127,24,199,48,221,200,55,78,28,211,84,230,134,173,195,114,9,205,40,231,223,7,61,8,212,64,
-            156,21,81,42,211,136,180,255,112,43,54,224,245,21,81,14,97,192,71,63,231,247,247,138,35,155,250,190,29,223,228,80,
-            108,120,155,234,213,192,204,191,136,173,124,3,52,102,118,118,89,113,107,99,71,53,105,122,113,86,78,117,77,75,121,82,
-            52,67,104,67,55,119,65,98,119,101,101,55,120,71,49,68,74,87,55,68,106,68,80,90,44,67,107,116,82,117,81,50,109,116,116,
-            103,82,71,107,88,74,116,121,107,115,100,75,72,106,85,100,99,50,67,52,84,103,68,122,121,66,57,56,111,69,122,121,56,44,
-            53,48,48,48,48,44,80,111,111,114,105,97,71,71,32,240,159,164,169
-        ]
-    };
-
-    let withdraw_spl_tokens_using_ed25519 = create_withdraw_spl_tokens_using_ed25519_signature(
+    let tokens_message = format!("{},{},10000,,{}", to.pubkey(), mint_account, MESSAGE_EXPIRES_AT);
+    let mut tokens_withdraw_ix = create_withdraw_spl_tokens_using_ed25519_signature(
         &mint_account,
         &bank_account,
         &bank_account_token_account,
-        &to.pubkey(),
+        &bank_account,
         &to.pubkey(),
         &to_token_account,
         &TOKEN_STANDARD_PROGRAM,
         &SYSTEM_PROGRAM_ID,
-        Some(&MEMO_PROGRAM_ID),
+        None,
+        None,
         &program_id
     );
+    tokens_withdraw_ix.accounts[3].is_signer = false;
+
+    for (message, withdraw_ix) in [(lamports_message, lamports_withdraw_ix), (tokens_message, tokens_withdraw_ix)] {
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                withdraw_ix
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &to],
+            recent_blockhash
+        );
+
+        let error = banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(
+                    BankError::InvalidFunderAccount as u32
+                )
+            ),
+            "Mismatch error types!"
+        );
+    };
+}
+
+#[tokio::test]
+async fn test_withdraw_lamports_using_ed25519_min_withdrawal_threshold() {
+    let program_id = Pubkey::new_from_array([2; 32]);
+    let mut pt = setup(&program_id);
+
+    let message_signer = Keypair::new();
+    pt.add_account(
+        message_signer.pubkey(),
+        SolanaAccount {
+            lamports: sol_to_lamports(100.0),
+            owner: Pubkey::default(),
+            rent_epoch: Epoch::default(),
+            executable: false,
+            data: vec![]
+        }
+    );
+
+    let (
+        mut banks_client,
+        payer,
+        recent_blockhash
+    ) = pt.start().await;
+
+    let bank_account_pda = get_bank_account_pda(&message_signer.pubkey(), &program_id);
+    setup_new_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &program_id,
+        recent_blockhash
+    ).await;
+    fund_bank_account(
+        &mut banks_client,
+        &message_signer,
+        &bank_account_pda,
+        LAMPORTS_PER_SOL,
+        recent_blockhash
+    ).await;
+
+    let to = Keypair::new();
+    let withdraw = |lamports: u64| {
+        let message = format!("{},{},,{}", to.pubkey(), lamports, MESSAGE_EXPIRES_AT);
+        Transaction::new_signed_with_payer(
+            &[
+                new_ed25519_signature_verification_instruction(&message_signer, &message),
+                create_withdraw_lamports_using_ed25519_signature(
+                    &bank_account_pda,
+                    &message_signer.pubkey(),
+                    &to.pubkey(),
+                    &to.pubkey(),
+                    &SYSTEM_PROGRAM_ID,
+                    None,
+                    None,
+                    None,
+                    &program_id
+                )
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &message_signer, &to],
+            recent_blockhash
+        )
+    };
+    let below_minimum_error = TransactionError::InstructionError(
+        1,
+        InstructionError::Custom(
+            BankError::WithdrawalBelowMinimum as u32
+        )
+    );
+
+    // default minimum
+    let error = banks_client
+        .process_transaction(withdraw(MIN_ED25519_WITHDRAW_LAMPORTS - 1))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(error, below_minimum_error, "Mismatch error types!");
+
+    // `to` must end rent-exempt -> the first withdrawal funds it
+    banks_client
+        .process_transaction(withdraw(100_000_000))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(withdraw(MIN_ED25519_WITHDRAW_LAMPORTS))
+        .await
+        .unwrap();
 
+    // bank-account's own minimum
     let tx = Transaction::new_signed_with_payer(
         &[
-            ed25519_signature_verification_instruction.clone(),
-            withdraw_spl_tokens_using_ed25519.clone(),
-            ed25519_signature_verification_instruction.clone(),
-            withdraw_spl_tokens_using_ed25519.clone()
+            create_set_min_withdrawal(
+                &message_signer.pubkey(),
+                &bank_account_pda,
+                1_000_000,
+                &program_id
+            )
         ],
         Some(&payer.pubkey()),
-        &[
-            &payer,
-            &to
-        ],
+        &[&payer, &message_signer],
         recent_blockhash
     );
-
     banks_client
         .process_transaction(tx)
         .await
         .unwrap();
 
-    let bank_account_data = banks_client
-        .get_account(bank_account)
+    let error = banks_client
+        .process_transaction(withdraw(999_999))
         .await
-        .unwrap()
-        .unwrap()
-        .data;
-    let bank_account_info = UserBankAccount::try_from_slice(
-        &bank_account_data
-    ).unwrap();
-
-    let expected_signature: [u8; 64] = ed25519_signature_verification_instruction
-        .data
-        .get(48..112)
-        .unwrap()
-        .try_into()
+        .unwrap_err()
         .unwrap();
+    assert_eq!(error, below_minimum_error, "Mismatch error types!");
 
-    assert!(!bank_account_info.signatures[0].is_ok);
-    assert!(!bank_account_info.signatures[1].is_ok);
-
-    let to_token_account_data = banks_client
-        .get_account(to_token_account)
-        .await
-        .unwrap()
-        .unwrap()
-        .data;
-    let to_token_account_info = TokenAccount::unpack(&to_token_account_data).unwrap();
-
-    let bank_account_tokne_account_data = banks_client
-        .get_account(bank_account_token_account)
+    banks_client
+        .process_transaction(withdraw(1_000_000))
         .await
-        .unwrap()
-        .unwrap()
-        .data;
-    let bank_account_token_account_info = TokenAccount::unpack(&bank_account_tokne_account_data).unwrap();
+        .unwrap();
 
     assert_eq!(
-        to_token_account_info.amount,
-        300_00u64
+        banks_client.get_balance(to.pubkey()).await.unwrap(),
+        100_000_000 + MIN_ED25519_WITHDRAW_LAMPORTS + 1_000_000,
+        "Recepient balance mismatch."
     );
 
-    assert_eq!(
-        bank_account_token_account_info.amount,
-        200_00u64
-    );
-}
\ No newline at end of file
+    let bank_account = try_from_slice_unchecked::<UserBankAccount>(
+        &banks_client.get_account(bank_account_pda).await.unwrap().unwrap().data[..]
+    ).unwrap();
+    assert_eq!(bank_account.signature_stats(), (3, 0), "Rejected withdrawals must not store signatures.");
+}